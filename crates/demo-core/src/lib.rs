@@ -2,9 +2,10 @@ use core::cmp::Ordering;
 use eframe::{App, CreationContext};
 use egui::{self, Align2, CollapsingHeader, Color32, Pos2, Rect, ScrollArea, Ui};
 use egui_graphs::{
-    generate_random_graph, FruchtermanReingoldWithCenterGravity,
-    FruchtermanReingoldWithCenterGravityState, Graph, LayoutForceDirected, LayoutHierarchical,
-    LayoutHierarchicalOrientation, LayoutStateHierarchical,
+    generate_random_graph, EdgeWeighting, FruchtermanReingoldWithBuiltinExtras,
+    FruchtermanReingoldWithBuiltinExtrasState, Graph, LayoutEasing, LayoutForceDirected,
+    LayoutHierarchical, LayoutHierarchicalCrossingHeuristic, LayoutHierarchicalLayering,
+    LayoutHierarchicalMode, LayoutHierarchicalOrientation, LayoutStateHierarchical, RadialMetric,
 };
 use petgraph::stable_graph::{DefaultIx, EdgeIndex, NodeIndex};
 use petgraph::{Directed, Undirected};
@@ -90,6 +91,7 @@ pub struct DemoApp {
     pub settings_interaction: settings::SettingsInteraction,
     pub settings_navigation: settings::SettingsNavigation,
     pub settings_style: settings::SettingsStyle,
+    pub settings_animation: settings::SettingsAnimation,
     pub metrics: MetricsRecorder,
     // UI
     pub show_sidebar: bool,
@@ -182,6 +184,7 @@ impl DemoApp {
                 labels_always: false,
                 edge_deemphasis: true,
             },
+            settings_animation: settings::SettingsAnimation::default(),
             metrics: MetricsRecorder::new(),
             // Start with side panel hidden by default
             show_sidebar: false,
@@ -439,6 +442,7 @@ impl DemoApp {
             labels_always: false,
             edge_deemphasis: true,
         };
+        self.settings_animation = settings::SettingsAnimation::default();
         self.show_debug_overlay = true;
         self.show_keybindings_overlay = false;
         let mut g = generate_random_graph(
@@ -447,7 +451,7 @@ impl DemoApp {
         );
         Self::distribute_nodes_circle_generic(&mut g);
         self.g = DemoGraph::Directed(g);
-        egui_graphs::reset::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+        egui_graphs::reset::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
         egui_graphs::reset::<LayoutStateHierarchical>(ui, None);
         ui.ctx().set_visuals(egui::Visuals::dark());
         self.dark_mode = ui.ctx().style().visuals.dark_mode;
@@ -543,7 +547,7 @@ impl DemoApp {
             // Inline settings for the selected layout
             match self.selected_layout {
                 DemoLayout::FruchtermanReingold => {
-                    let mut state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                    let mut state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
 
                     // Animation section
                     CollapsingHeader::new("Animation").default_open(true).show(ui, |ui| {
@@ -581,19 +585,19 @@ impl DemoApp {
                                         egui_graphs::GraphView::<
                                             (), (), petgraph::Directed, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_force_run(ui, g, 100, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                     DemoGraph::Undirected(g) => {
                                         egui_graphs::GraphView::<
                                             (), (), petgraph::Undirected, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_force_run(ui, g, 100, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                 }
                             }
@@ -603,19 +607,19 @@ impl DemoApp {
                                         let _ = egui_graphs::GraphView::<
                                             (), (), petgraph::Directed, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_budgeted_force_run(ui, g, 1000, 100, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                     DemoGraph::Undirected(g) => {
                                         let _ = egui_graphs::GraphView::<
                                             (), (), petgraph::Undirected, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_budgeted_force_run(ui, g, 1000, 100, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                 }
                             }
@@ -625,19 +629,19 @@ impl DemoApp {
                                         let _ = egui_graphs::GraphView::<
                                             (), (), petgraph::Directed, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_until_stable_force_run(ui, g, 0.01, 1000, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                     DemoGraph::Undirected(g) => {
                                         let _ = egui_graphs::GraphView::<
                                             (), (), petgraph::Undirected, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_until_stable_force_run(ui, g, 0.01, 1000, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                 }
                             }
@@ -647,19 +651,19 @@ impl DemoApp {
                                         let _ = egui_graphs::GraphView::<
                                             (), (), petgraph::Directed, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_until_stable_budgeted_force_run(ui, g, 0.01, 10000, 1000, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                     DemoGraph::Undirected(g) => {
                                         let _ = egui_graphs::GraphView::<
                                             (), (), petgraph::Undirected, petgraph::stable_graph::DefaultIx,
                                             egui_graphs::DefaultNodeShape, egui_graphs::DefaultEdgeShape,
-                                            FruchtermanReingoldWithCenterGravityState,
-                                            LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                                            FruchtermanReingoldWithBuiltinExtrasState,
+                                            LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                                         >::fast_forward_until_stable_budgeted_force_run(ui, g, 0.01, 10000, 1000, None);
-                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+                                        state = egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
                                     }
                                 }
                             }
@@ -678,27 +682,92 @@ impl DemoApp {
                             ui.add(egui::Slider::new(&mut state.base.c_attract, 0.1..=3.0).text("c_attract"));
                             info_icon(ui, "Multiplier for attractive force along edges (higher pulls connected nodes together).");
                         });
+                        ui.horizontal(|ui| {
+                            ui.label("edge_weighting");
+                            ui.selectable_value(&mut state.base.edge_weighting, EdgeWeighting::Ignore, "Ignore");
+                            ui.selectable_value(&mut state.base.edge_weighting, EdgeWeighting::Strength, "Strength");
+                            ui.selectable_value(&mut state.base.edge_weighting, EdgeWeighting::Length, "Length");
+                            info_icon(ui, "How edge weights scale the attraction: heavier edges pull harder (Strength) or settle longer (Length).");
+                        });
                         ui.horizontal(|ui| {
                             ui.add(egui::Slider::new(&mut state.base.c_repulse, 0.1..=3.0).text("c_repulse"));
                             info_icon(ui, "Multiplier for repulsive force between nodes (higher pushes nodes apart).");
                         });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut state.base.barnes_hut, "barnes_hut");
+                            info_icon(ui, "Approximate repulsion with a quad-tree. Much faster for thousands of nodes.");
+                        });
+                        ui.add_enabled_ui(state.base.barnes_hut, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.add(egui::Slider::new(&mut state.base.theta, 0.0..=2.0).text("theta"));
+                                info_icon(ui, "Barnes-Hut accuracy: lower is more exact and slower, 0 disables the approximation.");
+                            });
+                        });
 
                         ui.add_space(SECTION_SPACING);
                         ui.separator();
                         ui.label("Extras");
                         ui.horizontal(|ui| {
-                            ui.checkbox(&mut state.extras.0.enabled, "center_gravity");
+                            ui.checkbox(&mut state.center_gravity_mut().enabled, "center_gravity");
                             info_icon(ui, "Enable/disable center gravity force.");
                         });
-                        ui.add_enabled_ui(state.extras.0.enabled, |ui| {
+                        ui.add_enabled_ui(state.center_gravity().enabled, |ui| {
                             ui.horizontal(|ui| {
-                                ui.add(egui::Slider::new(&mut state.extras.0.params.c, 0.0..=2.0).text("center_strength"));
+                                ui.add(egui::Slider::new(&mut state.center_gravity_mut().params.c, 0.0..=2.0).text("center_strength"));
                                 info_icon(ui, "Coefficient for pull toward viewport/graph center.");
                             });
                         });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut state.collision_mut().enabled, "collision");
+                            info_icon(ui, "Push apart nodes whose shapes overlap.");
+                        });
+                        ui.add_enabled_ui(state.collision().enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.add(egui::Slider::new(&mut state.collision_mut().params.padding, 0.0..=50.0).text("collision_padding"));
+                                info_icon(ui, "Gap kept free between node shapes.");
+                            });
+                            ui.horizontal(|ui| {
+                                ui.add(egui::Slider::new(&mut state.collision_mut().params.strength, 0.0..=1.0).text("collision_strength"));
+                                info_icon(ui, "Share of the overlap resolved per step.");
+                            });
+                        });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut state.bounding_box_mut().enabled, "bounding_box");
+                            info_icon(ui, "Keep nodes inside the viewport.");
+                        });
+                        ui.add_enabled_ui(state.bounding_box().enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.add(egui::Slider::new(&mut state.bounding_box_mut().params.margin, 0.0..=200.0).text("box_margin"));
+                                info_icon(ui, "Distance kept from the viewport edges.");
+                            });
+                            ui.horizontal(|ui| {
+                                ui.add(egui::Slider::new(&mut state.bounding_box_mut().params.strength, 0.0..=100.0).text("box_strength"));
+                                info_icon(ui, "Stiffness of the walls.");
+                            });
+                        });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut state.radial_constraint_mut().enabled, "radial");
+                            info_icon(ui, "Pull nodes onto a ring around the viewport center.");
+                        });
+                        ui.add_enabled_ui(state.radial_constraint().enabled, |ui| {
+                            let radial = &mut state.radial_constraint_mut().params;
+                            ui.horizontal(|ui| {
+                                ui.add(egui::Slider::new(&mut radial.radius, 10.0..=1000.0).text("radial_radius"));
+                                info_icon(ui, "Radius of the outer ring.");
+                            });
+                            ui.horizontal(|ui| {
+                                ui.add(egui::Slider::new(&mut radial.strength, 0.0..=20.0).text("radial_strength"));
+                                info_icon(ui, "Pull toward the ring.");
+                            });
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut radial.metric, RadialMetric::Uniform, "Uniform");
+                                ui.selectable_value(&mut radial.metric, RadialMetric::Degree, "Degree");
+                                info_icon(ui, "Degree puts well connected nodes on inner rings.");
+                            });
+                        });
                     });
 
-                    egui_graphs::set_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, state, None);
+                    egui_graphs::set_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, state, None);
                 }
                 DemoLayout::Hierarchical => {
                     let mut state = egui_graphs::get_layout_state::<LayoutStateHierarchical>(ui, None);
@@ -715,9 +784,43 @@ impl DemoApp {
                         ui.checkbox(&mut state.center_parent, "center_parent");
                         info_icon(ui, "Center parent above/beside the span of its children.");
                     });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut state.fit_node_sizes, "fit_node_sizes");
+                        info_icon(ui, "Space levels and siblings by the drawn node sizes; row_dist and col_dist become minimums.");
+                    });
+                    ui.add_enabled_ui(state.fit_node_sizes, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Slider::new(&mut state.node_margin, 0.0..=200.0).text("node_margin"));
+                            info_icon(ui, "Free space kept between neighboring nodes.");
+                        });
+                    });
                     ui.horizontal(|ui| {
                         ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::TopDown, "TopDown");
                         ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::LeftRight, "LeftRight");
+                        ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::BottomUp, "BottomUp");
+                        ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::RightLeft, "RightLeft");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut state.mode, LayoutHierarchicalMode::Dfs, "Dfs");
+                        ui.selectable_value(&mut state.mode, LayoutHierarchicalMode::Tidy, "Tidy");
+                        ui.selectable_value(&mut state.mode, LayoutHierarchicalMode::Sugiyama, "Sugiyama");
+                        info_icon(ui, "Dfs places a spanning tree. Tidy packs the same tree compactly with centered parents. Sugiyama breaks cycles, minimizes crossings and routes long edges through bends.");
+                    });
+                    ui.add_enabled_ui(state.mode == LayoutHierarchicalMode::Sugiyama, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut state.layering, LayoutHierarchicalLayering::LongestPath, "LongestPath");
+                            ui.selectable_value(&mut state.layering, LayoutHierarchicalLayering::NetworkSimplex, "NetworkSimplex");
+                            info_icon(ui, "Layer assignment. Network simplex minimizes the total edge length.");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(&mut state.crossing_heuristic, LayoutHierarchicalCrossingHeuristic::Median, "Median");
+                            ui.selectable_value(&mut state.crossing_heuristic, LayoutHierarchicalCrossingHeuristic::Barycenter, "Barycenter");
+                            info_icon(ui, "Ordering heuristic of the crossing minimization sweeps.");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Slider::new(&mut state.crossing_sweeps, 0..=100).text("crossing_sweeps"));
+                            info_icon(ui, "Upper bound of ordering sweeps, more sweeps may remove more crossings.");
+                        });
                     });
                     ui.horizontal(|ui| {
                         if ui.button("Re-run layout").clicked() {
@@ -734,9 +837,9 @@ impl DemoApp {
 
     pub fn ui_layout_force_directed(&mut self, ui: &mut Ui) {
         let state =
-            egui_graphs::get_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, None);
+            egui_graphs::get_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, None);
 
-        egui_graphs::set_layout_state::<FruchtermanReingoldWithCenterGravityState>(ui, state, None);
+        egui_graphs::set_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(ui, state, None);
     }
 
     pub fn ui_layout_hierarchical(&mut self, ui: &mut Ui) {
@@ -763,6 +866,22 @@ impl DemoApp {
                     ui.checkbox(&mut state.center_parent, "center_parent");
                     info_icon(ui, "Center parent above/beside the span of its children.");
                 });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut state.fit_node_sizes, "fit_node_sizes");
+                    info_icon(
+                        ui,
+                        "Space levels and siblings by the drawn node sizes; row_dist and col_dist become minimums.",
+                    );
+                });
+                ui.add_enabled_ui(state.fit_node_sizes, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::Slider::new(&mut state.node_margin, 0.0..=200.0)
+                                .text("node_margin"),
+                        );
+                        info_icon(ui, "Free space kept between neighboring nodes.");
+                    });
+                });
                 ui.horizontal(|ui| {
                     ui.label("orientation");
                     let mut o = state.orientation;
@@ -782,6 +901,26 @@ impl DemoApp {
                     {
                         state.orientation = o;
                     }
+                    if ui
+                        .selectable_value(
+                            &mut o,
+                            LayoutHierarchicalOrientation::BottomUp,
+                            "BottomUp",
+                        )
+                        .clicked()
+                    {
+                        state.orientation = o;
+                    }
+                    if ui
+                        .selectable_value(
+                            &mut o,
+                            LayoutHierarchicalOrientation::RightLeft,
+                            "RightLeft",
+                        )
+                        .clicked()
+                    {
+                        state.orientation = o;
+                    }
                 });
 
                 ui.add_space(SECTION_SPACING);
@@ -904,6 +1043,15 @@ impl DemoApp {
                 }
                 info_icon(ui, "Master: also enables selection, node_clicking and hover.");
             });
+            ui.add_enabled_ui(self.settings_interaction.dragging_enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(
+                        &mut self.settings_interaction.pin_dragged_nodes,
+                        "pin_dragged_nodes",
+                    );
+                    info_icon(ui, "Dragged nodes stay where they are dropped, layouts skip them.");
+                });
+            });
         });
     }
 
@@ -943,6 +1091,24 @@ impl DemoApp {
                 ui.checkbox(&mut self.settings_style.edge_deemphasis, "edge_deemphasis");
                 info_icon(ui, "Dim non-selected edges to highlight current selection.");
             });
+            ui.add_space(2.0);
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.settings_animation.transition_duration, 0.0..=3.0)
+                        .text("transition_duration"),
+                );
+                info_icon(
+                    ui,
+                    "Seconds nodes take to move to the result of a layout change, 0 jumps.",
+                );
+            });
+            ui.horizontal(|ui| {
+                let easing = &mut self.settings_animation.transition_easing;
+                ui.selectable_value(easing, LayoutEasing::Linear, "Linear");
+                ui.selectable_value(easing, LayoutEasing::EaseIn, "EaseIn");
+                ui.selectable_value(easing, LayoutEasing::EaseOut, "EaseOut");
+                ui.selectable_value(easing, LayoutEasing::EaseInOut, "EaseInOut");
+            });
         });
     }
 
@@ -1319,7 +1485,8 @@ impl App for DemoApp {
                 .with_edge_selection_enabled(self.settings_interaction.edge_selection_enabled)
                 .with_edge_selection_multi_enabled(
                     self.settings_interaction.edge_selection_multi_enabled,
-                );
+                )
+                .with_pin_dragged_nodes(self.settings_interaction.pin_dragged_nodes);
             let settings_navigation = &egui_graphs::SettingsNavigation::new()
                 .with_zoom_and_pan_enabled(self.settings_navigation.zoom_and_pan_enabled)
                 .with_fit_to_screen_enabled(self.settings_navigation.fit_to_screen_enabled)
@@ -1341,11 +1508,14 @@ impl App for DemoApp {
                     });
             }
             let settings_style = &style_builder;
+            let settings_animation = &egui_graphs::SettingsAnimation::new()
+                .with_transition_duration(self.settings_animation.transition_duration)
+                .with_transition_easing(self.settings_animation.transition_easing);
 
             match (&mut self.g, self.selected_layout) {
                 (DemoGraph::Directed(ref mut g), DemoLayout::FruchtermanReingold) => {
                     if let Some(spec::PendingLayout::FR(st)) = self.pending_layout.take() {
-                        egui_graphs::set_layout_state::<FruchtermanReingoldWithCenterGravityState>(
+                        egui_graphs::set_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(
                             ui, st, None,
                         );
                     }
@@ -1356,12 +1526,13 @@ impl App for DemoApp {
                         _,
                         _,
                         _,
-                        FruchtermanReingoldWithCenterGravityState,
-                        LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                        FruchtermanReingoldWithBuiltinExtrasState,
+                        LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                     >::new(g)
                     .with_interactions(settings_interaction)
                     .with_navigations(settings_navigation)
-                    .with_styles(settings_style);
+                    .with_styles(settings_style)
+                    .with_animations(settings_animation);
                     #[cfg(feature = "events")]
                     {
                         #[cfg(not(target_arch = "wasm32"))]
@@ -1377,7 +1548,7 @@ impl App for DemoApp {
                 }
                 (DemoGraph::Undirected(ref mut g), DemoLayout::FruchtermanReingold) => {
                     if let Some(spec::PendingLayout::FR(st)) = self.pending_layout.take() {
-                        egui_graphs::set_layout_state::<FruchtermanReingoldWithCenterGravityState>(
+                        egui_graphs::set_layout_state::<FruchtermanReingoldWithBuiltinExtrasState>(
                             ui, st, None,
                         );
                     }
@@ -1388,12 +1559,13 @@ impl App for DemoApp {
                         _,
                         _,
                         _,
-                        FruchtermanReingoldWithCenterGravityState,
-                        LayoutForceDirected<FruchtermanReingoldWithCenterGravity>,
+                        FruchtermanReingoldWithBuiltinExtrasState,
+                        LayoutForceDirected<FruchtermanReingoldWithBuiltinExtras>,
                     >::new(g)
                     .with_interactions(settings_interaction)
                     .with_navigations(settings_navigation)
-                    .with_styles(settings_style);
+                    .with_styles(settings_style)
+                    .with_animations(settings_animation);
                     #[cfg(feature = "events")]
                     {
                         #[cfg(not(target_arch = "wasm32"))]
@@ -1423,7 +1595,8 @@ impl App for DemoApp {
                     >::new(g)
                     .with_interactions(settings_interaction)
                     .with_navigations(settings_navigation)
-                    .with_styles(settings_style);
+                    .with_styles(settings_style)
+                    .with_animations(settings_animation);
                     #[cfg(feature = "events")]
                     {
                         #[cfg(not(target_arch = "wasm32"))]
@@ -1453,7 +1626,8 @@ impl App for DemoApp {
                     >::new(g)
                     .with_interactions(settings_interaction)
                     .with_navigations(settings_navigation)
-                    .with_styles(settings_style);
+                    .with_styles(settings_style)
+                    .with_animations(settings_animation);
                     #[cfg(feature = "events")]
                     {
                         #[cfg(not(target_arch = "wasm32"))]
@@ -1501,13 +1675,13 @@ impl App for DemoApp {
                 let steps = match &self.g {
                     DemoGraph::Directed(_) => {
                         let st = egui_graphs::get_layout_state::<
-                            FruchtermanReingoldWithCenterGravityState,
+                            FruchtermanReingoldWithBuiltinExtrasState,
                         >(ui, None);
                         st.base.step_count as usize
                     }
                     DemoGraph::Undirected(_) => {
                         let st = egui_graphs::get_layout_state::<
-                            FruchtermanReingoldWithCenterGravityState,
+                            FruchtermanReingoldWithBuiltinExtrasState,
                         >(ui, None);
                         st.base.step_count as usize
                     }
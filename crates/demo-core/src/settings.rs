@@ -10,6 +10,7 @@ pub struct SettingsInteraction {
     pub edge_clicking_enabled: bool,
     pub edge_selection_enabled: bool,
     pub edge_selection_multi_enabled: bool,
+    pub pin_dragged_nodes: bool,
 }
 
 impl Default for SettingsInteraction {
@@ -23,6 +24,7 @@ impl Default for SettingsInteraction {
             edge_clicking_enabled: false,
             edge_selection_enabled: false,
             edge_selection_multi_enabled: false,
+            pin_dragged_nodes: false,
         }
     }
 }
@@ -53,6 +55,21 @@ impl Default for SettingsNavigation {
     }
 }
 
+// Layout transition parameters
+pub struct SettingsAnimation {
+    pub transition_duration: f32,
+    pub transition_easing: egui_graphs::LayoutEasing,
+}
+
+impl Default for SettingsAnimation {
+    fn default() -> Self {
+        Self {
+            transition_duration: 0.5,
+            transition_easing: egui_graphs::LayoutEasing::EaseInOut,
+        }
+    }
+}
+
 // Graph generation / counts controlled by UI
 pub struct SettingsGraph {
     pub count_node: usize,
@@ -21,11 +21,11 @@ const SCHEMA_FULL: &str = r#"{
         "directed": true 
     },
     "layout": {
-        "type": "FruchtermanReingold",
+        "type": "fruchtermanReingold",
         "running": true,
         "dt": 0.05,
         "k_scale": 1.2,
-        "extras": [ { "type": "CenterGravity", "enabled": true, "c": 0.3 } ]
+        "extras": [ { "type": "centerGravity", "enabled": true, "c": 0.3 } ]
     }
 }"#;
 
@@ -88,6 +88,8 @@ impl DemoApp {
                     {
                         if let Some(path) = rfd::FileDialog::new()
                             .add_filter("JSON", &["json"])
+                            .add_filter("GraphML", &["graphml", "xml"])
+                            .add_filter("DOT", &["dot", "gv"])
                             .pick_file()
                         {
                             let name = path
@@ -296,7 +298,7 @@ fn pick_json_file_web(buf: std::rc::Rc<std::cell::RefCell<Vec<UserUpload>>>) {
         Err(_) => return,
     };
     input.set_type("file");
-    input.set_accept(".json,application/json");
+    input.set_accept(".json,application/json,.graphml,.xml,.dot,.gv");
     input.set_hidden(true);
 
     // Keep a clone for the change handler
@@ -1,5 +1,6 @@
 use crate::{DemoApp, DemoGraph};
 use egui::{CollapsingHeader, Modal, ScrollArea, Ui};
+use egui_graphs::io::json::JsonGraph;
 
 impl DemoApp {
     pub fn ui_playground_tab(&mut self, ui: &mut Ui) {
@@ -42,7 +43,7 @@ impl DemoApp {
                     ui.horizontal(|ui| {
                         ui.checkbox(&mut self.export_include_layout, "Include Layout");
                         ui.small_button("ℹ").on_hover_text(
-                            "Exports the current layout choice and its parameters:\n\n- Fruchterman-Reingold: simulation params and CenterGravity\n- Hierarchical: row/col distances, centering, orientation",
+                            "Exports the current layout choice and its parameters:\n\n- Fruchterman-Reingold: simulation params and extras (CenterGravity, Collision, BoundingBox, RadialConstraint, ClusterAttraction)\n- Hierarchical: row/col distances, centering, orientation",
                         );
                     });
                     ui.horizontal(|ui| {
@@ -83,65 +84,21 @@ impl DemoApp {
                             self.show_export_modal = false;
                         }
                         if ui.button("Export").clicked() {
-                            // Gather graph topology
-                            let (nodes, edges, positions, directed) = match &self.g {
-                                DemoGraph::Directed(g) => {
-                                    let nodes: Vec<i64> = g
-                                        .g()
-                                        .node_indices()
-                                        .map(|i| i.index() as i64)
-                                        .collect();
-                                    let edges: Vec<(i64, i64)> = g
-                                        .g()
-                                        .edge_indices()
-                                        .filter_map(|e| g.g().edge_endpoints(e))
-                                        .map(|(a, b)| (a.index() as i64, b.index() as i64))
-                                        .collect();
-                                    let positions = if self.export_include_positions {
-                                        let mut v = Vec::with_capacity(nodes.len());
-                                        for idx in g.g().node_indices() {
-                                            let p = g.g().node_weight(idx).unwrap().location();
-                                            v.push((idx.index() as i64, p.x, p.y));
-                                        }
-                                        Some(v)
-                                    } else { None };
-                                    (nodes, edges, positions, true)
-                                }
-                                DemoGraph::Undirected(g) => {
-                                    let nodes: Vec<i64> = g
-                                        .g()
-                                        .node_indices()
-                                        .map(|i| i.index() as i64)
-                                        .collect();
-                                    let edges: Vec<(i64, i64)> = g
-                                        .g()
-                                        .edge_indices()
-                                        .filter_map(|e| g.g().edge_endpoints(e))
-                                        .map(|(a, b)| {
-                                            let (u, v) = if a.index() <= b.index() { (a, b) } else { (b, a) };
-                                            (u.index() as i64, v.index() as i64)
-                                        })
-                                        .collect();
-                                    let positions = if self.export_include_positions {
-                                        let mut v = Vec::with_capacity(nodes.len());
-                                        for idx in g.g().node_indices() {
-                                            let p = g.g().node_weight(idx).unwrap().location();
-                                            v.push((idx.index() as i64, p.x, p.y));
-                                        }
-                                        Some(v)
-                                    } else { None };
-                                    (nodes, edges, positions, false)
+                            let graph = self.export_include_graph.then(|| {
+                                let mut graph = match &self.g {
+                                    DemoGraph::Directed(g) => JsonGraph::from_graph(g),
+                                    DemoGraph::Undirected(g) => JsonGraph::from_graph(g),
+                                };
+                                if !self.export_include_positions {
+                                    graph.positions = None;
                                 }
-                            };
+                                graph
+                            });
                             let spec = crate::spec::build_export_spec(
                                 ui,
                                 self.export_include_layout,
-                                self.export_include_graph,
-                                directed,
                                 self.selected_layout,
-                                nodes,
-                                edges,
-                                positions,
+                                graph,
                             );
                             match serde_json::to_string_pretty(&spec) {
                                 Ok(json) => {
@@ -1,4 +1,4 @@
-use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use egui::{Pos2, Rect};
 use egui_graphs::{to_graph, DefaultEdgeShape, DefaultNodeShape, Graph};
 use petgraph::stable_graph::StableGraph;
@@ -105,9 +105,46 @@ fn bench_fr_step(c: &mut Criterion) {
     group.finish();
 }
 
+/// Single step repulsion cost of the exact O(n²) sum versus the Barnes-Hut approximation.
+fn bench_fr_repulsion_scaling(c: &mut Criterion) {
+    let view = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1200.0, 800.0));
+    let mut group = c.benchmark_group("fr_repulsion_scaling");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_millis(600));
+    group.warm_up_time(Duration::from_millis(200));
+
+    for n in [1000usize, 4000, 16000] {
+        let variants = [
+            ("exact", FruchtermanReingoldState::default()),
+            (
+                "barnes_hut",
+                FruchtermanReingoldState::default().with_barnes_hut(0.8),
+            ),
+        ];
+        for (name, state) in variants {
+            group.bench_with_input(BenchmarkId::new(name, n), &n, |b, &n| {
+                b.iter_batched(
+                    || {
+                        let g = make_graph(n, n * 2);
+                        let alg = FruchtermanReingold::from_state(state.clone());
+                        (g, alg)
+                    },
+                    |(mut g, mut alg)| {
+                        alg.step(&mut g, view);
+                        black_box(g);
+                        black_box(alg);
+                    },
+                    BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().configure_from_args();
-    targets = bench_fr_step
+    targets = bench_fr_step, bench_fr_repulsion_scaling
 }
 criterion_main!(benches);
@@ -1,4 +1,4 @@
-use egui::{Pos2, Shape, Vec2};
+use egui::{Pos2, Rect, Shape, Vec2};
 use petgraph::{stable_graph::IndexType, EdgeType};
 
 use crate::{draw::drawer::DrawContext, elements::EdgeProps, Node, NodeProps};
@@ -35,6 +35,39 @@ where
     ///
     /// Could be used to bind mouse events to the custom drawn nodes.
     fn is_inside(&self, pos: Pos2) -> bool;
+
+    /// Returns the rect covered by the node in canvas coordinates.
+    ///
+    /// Used by layouts to keep large nodes apart. Default implementation spans the boundary
+    /// points along the axes given by [`closest_boundary_point`](DisplayNode::closest_boundary_point).
+    fn bounding_rect(&self) -> Rect {
+        Rect::from_points(&[
+            self.closest_boundary_point(Vec2::X),
+            self.closest_boundary_point(-Vec2::X),
+            self.closest_boundary_point(Vec2::Y),
+            self.closest_boundary_point(-Vec2::Y),
+        ])
+    }
+
+    /// Returns the position of the named port in canvas coordinates.
+    ///
+    /// Used by edges referencing the port, see [`EdgeProps::source_port`]. Default
+    /// implementation returns None which makes edges fall back to
+    /// [`closest_boundary_point`](DisplayNode::closest_boundary_point).
+    #[allow(unused_variables)]
+    fn port_point(&self, name: &str) -> Option<Pos2> {
+        None
+    }
+
+    /// Returns the name of the port under the provided `pos` if any.
+    ///
+    /// * `pos` - position is in the canvas coordinates.
+    ///
+    /// Default implementation returns None.
+    #[allow(unused_variables)]
+    fn port_at(&self, pos: Pos2) -> Option<String> {
+        None
+    }
 }
 
 pub trait DisplayEdge<N, E, Ty, Ix, D>: Clone + From<EdgeProps<E>>
@@ -45,7 +78,8 @@ where
     Ix: IndexType,
     D: DisplayNode<N, E, Ty, Ix>,
 {
-    /// Draws shapes of the edge. Uses [`DisplayNode`] implementation from node endpoints to get start and end coordinates using [`closest_boundary_point`](DisplayNode::closest_boundary_point).
+    /// Draws shapes of the edge. Uses [`DisplayNode`] implementation from node endpoints to get start and end coordinates using [`closest_boundary_point`](DisplayNode::closest_boundary_point),
+    /// or [`port_point`](DisplayNode::port_point) for edges attached to ports.
     /// If the node is interacted these shapes will be used for drawing on foreground layer, otherwise on background layer.
     /// Has mutable reference to itself for possibility to change internal state for the visualizations where this is important.
     ///
@@ -21,6 +21,13 @@ pub struct DefaultEdgeShape {
     pub curve_size: f32,
     pub loop_size: f32,
     pub label_text: String,
+
+    #[serde(default)]
+    pub source_port: Option<String>,
+    #[serde(default)]
+    pub target_port: Option<String>,
+    #[serde(default)]
+    pub bends: Vec<Pos2>,
 }
 
 impl<E: Clone> From<EdgeProps<E>> for DefaultEdgeShape {
@@ -29,6 +36,9 @@ impl<E: Clone> From<EdgeProps<E>> for DefaultEdgeShape {
             order: edge.order,
             selected: edge.selected,
             label_text: edge.label,
+            source_port: edge.source_port,
+            target_port: edge.target_port,
+            bends: edge.bends,
 
             width: 2.,
             tip_size: 15.,
@@ -52,6 +62,13 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, I
             return self.is_inside_loop(start, pos);
         }
 
+        if !self.bends.is_empty() {
+            return self
+                .bent_points(start, end)
+                .windows(2)
+                .any(|w| distance_segment_to_point(w[0], w[1], pos) <= self.width);
+        }
+
         if self.order == 0 {
             return self.is_inside_line(start, end, pos);
         }
@@ -73,6 +90,10 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, I
             return self.loop_shapes(start, ctx, stroke, color, label_visible);
         }
 
+        if !self.bends.is_empty() {
+            return self.bent_shapes(start, end, ctx, stroke, color, label_visible);
+        }
+
         let dir = (end.location() - start.location()).normalized();
         if self.order == 0 {
             return self.straight_shapes(start, end, ctx, dir);
@@ -84,6 +105,9 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, I
         self.order = state.order;
         self.selected = state.selected;
         self.label_text = state.label.to_string();
+        self.source_port.clone_from(&state.source_port);
+        self.target_port.clone_from(&state.target_port);
+        self.bends.clone_from(&state.bends);
     }
 
     fn extra_bounds(
@@ -104,6 +128,11 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, I
             return Some((min, max));
         }
 
+        if !self.bends.is_empty() {
+            let rect = egui::Rect::from_points(&self.bends);
+            return Some((rect.min, rect.max));
+        }
+
         // curved edges (order > 0): approximate cubic bezier hull from control points
         if self.order > 0 {
             let dir_vec = end.location() - start.location();
@@ -112,8 +141,7 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, I
             }
             // connector points
             let dir = dir_vec.normalized();
-            let start_p = start.display().closest_boundary_point(dir);
-            let end_p = end.display().closest_boundary_point(-dir);
+            let (start_p, end_p) = self.connector_points(start, end, dir);
             let dist = end_p - start_p;
             if dist == Vec2::ZERO {
                 return None;
@@ -150,6 +178,59 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, I
 }
 
 impl DefaultEdgeShape {
+    /// Edge ends: the referenced ports if the nodes declare them, boundary points along `dir`
+    /// otherwise.
+    fn connector_points<
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        D: DisplayNode<N, E, Ty, Ix>,
+    >(
+        &self,
+        start: &Node<N, E, Ty, Ix, D>,
+        end: &Node<N, E, Ty, Ix, D>,
+        dir: Vec2,
+    ) -> (Pos2, Pos2) {
+        let start_p = port_point(start, self.source_port.as_deref())
+            .unwrap_or_else(|| start.display().closest_boundary_point(dir));
+        let end_p = port_point(end, self.target_port.as_deref())
+            .unwrap_or_else(|| end.display().closest_boundary_point(-dir));
+        (start_p, end_p)
+    }
+
+    /// Connector points joined by the bend points, the ends face the first and last bend.
+    fn bent_points<
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        D: DisplayNode<N, E, Ty, Ix>,
+    >(
+        &self,
+        start: &Node<N, E, Ty, Ix, D>,
+        end: &Node<N, E, Ty, Ix, D>,
+    ) -> Vec<Pos2> {
+        let (Some(first), Some(last)) = (self.bends.first(), self.bends.last()) else {
+            return vec![start.location(), end.location()];
+        };
+        let start_p = port_point(start, self.source_port.as_deref()).unwrap_or_else(|| {
+            start
+                .display()
+                .closest_boundary_point((*first - start.location()).normalized())
+        });
+        let end_p = port_point(end, self.target_port.as_deref()).unwrap_or_else(|| {
+            end.display()
+                .closest_boundary_point((*last - end.location()).normalized())
+        });
+
+        let mut points = Vec::with_capacity(self.bends.len() + 2);
+        points.push(start_p);
+        points.extend_from_slice(&self.bends);
+        points.push(end_p);
+        points
+    }
+
     fn current_color(&self, ctx: &DrawContext) -> Color32 {
         let style = if self.selected {
             ctx.ctx.style().visuals.widgets.active
@@ -208,6 +289,60 @@ impl DefaultEdgeShape {
         res
     }
 
+    fn bent_shapes<
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        D: DisplayNode<N, E, Ty, Ix>,
+    >(
+        &mut self,
+        start: &Node<N, E, Ty, Ix, D>,
+        end: &Node<N, E, Ty, Ix, D>,
+        ctx: &DrawContext,
+        stroke: Stroke,
+        color: Color32,
+        label_visible: bool,
+    ) -> Vec<Shape> {
+        let points = self.bent_points(start, end);
+        let tip = TipProps {
+            size: self.tip_size,
+            angle: self.tip_angle,
+        };
+        let mut builder = EdgeShapeBuilder::new(stroke)
+            .polyline(points.clone())
+            .with_scaler(ctx.meta);
+        if ctx.is_directed {
+            builder = builder.with_tip(&tip);
+        }
+        let mut res = builder.build();
+
+        if label_visible {
+            let dir = (end.location() - start.location()).normalized();
+            let size = f32::midpoint(node_size(start, dir), node_size(end, dir));
+            let galley = ctx.ctx.fonts_mut(|f| {
+                f.layout_no_wrap(
+                    self.label_text.clone(),
+                    FontId::new(ctx.meta.canvas_to_screen_size(size), FontFamily::Monospace),
+                    color,
+                )
+            });
+            // Middle point for an odd count, middle of the central segment otherwise.
+            let mid = points.len() / 2;
+            let anchor = if points.len() % 2 == 1 {
+                points[mid]
+            } else {
+                points[mid - 1] + (points[mid] - points[mid - 1]) / 2.
+            };
+            res.push(Self::label_shape(
+                galley,
+                ctx.meta.canvas_to_screen_pos(anchor),
+                color,
+            ));
+        }
+        res
+    }
+
     fn straight_shapes<
         N: Clone,
         E: Clone,
@@ -225,8 +360,7 @@ impl DefaultEdgeShape {
         let color = self.current_color(ctx);
         let stroke = self.current_stroke(ctx, color);
         let label_visible = ctx.style.labels_always || self.selected;
-        let start_connector_point = start.display().closest_boundary_point(dir);
-        let end_connector_point = end.display().closest_boundary_point(-dir);
+        let (start_connector_point, end_connector_point) = self.connector_points(start, end, dir);
         let mut builder = EdgeShapeBuilder::new(stroke)
             .straight((start_connector_point, end_connector_point))
             .with_scaler(ctx.meta);
@@ -277,8 +411,7 @@ impl DefaultEdgeShape {
         let color = self.current_color(ctx);
         let stroke = self.current_stroke(ctx, color);
         let label_visible = ctx.style.labels_always || self.selected;
-        let start_connector_point = start.display().closest_boundary_point(dir);
-        let end_connector_point = end.display().closest_boundary_point(-dir);
+        let (start_connector_point, end_connector_point) = self.connector_points(start, end, dir);
         let mut builder = EdgeShapeBuilder::new(stroke)
             .curved(
                 (start_connector_point, end_connector_point),
@@ -380,7 +513,9 @@ impl DefaultEdgeShape {
         end: &Node<N, E, Ty, Ix, D>,
         pos: Pos2,
     ) -> bool {
-        distance_segment_to_point(start.location(), end.location(), pos) <= self.width
+        let a = port_point(start, self.source_port.as_deref()).unwrap_or(start.location());
+        let b = port_point(end, self.target_port.as_deref()).unwrap_or(end.location());
+        distance_segment_to_point(a, b, pos) <= self.width
     }
 
     fn is_inside_curve<
@@ -396,8 +531,7 @@ impl DefaultEdgeShape {
         pos: Pos2,
     ) -> bool {
         let dir = (node_end.location() - node_start.location()).normalized();
-        let start = node_start.display().closest_boundary_point(dir);
-        let end = node_end.display().closest_boundary_point(-dir);
+        let (start, end) = self.connector_points(node_start, node_end, dir);
 
         let stroke = Stroke::new(self.width, Color32::default());
         let curved_shapes = EdgeShapeBuilder::new(stroke)
@@ -412,6 +546,13 @@ impl DefaultEdgeShape {
     }
 }
 
+fn port_point<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, Ix>>(
+    node: &Node<N, E, Ty, Ix, D>,
+    port: Option<&str>,
+) -> Option<Pos2> {
+    port.and_then(|p| node.display().port_point(p))
+}
+
 /// Returns the distance from line segment [`a`, `b`] to point `c`.
 /// Adapted from <https://stackoverflow.com/questions/1073336/circle-line-segment-collision-detection-algorithm>
 fn distance_segment_to_point(a: Pos2, b: Pos2, point: Pos2) -> f32 {
@@ -548,4 +689,29 @@ mod tests {
         );
         let _ = is_point_on_curve(Pos2::new(5.0, 5.0), &curve, 2.0);
     }
+
+    #[test]
+    fn test_is_inside_line_between_ports() {
+        use crate::{DefaultNodeShape, Edge, Node, Port};
+        use petgraph::stable_graph::NodeIndex;
+
+        let mut start = Node::<(), ()>::new(());
+        start.set_id(NodeIndex::new(0));
+        start.add_port(Port::output("out".to_string(), Vec2::new(0.0, 5.0)));
+        let mut end = Node::<(), ()>::new(());
+        end.set_id(NodeIndex::new(1));
+        end.set_location(Pos2::new(100.0, 0.0));
+        end.add_port(Port::input("in".to_string(), Vec2::new(0.0, 5.0)));
+        for n in [&mut start, &mut end] {
+            let props = n.props().clone();
+            *n.display_mut() = DefaultNodeShape::from(props);
+        }
+
+        let mut edge = Edge::<(), ()>::new(());
+        edge.set_source_port(Some("out".to_string()));
+        edge.set_target_port(Some("in".to_string()));
+        let shape = DefaultEdgeShape::from(edge.props().clone());
+        assert!(shape.is_inside(&start, &end, Pos2::new(50.0, 5.0)));
+        assert!(!shape.is_inside(&start, &end, Pos2::new(50.0, 0.0)));
+    }
 }
@@ -19,6 +19,9 @@ enum EdgeShapeProps {
         loop_size: f32,
         order: usize,
     },
+    Polyline {
+        points: Vec<Pos2>,
+    },
 }
 
 impl Default for EdgeShapeProps {
@@ -84,6 +87,13 @@ impl<'a> EdgeShapeBuilder<'a> {
         self
     }
 
+    /// Straight segments through `points`, the tip is placed at the last one.
+    pub fn polyline(mut self, points: Vec<Pos2>) -> Self {
+        self.shape_props = EdgeShapeProps::Polyline { points };
+
+        self
+    }
+
     pub fn with_scaler(mut self, scaler: &'a MetadataFrame) -> Self {
         self.scaler = Some(scaler);
 
@@ -274,8 +284,54 @@ impl<'a> EdgeShapeBuilder<'a> {
         res
     }
 
+    fn shape_polyline(&self, points: &[Pos2]) -> Vec<Shape> {
+        let mut res = vec![];
+        let mut stroke = self.stroke;
+
+        let mut points_line = points.to_vec();
+        let mut points_tip = match (self.tip, points_line.len()) {
+            (Some(tip_props), len) if len >= 2 => {
+                let end = points_line[len - 1];
+                let tip_dir = (end - points_line[len - 2]).normalized();
+
+                let arrow_tip_dir_1 = rotate_vector(tip_dir, tip_props.angle) * tip_props.size;
+                let arrow_tip_dir_2 = rotate_vector(tip_dir, -tip_props.angle) * tip_props.size;
+
+                // replace end of an edge with start of tip
+                points_line[len - 1] = end - tip_props.size * tip_dir;
+
+                vec![end, end - arrow_tip_dir_1, end - arrow_tip_dir_2]
+            }
+            _ => vec![],
+        };
+
+        if let Some(scaler) = self.scaler {
+            stroke.width = scaler.canvas_to_screen_size(stroke.width);
+            points_line = points_line
+                .iter()
+                .map(|p| scaler.canvas_to_screen_pos(*p))
+                .collect();
+            points_tip = points_tip
+                .iter()
+                .map(|p| scaler.canvas_to_screen_pos(*p))
+                .collect();
+        }
+
+        res.push(Shape::line(points_line, stroke));
+        if !points_tip.is_empty() {
+            res.push(Shape::convex_polygon(
+                points_tip,
+                stroke.color,
+                Stroke::default(),
+            ));
+        }
+
+        res
+    }
+
     pub fn build(&self) -> Vec<Shape> {
         match self.shape_props {
+            EdgeShapeProps::Polyline { ref points } => self.shape_polyline(points),
             EdgeShapeProps::Straight { bounds } => self.shape_straight(bounds),
             EdgeShapeProps::Looped {
                 node_center,
@@ -334,4 +390,29 @@ mod tests {
         // First shape should be a cubic bezier
         assert!(matches!(shapes.first(), Some(Shape::CubicBezier(_))));
     }
+
+    #[test]
+    fn polyline_ends_at_tip() {
+        let stroke = Stroke::new(1.0, Color32::WHITE);
+        let tip = TipProps {
+            size: 2.0,
+            angle: 0.3,
+        };
+        let points = vec![
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+        ];
+        let shapes = EdgeShapeBuilder::new(stroke)
+            .polyline(points)
+            .with_tip(&tip)
+            .build();
+
+        let Some(Shape::Path(path)) = shapes.first() else {
+            panic!("expected a path, got {shapes:?}");
+        };
+        assert_eq!(path.points.len(), 3);
+        assert_eq!(path.points[2], Pos2::new(10.0, 8.0));
+        assert!(matches!(shapes.get(1), Some(Shape::Path(tip)) if tip.closed));
+    }
 }
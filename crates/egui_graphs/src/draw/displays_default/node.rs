@@ -4,7 +4,9 @@ use egui::{
 };
 use petgraph::{stable_graph::IndexType, EdgeType};
 
-use crate::{draw::drawer::DrawContext, DisplayNode, NodeProps};
+use crate::{
+    draw::drawer::DrawContext, elements::PORT_RADIUS, DisplayNode, NodeProps, Port, PortKind,
+};
 
 /// This is the default node shape which is used to display nodes in the graph.
 ///
@@ -22,6 +24,9 @@ pub struct DefaultNodeShape {
 
     /// Shape dependent property
     pub radius: f32,
+
+    #[serde(default)]
+    pub ports: Vec<Port>,
 }
 
 impl<N: Clone> From<NodeProps<N>> for DefaultNodeShape {
@@ -35,6 +40,7 @@ impl<N: Clone> From<NodeProps<N>> for DefaultNodeShape {
             color: node_props.color(),
 
             radius: 5.0,
+            ports: node_props.ports,
         }
     }
 }
@@ -43,7 +49,18 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
     for DefaultNodeShape
 {
     fn is_inside(&self, pos: Pos2) -> bool {
-        is_inside_circle(self.pos, self.radius, pos)
+        is_inside_circle(self.pos, self.radius, pos) || self.port_index_at(pos).is_some()
+    }
+
+    fn port_point(&self, name: &str) -> Option<Pos2> {
+        self.ports
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| self.pos + p.offset)
+    }
+
+    fn port_at(&self, pos: Pos2) -> Option<String> {
+        self.port_index_at(pos).map(|i| self.ports[i].name.clone())
     }
 
     fn closest_boundary_point(&self, dir: Vec2) -> Pos2 {
@@ -66,6 +83,7 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
             }
             .into(),
         );
+        res.extend(self.port_shapes(ctx, color));
 
         if !(ctx.style.labels_always || self.selected || self.dragged || self.hovered) {
             return res;
@@ -88,6 +106,7 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
         self.hovered = state.hovered;
         self.label_text = state.label.to_string();
         self.color = state.color();
+        self.ports.clone_from(&state.ports);
     }
 }
 
@@ -101,6 +120,38 @@ fn is_inside_circle(center: Pos2, radius: f32, pos: Pos2) -> bool {
 }
 
 impl DefaultNodeShape {
+    /// Index of the port whose marker contains `pos`. Closest port wins when markers overlap.
+    fn port_index_at(&self, pos: Pos2) -> Option<usize> {
+        self.ports
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, (self.pos + p.offset).distance(pos)))
+            .filter(|(_, dist)| *dist <= PORT_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Inputs are drawn as rings, outputs as filled circles.
+    fn port_shapes(&self, ctx: &DrawContext, color: Color32) -> Vec<Shape> {
+        let radius = ctx.meta.canvas_to_screen_size(PORT_RADIUS);
+        self.ports
+            .iter()
+            .map(|p| {
+                let fill = match p.kind {
+                    PortKind::Input => Color32::TRANSPARENT,
+                    PortKind::Output => color,
+                };
+                CircleShape {
+                    center: ctx.meta.canvas_to_screen_pos(self.pos + p.offset),
+                    radius,
+                    fill,
+                    stroke: Stroke::new(1., color),
+                }
+                .into()
+            })
+            .collect()
+    }
+
     fn is_interacted(&self) -> bool {
         self.selected || self.dragged || self.hovered
     }
@@ -192,4 +243,36 @@ mod test {
             Pos2::new(0.0, 10.0)
         ));
     }
+
+    #[test]
+    fn test_ports_hit_testing() {
+        use petgraph::{stable_graph::DefaultIx, Directed};
+
+        let mut node = crate::Node::<(), ()>::new(());
+        node.set_location(Pos2::new(10.0, 10.0));
+        node.add_port(Port::input("in".to_string(), Vec2::new(-5.0, 0.0)));
+        node.add_port(Port::output("out".to_string(), Vec2::new(5.0, 0.0)));
+        let shape = DefaultNodeShape::from(node.props().clone());
+        assert_eq!(
+            DisplayNode::<(), (), Directed, DefaultIx>::port_point(&shape, "out"),
+            Some(Pos2::new(15.0, 10.0))
+        );
+        assert_eq!(
+            DisplayNode::<(), (), Directed, DefaultIx>::port_point(&shape, "missing"),
+            None
+        );
+        assert_eq!(
+            DisplayNode::<(), (), Directed, DefaultIx>::port_at(&shape, Pos2::new(4.0, 10.0)),
+            Some("in".to_string())
+        );
+        assert_eq!(
+            DisplayNode::<(), (), Directed, DefaultIx>::port_at(&shape, Pos2::new(10.0, 10.0)),
+            None
+        );
+        // Port markers stick out of the circle and still belong to the node.
+        assert!(DisplayNode::<(), (), Directed, DefaultIx>::is_inside(
+            &shape,
+            Pos2::new(16.5, 10.0)
+        ));
+    }
 }
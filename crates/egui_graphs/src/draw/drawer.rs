@@ -1,6 +1,9 @@
 use std::marker::PhantomData;
 
-use egui::{Context, Painter, Shape};
+use egui::{
+    epaint::RectShape, Align2, Context, CornerRadius, FontId, Painter, Rect, Shape, StrokeKind,
+    Vec2,
+};
 use petgraph::graph::IndexType;
 use petgraph::EdgeType;
 
@@ -13,6 +16,9 @@ use crate::{
 
 use super::{DisplayEdge, DisplayNode};
 
+/// Space between group members and the group frame, canvas units.
+const GROUP_FRAME_PADDING: f32 = 10.;
+
 /// Contains all the data about current widget state which is needed for custom drawing functions.
 pub struct DrawContext<'a> {
     pub ctx: &'a Context,
@@ -35,6 +41,7 @@ where
 {
     ctx: &'a DrawContext<'a>,
     g: &'a mut Graph<N, E, Ty, Ix, Nd, Ed>,
+    shapes: Vec<Shape>,
     delayed: Vec<Shape>,
 
     _marker: PhantomData<(Nd, Ed, L, S)>,
@@ -55,24 +62,34 @@ where
         Drawer {
             ctx,
             g,
+            shapes: Vec::new(),
             delayed: Vec::new(),
             _marker: PhantomData,
         }
     }
 
     /// Renders the graph for the current frame.
+    pub(crate) fn draw(self) {
+        let painter = self.ctx.painter;
+        painter.extend(self.shapes());
+    }
+
+    /// Builds the shapes of the current frame in paint order without painting them.
     ///
     /// Order matters:
     /// 1. `update_nodes` syncs each node's display object from its props so edge geometry
     ///    (which reads node display boundary points) uses fresh positions / sizes.
-    /// 2. `draw_edges` builds edge shapes using the updated node display state.
-    /// 3. `draw_nodes` paints nodes (non‑selected first) while deferring highlighted ones.
-    /// 4. `draw_delayed` paints deferred (selected / dragged) shapes on top.
-    pub(crate) fn draw(mut self) {
+    /// 2. `draw_groups` paints frames of expanded groups behind everything else.
+    /// 3. `draw_edges` builds edge shapes using the updated node display state.
+    /// 4. `draw_nodes` paints nodes (non‑selected first) while deferring highlighted ones.
+    /// 5. deferred (selected / dragged) shapes go on top.
+    pub(crate) fn shapes(mut self) -> Vec<Shape> {
         self.update_nodes();
+        self.draw_groups();
         self.draw_edges();
         self.draw_nodes();
-        self.draw_delayed();
+        self.shapes.append(&mut self.delayed);
+        self.shapes
     }
 
     /// Synchronizes node display state with their current props without emitting shapes.
@@ -81,74 +98,105 @@ where
     /// points) already being in sync when computing connector positions.
     fn update_nodes(&mut self) {
         self.g
-            .g_mut()
+            .g()
             .node_indices()
             .collect::<Vec<_>>()
             .into_iter()
             .for_each(|idx| {
-                if let Some(n) = self.g.node_mut(idx) {
+                if let Some(n) = self.g.node_mut_untracked(idx) {
                     let props = n.props().clone();
                     n.display_mut().update(&props);
                 }
             });
     }
 
-    fn draw_delayed(&mut self) {
-        self.delayed.iter().for_each(|s| {
-            self.ctx.painter.add(s.clone());
-        });
+    fn draw_groups(&mut self) {
+        let style = self.ctx.ctx.style();
+        let fill = style.visuals.faint_bg_color;
+        let stroke = style.visuals.widgets.noninteractive.bg_stroke;
+        let text_color = style.visuals.weak_text_color();
+
+        for (id, group) in self.g.groups_iter() {
+            let Some(rect) = self.g.group_rect(id) else {
+                continue;
+            };
+            let rect = rect.expand(GROUP_FRAME_PADDING);
+            let rect = Rect::from_min_max(
+                self.ctx.meta.canvas_to_screen_pos(rect.min),
+                self.ctx.meta.canvas_to_screen_pos(rect.max),
+            );
+
+            self.shapes.push(Shape::Rect(RectShape::new(
+                rect,
+                CornerRadius::same(4),
+                fill,
+                stroke,
+                StrokeKind::Outside,
+            )));
+            self.shapes.push(self.ctx.ctx.fonts_mut(|f| {
+                Shape::text(
+                    f,
+                    rect.left_top() - Vec2::new(0., 2.),
+                    Align2::LEFT_BOTTOM,
+                    group.label(),
+                    FontId::proportional(12.),
+                    text_color,
+                )
+            }));
+        }
     }
 
     fn draw_nodes(&mut self) {
         self.g
-            .g_mut()
+            .g()
             .node_indices()
             .collect::<Vec<_>>()
             .into_iter()
             .for_each(|idx| {
-                let n = self.g.node_mut(idx).unwrap();
+                if !self.g.is_node_visible(idx) {
+                    return;
+                }
+
+                let n = self.g.node_mut_untracked(idx).unwrap();
                 let shapes = n.display_mut().shapes(self.ctx);
 
                 if n.selected() || n.dragged() {
-                    for s in shapes {
-                        self.delayed.push(s);
-                    }
+                    self.delayed.extend(shapes);
                 } else {
-                    for s in shapes {
-                        self.ctx.painter.add(s);
-                    }
+                    self.shapes.extend(shapes);
                 }
             });
     }
 
     fn draw_edges(&mut self) {
         self.g
-            .g_mut()
+            .g()
             .edge_indices()
             .collect::<Vec<_>>()
             .into_iter()
             .for_each(|idx| {
-                let (idx_start, idx_end) = self.g.edge_endpoints(idx).unwrap();
+                // Edges of collapsed group members are drawn from/to the group node.
+                let Some((idx_start, idx_end)) = self.g.visible_edge_endpoints(idx) else {
+                    return;
+                };
+                let order = self.g.visible_edge_order(idx).unwrap();
 
                 // FIXME: too costly to clone nodes for every edge
                 let start = self.g.node(idx_start).cloned().unwrap();
                 let end = self.g.node(idx_end).cloned().unwrap();
 
-                let e = self.g.edge_mut(idx).unwrap();
-                let props = e.props().clone();
+                let e = self.g.edge_mut_untracked(idx).unwrap();
+                let mut props = e.props().clone();
+                props.order = order;
 
                 let display = e.display_mut();
                 display.update(&props);
                 let shapes = display.shapes(&start, &end, self.ctx);
 
                 if e.selected() {
-                    for s in shapes {
-                        self.delayed.push(s);
-                    }
+                    self.delayed.extend(shapes);
                 } else {
-                    for s in shapes {
-                        self.ctx.painter.add(s);
-                    }
+                    self.shapes.extend(shapes);
                 }
             });
     }
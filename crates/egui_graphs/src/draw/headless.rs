@@ -0,0 +1,257 @@
+use egui::{
+    epaint::ClippedShape, ColorImage, Context, LayerId, Pos2, RawInput, Rect, Shape, Vec2,
+    ViewportId, ViewportInfo, Visuals,
+};
+use petgraph::{stable_graph::IndexType, EdgeType};
+
+use crate::{
+    layouts::random::{Random, State},
+    metadata::MetadataFrame,
+    settings::SettingsStyle,
+    Graph,
+};
+
+use super::{drawer::Drawer, raster::rasterize, DisplayEdge, DisplayNode, DrawContext};
+
+/// Part of the canvas shown by a headless render.
+#[derive(Debug, Clone)]
+pub enum RenderView {
+    /// Fits all visible nodes. `padding` is a fraction of the graph size added around it,
+    /// like [`crate::SettingsNavigation::with_fit_to_screen_padding`].
+    Fit { padding: f32 },
+    /// Zoom and pan of a frame, e.g. the one stored by the widget.
+    Frame(MetadataFrame),
+}
+
+/// Viewport and styling of a headless render, see [`render_shapes`].
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub(crate) size: Vec2,
+    pub(crate) view: RenderView,
+    pub(crate) style: SettingsStyle,
+    pub(crate) visuals: Visuals,
+    pub(crate) pixels_per_point: f32,
+}
+
+impl RenderOptions {
+    /// Viewport of the given size in points fitting the graph with default dark visuals.
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            view: RenderView::Fit { padding: 0.1 },
+            style: SettingsStyle::default(),
+            visuals: Visuals::dark(),
+            pixels_per_point: 1.,
+        }
+    }
+
+    pub fn with_view(mut self, view: RenderView) -> Self {
+        self.view = view;
+        self
+    }
+
+    /// Styling hooks applied exactly as in the widget.
+    pub fn with_style(mut self, style: SettingsStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Theme the default display shapes take their colors from.
+    pub fn with_visuals(mut self, visuals: Visuals) -> Self {
+        self.visuals = visuals;
+        self
+    }
+
+    /// Pixel density of rasterized images, see [`render_image`].
+    pub fn with_pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        self.pixels_per_point = pixels_per_point;
+        self
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    pub fn visuals(&self) -> &Visuals {
+        &self.visuals
+    }
+
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// Image size in pixels.
+    pub fn size_px(&self) -> [usize; 2] {
+        let size = (self.size * self.pixels_per_point).round();
+        [size.x as usize, size.y as usize]
+    }
+}
+
+/// Draws one frame of the graph off-screen and returns the shapes in paint order, in
+/// coordinates of a viewport with its top left corner at the origin.
+///
+/// The shapes come from the same [`DisplayNode::shapes`] and [`DisplayEdge::shapes`] calls the
+/// widget makes, so custom display types render identically. Like a widget frame this syncs
+/// the display state of nodes and edges with their props.
+///
+/// Text is laid out with the egui default fonts.
+pub fn render_shapes<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    options: &RenderOptions,
+) -> Vec<Shape>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    draw_headless(g, options).1
+}
+
+/// Draws one frame of the graph off-screen and rasterizes it on the CPU.
+///
+/// Shapes from [`render_shapes`] are tessellated by egui exactly as for a GPU backend,
+/// including anti-aliasing feathering and text glyphs from the font atlas, and the resulting
+/// triangles are blended in gamma space over the panel color of the options visuals.
+pub fn render_image<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    options: &RenderOptions,
+) -> ColorImage
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let (ctx, shapes) = draw_headless(g, options);
+    let clip_rect = Rect::from_min_size(Pos2::ZERO, options.size);
+    let shapes = shapes
+        .into_iter()
+        .map(|shape| ClippedShape { clip_rect, shape })
+        .collect();
+    let primitives = ctx.tessellate(shapes, options.pixels_per_point);
+    let font_image = ctx.fonts(|f| f.image());
+
+    rasterize(
+        &primitives,
+        &font_image,
+        options.size_px(),
+        options.pixels_per_point,
+        options.visuals.panel_fill,
+    )
+}
+
+/// Runs a single headless pass drawing the graph and returns the context together with the
+/// shapes so text galleys stay valid for tessellation.
+fn draw_headless<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    options: &RenderOptions,
+) -> (Context, Vec<Shape>)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let meta = match &options.view {
+        RenderView::Fit { padding } => fit_frame(g, options.size, *padding),
+        RenderView::Frame(meta) => meta.clone(),
+    };
+    let is_directed = g.is_directed();
+
+    let ctx = Context::default();
+    ctx.set_visuals(options.visuals.clone());
+    let mut input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, options.size)),
+        ..RawInput::default()
+    };
+    input.viewports.insert(
+        ViewportId::ROOT,
+        ViewportInfo {
+            native_pixels_per_point: Some(options.pixels_per_point),
+            ..ViewportInfo::default()
+        },
+    );
+
+    let mut shapes = Vec::new();
+    let _ = ctx.run(input, |ctx| {
+        let painter = ctx.layer_painter(LayerId::background());
+        let draw_ctx = DrawContext {
+            ctx,
+            painter: &painter,
+            style: &options.style,
+            is_directed,
+            meta: &meta,
+        };
+        shapes = Drawer::<N, E, Ty, Ix, Dn, De, State, Random>::new(g, &draw_ctx).shapes();
+    });
+    (ctx, shapes)
+}
+
+/// Zoom and pan fitting the visible nodes into a viewport of the given size.
+fn fit_frame<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    size: Vec2,
+    padding: f32,
+) -> MetadataFrame
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut meta = MetadataFrame::default();
+    for idx in g.visible_node_indices() {
+        if let Some(n) = g.node(idx) {
+            meta.process_bounds(n);
+        }
+    }
+
+    let bounds = meta.graph_bounds();
+    let bounds = if bounds.is_positive() {
+        bounds
+    } else {
+        Rect::from_center_size(bounds.center(), Vec2::splat(1.))
+    };
+    let graph_size = bounds.size() * (1. + padding);
+    let zoom = (size.x / graph_size.x).min(size.y / graph_size.y);
+    meta.zoom = if zoom.is_finite() && zoom > 0. {
+        zoom
+    } else {
+        1.
+    };
+    meta.pan = size / 2. - bounds.center().to_vec2() * meta.zoom;
+    meta
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn fit_view_keeps_nodes_inside_viewport() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(-100., 20.));
+        let b = g.add_node_with_location((), Pos2::new(300., 80.));
+        g.add_edge(a, b, ());
+
+        let options = RenderOptions::new(Vec2::new(200., 100.));
+        let shapes = render_shapes(&mut g, &options);
+        assert!(!shapes.is_empty());
+
+        let viewport = Rect::from_min_size(Pos2::ZERO, options.size());
+        for s in &shapes {
+            assert!(viewport.contains_rect(s.visual_bounding_rect()), "{s:?}");
+        }
+    }
+}
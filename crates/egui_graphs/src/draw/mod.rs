@@ -1,8 +1,11 @@
 mod displays;
 mod displays_default;
 pub(crate) mod drawer;
+mod headless;
+mod raster;
 
 pub use displays::{DisplayEdge, DisplayNode};
 pub use displays_default::DefaultEdgeShape;
 pub use displays_default::DefaultNodeShape;
 pub use drawer::DrawContext;
+pub use headless::{render_image, render_shapes, RenderOptions, RenderView};
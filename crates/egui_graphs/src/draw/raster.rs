@@ -0,0 +1,177 @@
+use egui::{
+    epaint::{ClippedPrimitive, Primitive, Vertex},
+    Color32, ColorImage, Rgba, TextureId, Vec2,
+};
+
+/// Rasterizes tessellated primitives into an image of `size` pixels.
+///
+/// Triangles are filled at pixel centers with colors and uvs interpolated barycentrically.
+/// Anti-aliasing comes from the feathering the tessellator already baked into the meshes.
+/// Meshes using the font texture sample the font atlas bilinearly, other textures are not
+/// available off-screen and are drawn with their vertex colors only.
+pub(crate) fn rasterize(
+    primitives: &[ClippedPrimitive],
+    font_image: &ColorImage,
+    size: [usize; 2],
+    pixels_per_point: f32,
+    background: Color32,
+) -> ColorImage {
+    let mut image = ColorImage::filled(size, background);
+
+    for p in primitives {
+        let Primitive::Mesh(mesh) = &p.primitive else {
+            continue;
+        };
+        let clip = p.clip_rect * pixels_per_point;
+        let clip = [
+            clip.min.x.max(0.).floor() as usize,
+            clip.min.y.max(0.).floor() as usize,
+            (clip.max.x.ceil().max(0.) as usize).min(size[0]),
+            (clip.max.y.ceil().max(0.) as usize).min(size[1]),
+        ];
+        let texture = (mesh.texture_id == TextureId::default()).then_some(font_image);
+
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| &mesh.vertices[tri[i] as usize]);
+            fill_triangle(&mut image, clip, [a, b, c], pixels_per_point, texture);
+        }
+    }
+
+    image
+}
+
+fn fill_triangle(
+    image: &mut ColorImage,
+    clip: [usize; 4],
+    vertices: [&Vertex; 3],
+    pixels_per_point: f32,
+    texture: Option<&ColorImage>,
+) {
+    let [p0, p1, p2] = vertices.map(|v| v.pos.to_vec2() * pixels_per_point);
+    let area = cross(p1 - p0, p2 - p0);
+    if area.abs() < f32::EPSILON {
+        return;
+    }
+
+    let min = p0.min(p1).min(p2);
+    let max = p0.max(p1).max(p2);
+    let x0 = (min.x.floor().max(0.) as usize).max(clip[0]);
+    let y0 = (min.y.floor().max(0.) as usize).max(clip[1]);
+    let x1 = (max.x.ceil().max(0.) as usize).min(clip[2]);
+    let y1 = (max.y.ceil().max(0.) as usize).min(clip[3]);
+
+    let colors = vertices.map(|v| {
+        Rgba::from_rgba_premultiplied(
+            f32::from(v.color.r()),
+            f32::from(v.color.g()),
+            f32::from(v.color.b()),
+            f32::from(v.color.a()),
+        )
+    });
+    let width = image.size[0];
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let w0 = cross(p2 - p1, p - p1) / area;
+            let w1 = cross(p0 - p2, p - p2) / area;
+            let w2 = 1. - w0 - w1;
+            // Top-left fill convention is approximated by a half-open test on one weight so
+            // pixels on a shared edge of two triangles are filled once.
+            if w0 < 0. || w1 < 0. || w2 <= 0. {
+                continue;
+            }
+
+            let mut src = colors[0] * w0 + colors[1] * w1 + colors[2] * w2;
+            if let Some(texture) = texture {
+                let uv = vertices[0].uv.to_vec2() * w0
+                    + vertices[1].uv.to_vec2() * w1
+                    + vertices[2].uv.to_vec2() * w2;
+                src = src * sample(texture, uv);
+            }
+
+            let dst = &mut image.pixels[y * width + x];
+            *dst = blend(src, *dst);
+        }
+    }
+}
+
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Bilinear sample at normalized `uv`, premultiplied channels in `0..=1`.
+fn sample(texture: &ColorImage, uv: Vec2) -> Rgba {
+    let [w, h] = texture.size;
+    let x = (uv.x * w as f32 - 0.5).clamp(0., (w - 1) as f32);
+    let y = (uv.y * h as f32 - 0.5).clamp(0., (h - 1) as f32);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    let texel = |x: usize, y: usize| {
+        let c = texture.pixels[y * w + x];
+        Rgba::from_rgba_premultiplied(
+            f32::from(c.r()) / 255.,
+            f32::from(c.g()) / 255.,
+            f32::from(c.b()) / 255.,
+            f32::from(c.a()) / 255.,
+        )
+    };
+    let top = texel(x0, y0) * (1. - fx) + texel(x1, y0) * fx;
+    let bottom = texel(x0, y1) * (1. - fx) + texel(x1, y1) * fx;
+    top * (1. - fy) + bottom * fy
+}
+
+/// Premultiplied source-over in gamma space like the egui GPU backends. `src` channels are in
+/// `0..=255`.
+fn blend(src: Rgba, dst: Color32) -> Color32 {
+    let inv = 1. - src.a() / 255.;
+    let channel = |s: f32, d: u8| (s + f32::from(d) * inv).round().clamp(0., 255.) as u8;
+    Color32::from_rgba_premultiplied(
+        channel(src.r(), dst.r()),
+        channel(src.g(), dst.g()),
+        channel(src.b(), dst.b()),
+        channel(src.a(), dst.a()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{epaint::Mesh, Pos2, Rect};
+
+    use super::*;
+
+    #[test]
+    fn fills_triangles_within_clip() {
+        let mut mesh = Mesh::default();
+        mesh.add_colored_rect(
+            Rect::from_min_max(Pos2::new(1., 1.), Pos2::new(5., 3.)),
+            Color32::RED,
+        );
+        let primitives = [ClippedPrimitive {
+            clip_rect: Rect::from_min_max(Pos2::ZERO, Pos2::new(4., 8.)),
+            primitive: Primitive::Mesh(mesh),
+        }];
+        let font = ColorImage::filled([1, 1], Color32::WHITE);
+
+        let image = rasterize(&primitives, &font, [8, 4], 1., Color32::BLACK);
+
+        let px = |x: usize, y: usize| image.pixels[y * 8 + x];
+        assert_eq!(px(1, 1), Color32::RED);
+        assert_eq!(px(3, 2), Color32::RED);
+        // Outside the rect and cut by the clip rect.
+        assert_eq!(px(0, 0), Color32::BLACK);
+        assert_eq!(px(4, 1), Color32::BLACK);
+        assert_eq!(px(1, 3), Color32::BLACK);
+    }
+
+    #[test]
+    fn blends_premultiplied_colors() {
+        let half_white = Rgba::from_rgba_premultiplied(128., 128., 128., 128.);
+        assert_eq!(
+            blend(half_white, Color32::BLACK),
+            Color32::from_rgba_premultiplied(128, 128, 128, 255)
+        );
+    }
+}
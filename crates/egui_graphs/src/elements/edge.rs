@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 
+use egui::Pos2;
 use petgraph::{
     stable_graph::{DefaultIx, EdgeIndex, IndexType},
     Directed, EdgeType,
@@ -15,10 +16,28 @@ pub struct EdgeProps<E: Clone> {
     pub order: usize,
     pub selected: bool,
     pub label: String,
+    /// Hidden edges stay in the graph but are not drawn, hit-tested or laid out.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Name of the [`crate::Port`] of the start node the edge leaves from.
+    #[serde(default)]
+    pub source_port: Option<String>,
+    /// Name of the [`crate::Port`] of the end node the edge arrives at.
+    #[serde(default)]
+    pub target_port: Option<String>,
+    /// Points in canvas coordinates the edge is routed through from start to end, e.g. set by
+    /// the Sugiyama mode of [`crate::LayoutHierarchical`] for edges spanning several layers.
+    /// Cleared when an endpoint is moved or another layout runs.
+    #[serde(default)]
+    pub bends: Vec<Pos2>,
+    /// Weight read by [`crate::Graph::edge_weight`] when no edge weight function is set, None
+    /// counts as 1.
+    #[serde(default)]
+    pub weight: Option<f32>,
 }
 
 /// Stores properties of an edge that can be changed. Used to apply changes to the graph.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Edge<
     N: Clone,
     E: Clone,
@@ -35,6 +54,25 @@ pub struct Edge<
     _marker: PhantomData<(N, Ty, Dn)>,
 }
 
+impl<
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        D: DisplayEdge<N, E, Ty, Ix, Dn>,
+    > Clone for Edge<N, E, Ty, Ix, Dn, D>
+{
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            display: self.display.clone(),
+            props: self.props.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
 impl<
         N: Clone,
         E: Clone,
@@ -51,6 +89,11 @@ impl<
             order: usize::default(),
             selected: bool::default(),
             label: String::default(),
+            hidden: bool::default(),
+            source_port: Option::default(),
+            target_port: Option::default(),
+            bends: Vec::default(),
+            weight: Option::default(),
         };
 
         let display = D::from(props.clone());
@@ -115,4 +158,46 @@ impl<
     pub fn label(&self) -> String {
         self.props.label.clone()
     }
+
+    pub fn source_port(&self) -> Option<&str> {
+        self.props.source_port.as_deref()
+    }
+
+    pub fn set_source_port(&mut self, port: Option<String>) {
+        self.props.source_port = port;
+    }
+
+    pub fn target_port(&self) -> Option<&str> {
+        self.props.target_port.as_deref()
+    }
+
+    pub fn set_target_port(&mut self, port: Option<String>) {
+        self.props.target_port = port;
+    }
+
+    pub fn bends(&self) -> &[Pos2] {
+        &self.props.bends
+    }
+
+    /// Routes the edge through the given canvas points, an empty list draws it directly.
+    pub fn set_bends(&mut self, bends: Vec<Pos2>) {
+        self.props.bends = bends;
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.props.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.props.hidden = hidden;
+    }
+
+    pub fn weight(&self) -> Option<f32> {
+        self.props.weight
+    }
+
+    /// Sets the weight used by weight aware layouts, see [`EdgeProps::weight`].
+    pub fn set_weight(&mut self, weight: Option<f32>) {
+        self.props.weight = weight;
+    }
 }
@@ -1,5 +1,8 @@
 mod edge;
 mod node;
+mod port;
 
 pub use edge::{Edge, EdgeProps};
 pub use node::{Node, NodeProps};
+pub(crate) use port::PORT_RADIUS;
+pub use port::{Port, PortKind};
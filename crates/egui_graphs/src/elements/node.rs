@@ -8,7 +8,7 @@ use petgraph::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{DefaultNodeShape, DisplayNode};
+use crate::{DefaultNodeShape, DisplayNode, Port};
 
 /// Stores properties of a [Node]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,6 +21,16 @@ where
     pub selected: bool,
     pub dragged: bool,
     pub hovered: bool,
+    /// Hidden nodes stay in the graph but are not drawn, hit-tested or laid out.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Pinned nodes keep their location: layouts do not move them, they still push and pull
+    /// the other nodes. Dragging moves pinned nodes as usual.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Named anchor points edges can be attached to, see [`Port`].
+    #[serde(default)]
+    pub ports: Vec<Port>,
 
     color: Option<Color32>,
     location: Pos2,
@@ -107,6 +117,9 @@ where
             selected: bool::default(),
             dragged: bool::default(),
             hovered: bool::default(),
+            hidden: bool::default(),
+            pinned: bool::default(),
+            ports: Vec::default(),
         };
 
         Node::new_with_props(props)
@@ -193,6 +206,41 @@ where
         self.props.hovered = hovered;
     }
 
+    pub fn hidden(&self) -> bool {
+        self.props.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.props.hidden = hidden;
+    }
+
+    pub fn pinned(&self) -> bool {
+        self.props.pinned
+    }
+
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.props.pinned = pinned;
+    }
+
+    pub fn ports(&self) -> &[Port] {
+        &self.props.ports
+    }
+
+    /// Port with the given name if the node declares one.
+    pub fn port(&self, name: &str) -> Option<&Port> {
+        self.props.ports.iter().find(|p| p.name == name)
+    }
+
+    /// Declares a port replacing any existing port with the same name.
+    pub fn add_port(&mut self, port: Port) {
+        self.props.ports.retain(|p| p.name != port.name);
+        self.props.ports.push(port);
+    }
+
+    pub fn set_ports(&mut self, ports: Vec<Port>) {
+        self.props.ports = ports;
+    }
+
     pub fn label(&self) -> String {
         self.props.label.clone()
     }
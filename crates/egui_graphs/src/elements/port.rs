@@ -0,0 +1,39 @@
+use egui::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Radius of the port markers drawn and hit-tested by the default node shape, canvas units.
+pub(crate) const PORT_RADIUS: f32 = 2.;
+
+/// Whether edges enter or leave a node through the [Port].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PortKind {
+    #[default]
+    Input,
+    Output,
+}
+
+/// Named anchor point on a node which edges can be attached to instead of the node boundary.
+///
+/// Edges reference ports by name, see [`crate::Edge::set_source_port`] and
+/// [`crate::Edge::set_target_port`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Port {
+    pub name: String,
+    pub kind: PortKind,
+    /// Position relative to the node center in canvas coordinates.
+    pub offset: Vec2,
+}
+
+impl Port {
+    pub fn new(name: String, kind: PortKind, offset: Vec2) -> Self {
+        Self { name, kind, offset }
+    }
+
+    pub fn input(name: String, offset: Vec2) -> Self {
+        Self::new(name, PortKind::Input, offset)
+    }
+
+    pub fn output(name: String, offset: Vec2) -> Self {
+        Self::new(name, PortKind::Output, offset)
+    }
+}
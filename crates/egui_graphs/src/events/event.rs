@@ -42,6 +42,9 @@ pub struct PayloadNodeDeselect {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PayloadNodeClick {
     pub id: usize,
+    /// Name of the clicked port of the node, if any.
+    #[serde(default)]
+    pub port: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -1,6 +1,8 @@
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
-use egui::{Pos2, Rect};
+use egui::{Pos2, Rect, Vec2};
 use petgraph::stable_graph::DefaultIx;
 use petgraph::Directed;
 
@@ -13,14 +15,129 @@ use petgraph::{
 use serde::{Deserialize, Serialize};
 
 use crate::draw::{DisplayEdge, DisplayNode};
+use crate::elements::PORT_RADIUS;
+use crate::groups::{CollapseState, Group, GroupId, GroupSet};
+use crate::history::{Change, EdgeOrders, History};
+use crate::spatial_index::SpatialIndex;
 use crate::{
-    default_edge_transform, default_node_transform, to_graph, DefaultEdgeShape, DefaultNodeShape,
+    default_edge_transform, default_node_transform, node_size, to_graph, DefaultEdgeShape,
+    DefaultNodeShape,
 };
-use crate::{metadata::MetadataFrame, Edge, Node};
+use crate::{metadata::MetadataFrame, Edge, EdgeProps, Node, NodeProps};
 
-type StableGraphType<N, E, Ty, Ix, Dn, De> =
+/// Extra margin (canvas units) added around indexed edge geometry to cover stroke width.
+const EDGE_INDEX_PADDING: f32 = 5.;
+
+pub(crate) type StableGraphType<N, E, Ty, Ix, Dn, De> =
     StableGraph<Node<N, E, Ty, Ix, Dn>, Edge<N, E, Ty, Ix, Dn, De>, Ty, Ix>;
 
+/// Type alias for the node visibility predicate, see [`Graph::set_node_filter`].
+pub type NodeFilter<N> = Arc<dyn Fn(&NodeProps<N>) -> bool + Send + Sync>;
+
+/// Type alias for the edge visibility predicate, see [`Graph::set_edge_filter`].
+pub type EdgeFilter<E> = Arc<dyn Fn(&EdgeProps<E>) -> bool + Send + Sync>;
+
+/// Type alias for the edge weight function, see [`Graph::set_edge_weight_fn`].
+pub type EdgeWeightFn<E> = Arc<dyn Fn(&EdgeProps<E>) -> f32 + Send + Sync>;
+
+/// Type alias for the node cluster function, see [`Graph::set_node_cluster_fn`].
+pub type NodeClusterFn<N> = Arc<dyn Fn(&NodeProps<N>) -> Option<u64> + Send + Sync>;
+
+/// Source of [`Graph::revision`] values. Shared by all graphs so that a revision seen on one
+/// graph never shows up on another.
+static REVISION: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    REVISION.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Visibility predicates of a [`Graph`]. Not serialized.
+#[derive(Clone)]
+struct Filters<N: Clone, E: Clone> {
+    node: Option<NodeFilter<N>>,
+    edge: Option<EdgeFilter<E>>,
+}
+
+impl<N: Clone, E: Clone> Default for Filters<N, E> {
+    fn default() -> Self {
+        Self {
+            node: None,
+            edge: None,
+        }
+    }
+}
+
+impl<N: Clone, E: Clone> core::fmt::Debug for Filters<N, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Filters")
+            .field("node", &self.node.as_ref().map(|_| "<filter>"))
+            .field("edge", &self.edge.as_ref().map(|_| "<filter>"))
+            .finish()
+    }
+}
+
+/// Edge weight function of a [`Graph`]. Not serialized.
+#[derive(Clone)]
+struct EdgeWeights<E: Clone>(Option<EdgeWeightFn<E>>);
+
+impl<E: Clone> Default for EdgeWeights<E> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<E: Clone> core::fmt::Debug for EdgeWeights<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("EdgeWeights")
+            .field(&self.0.as_ref().map(|_| "<weight>"))
+            .finish()
+    }
+}
+
+/// Node cluster function of a [`Graph`]. Not serialized.
+#[derive(Clone)]
+struct NodeClusters<N: Clone>(Option<NodeClusterFn<N>>);
+
+impl<N: Clone> Default for NodeClusters<N> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<N: Clone> core::fmt::Debug for NodeClusters<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("NodeClusters")
+            .field(&self.0.as_ref().map(|_| "<cluster>"))
+            .finish()
+    }
+}
+
+/// Elements whose geometry may have changed since the last [`Graph::sync_spatial_index`].
+#[derive(Debug, Clone)]
+struct DirtyElements<Ix: IndexType> {
+    /// Set when the graph was changed bypassing the tracked accessors: the next sync walks
+    /// the whole graph.
+    all: bool,
+    nodes: HashSet<NodeIndex<Ix>>,
+    edges: HashSet<EdgeIndex<Ix>>,
+    /// Elements synced last time. Their display state is refreshed only when they are drawn,
+    /// so they are synced once more to pick up size and shape changes.
+    settling_nodes: Vec<NodeIndex<Ix>>,
+    settling_edges: Vec<EdgeIndex<Ix>>,
+}
+
+impl<Ix: IndexType> Default for DirtyElements<Ix> {
+    fn default() -> Self {
+        Self {
+            all: true,
+            nodes: HashSet::new(),
+            edges: HashSet::new(),
+            settling_nodes: Vec::new(),
+            settling_edges: Vec::new(),
+        }
+    }
+}
+
 /// Wrapper around [`petgraph::stable_graph::StableGraph`] compatible with [`super::GraphView`].
 /// It is used to store graph data and provide access to it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +164,30 @@ pub struct Graph<
     hovered_node: Option<NodeIndex<Ix>>,
 
     bounds: Rect,
+
+    #[serde(skip)]
+    index: SpatialIndex<Ix>,
+    #[serde(skip, default = "DirtyElements::default")]
+    dirty: DirtyElements<Ix>,
+
+    #[serde(skip, default = "History::default")]
+    history: History<N, E, Ty, Ix, Dn, De>,
+
+    #[serde(default = "GroupSet::default")]
+    groups: GroupSet<Ix>,
+    #[serde(skip, default = "CollapseState::default")]
+    collapse: CollapseState<Ix>,
+
+    #[serde(skip, default = "Filters::default")]
+    filters: Filters<N, E>,
+    #[serde(skip, default = "EdgeWeights::default")]
+    edge_weights: EdgeWeights<E>,
+    #[serde(skip, default = "NodeClusters::default")]
+    node_clusters: NodeClusters<N>,
+
+    /// See [`Graph::revision`].
+    #[serde(skip, default = "next_revision")]
+    revision: u64,
 }
 
 impl<N, E, Ty, Ix, Dn, De> From<&StableGraph<N, E, Ty, Ix>> for Graph<N, E, Ty, Ix, Dn, De>
@@ -72,56 +213,1023 @@ where
     Dn: DisplayNode<N, E, Ty, Ix>,
     De: DisplayEdge<N, E, Ty, Ix, Dn>,
 {
-    pub fn new(g: StableGraphType<N, E, Ty, Ix, Dn, De>) -> Self {
-        Self {
+    pub fn new(mut g: StableGraphType<N, E, Ty, Ix, Dn, De>) -> Self {
+        // Elements built outside of `to_graph` may not know their indices yet.
+        let node_ids = g.node_indices().collect::<Vec<_>>();
+        for idx in node_ids {
+            g[idx].set_id(idx);
+        }
+        let edge_ids = g.edge_indices().collect::<Vec<_>>();
+        for idx in edge_ids {
+            g[idx].set_id(idx);
+        }
+
+        let mut res = Self {
             g,
             selected_nodes: Vec::default(),
             selected_edges: Vec::default(),
             dragged_node: Option::default(),
             hovered_node: Option::default(),
             bounds: Rect::from_min_max(Pos2::ZERO, Pos2::ZERO),
-        }
+            index: SpatialIndex::default(),
+            dirty: DirtyElements::default(),
+            history: History::default(),
+            groups: GroupSet::default(),
+            collapse: CollapseState::default(),
+            filters: Filters::default(),
+            edge_weights: EdgeWeights::default(),
+            node_clusters: NodeClusters::default(),
+            revision: next_revision(),
+        };
+        res.sync_spatial_index();
+        res
+    }
+
+    /// Finds the port under the position, returns the node declaring it and the port name.
+    ///
+    /// Ports are reported by [`DisplayNode::port_at`]. If several nodes overlap the one with
+    /// the lowest index wins.
+    pub fn port_by_screen_pos(
+        &self,
+        meta: &MetadataFrame,
+        screen_pos: Pos2,
+    ) -> Option<(NodeIndex<Ix>, String)> {
+        let pos_in_graph = meta.screen_to_canvas_pos(screen_pos);
+        let mut candidates = self
+            .index
+            .node_candidates(pos_in_graph, self.index.cell_size());
+        candidates.sort_unstable();
+        candidates
+            .into_iter()
+            .filter(|idx| self.is_node_visible(*idx))
+            .find_map(|idx| {
+                self.g[idx]
+                    .display()
+                    .port_at(pos_in_graph)
+                    .map(|port| (idx, port))
+            })
     }
 
-    /// Finds node by position. Can be optimized by using a spatial index like quad-tree if needed.
+    /// Finds node by position.
+    ///
+    /// Only nodes found in the spatial index around the position are tested, see
+    /// [`Graph::sync_spatial_index`]. If several nodes overlap the one with the lowest index wins.
     pub fn node_by_screen_pos(
         &self,
         meta: &MetadataFrame,
         screen_pos: Pos2,
     ) -> Option<NodeIndex<Ix>> {
         let pos_in_graph = meta.screen_to_canvas_pos(screen_pos);
-        for (idx, node) in self.nodes_iter() {
-            let display = node.display();
-            if display.is_inside(pos_in_graph) {
-                return Some(idx);
-            }
-        }
-        None
+        // Display state may lag behind the indexed location by a frame, so search one cell wider.
+        self.index
+            .node_candidates(pos_in_graph, self.index.cell_size())
+            .into_iter()
+            .filter(|idx| {
+                self.is_node_visible(*idx) && self.g[*idx].display().is_inside(pos_in_graph)
+            })
+            .min()
     }
 
     /// Finds edge by position.
-    #[allow(clippy::missing_panics_doc)] // TODO: add panics doc
+    ///
+    /// Only edges found in the spatial index around the position are tested, see
+    /// [`Graph::sync_spatial_index`]. If several edges overlap the one with the lowest index wins.
     pub fn edge_by_screen_pos(
         &self,
         meta: &MetadataFrame,
         screen_pos: Pos2,
     ) -> Option<EdgeIndex<Ix>> {
         let pos_in_graph = meta.screen_to_canvas_pos(screen_pos);
-        for (idx, e) in self.edges_iter() {
-            let Some((idx_start, idx_end)) = self.g.edge_endpoints(e.id()) else {
+        self.index
+            .edge_candidates(pos_in_graph)
+            .into_iter()
+            .filter(|idx| {
+                let Some((idx_start, idx_end)) = self.visible_edge_endpoints(*idx) else {
+                    return false;
+                };
+                let (Some(e), Some(start), Some(end)) = (
+                    self.g.edge_weight(*idx),
+                    self.g.node_weight(idx_start),
+                    self.g.node_weight(idx_end),
+                ) else {
+                    return false;
+                };
+                e.display().is_inside(start, end, pos_in_graph)
+            })
+            .min()
+    }
+
+    /// Returns all nodes whose location lies inside the `rect` (canvas coordinates).
+    pub fn nodes_in_rect(&self, rect: Rect) -> Vec<NodeIndex<Ix>> {
+        let mut res = self.index.nodes_in_rect(rect);
+        res.sort_unstable();
+        res
+    }
+
+    /// Returns the node whose location is the closest to `pos` (canvas coordinates).
+    pub fn nearest_node(&self, pos: Pos2) -> Option<NodeIndex<Ix>> {
+        self.index.nearest_node(pos)
+    }
+
+    /// Sets the size of the spatial index grid cell (canvas units) and rebuilds the index.
+    ///
+    /// Cells a few times larger than a typical node work best. Default: `50.0`.
+    pub fn set_spatial_cell_size(&mut self, cell_size: f32) {
+        self.index.reset(cell_size);
+        self.resync_spatial_index();
+    }
+
+    /// Brings the spatial index in line with the current node locations and edge geometry.
+    ///
+    /// Only elements accessed through [`Graph::node_mut`] and [`Graph::edge_mut`] since the
+    /// last sync are reindexed, together with the edges of such nodes. Accessing the graph
+    /// through [`Graph::g_mut`] makes the next sync walk the whole graph.
+    ///
+    /// [`super::GraphView`] calls this once per frame after the layout step. Call it yourself
+    /// if you move nodes through [`Graph::node_mut`] or [`Graph::g_mut`] and query the graph
+    /// before the next frame. Moving nodes with [`Graph::set_node_location`] keeps the index
+    /// up to date.
+    pub fn sync_spatial_index(&mut self) {
+        if !self.collapse.is_built() {
+            self.collapse.rebuild(&self.groups, &self.g);
+            self.dirty.all = true;
+        }
+
+        if !self.dirty.all {
+            self.sync_dirty_elements();
+            return;
+        }
+
+        self.dirty = DirtyElements {
+            all: false,
+            ..DirtyElements::default()
+        };
+        let (nodes, edges) = self.sync_spatial_index_pass();
+
+        // Every visible element of the graph is indexed now, so any surplus entries belong to
+        // elements removed bypassing the graph methods. Start from scratch in that case.
+        if self.index.nodes_len() != nodes || self.index.edges_len() != edges {
+            self.index.reset(self.index.cell_size());
+            self.sync_spatial_index_pass();
+        }
+    }
+
+    /// Rebuilds the index entries of all elements, e.g. after visibility rules changed.
+    /// Counter changed by everything layouts may care about besides node locations: adding,
+    /// removing, hiding and pinning elements, filters, groups, edge weight and node cluster
+    /// functions, undo and redo as well as any access through [`Graph::node_mut`],
+    /// [`Graph::edge_mut`] and [`Graph::g_mut`]. Revisions are unique across graphs.
+    ///
+    /// Layouts keeping a copy of the graph, e.g. `BackgroundLayout`, compare it
+    /// between frames to find out whether the copy is still current.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn touch(&mut self) {
+        self.revision = next_revision();
+    }
+
+    fn resync_spatial_index(&mut self) {
+        self.touch();
+        self.dirty.all = true;
+        self.sync_spatial_index();
+    }
+
+    /// Reindexes elements changed since the last sync and the ones still settling.
+    fn sync_dirty_elements(&mut self) {
+        let nodes: Vec<_> = self.dirty.nodes.drain().collect();
+        let edges: Vec<_> = self.dirty.edges.drain().collect();
+        let settling_nodes = std::mem::replace(&mut self.dirty.settling_nodes, nodes);
+        let settling_edges = std::mem::replace(&mut self.dirty.settling_edges, edges);
+
+        let mut seen = HashSet::new();
+        let nodes: Vec<_> = self
+            .dirty
+            .settling_nodes
+            .iter()
+            .chain(&settling_nodes)
+            .copied()
+            .filter(|idx| seen.insert(*idx))
+            .collect();
+        let mut edges = HashSet::new();
+        for idx in nodes {
+            self.index_node(idx);
+            edges.extend(self.incident_edges(idx));
+        }
+        edges.extend(self.dirty.settling_edges.iter().copied());
+        edges.extend(settling_edges);
+        for idx in edges {
+            self.index_edge(idx);
+        }
+    }
+
+    /// Edges drawn at the node: its own edges and edges rerouted to it.
+    fn incident_edges(&self, idx: NodeIndex<Ix>) -> Vec<EdgeIndex<Ix>> {
+        self.g
+            .edges_directed(idx, Direction::Outgoing)
+            .chain(self.g.edges_directed(idx, Direction::Incoming))
+            .map(|e| e.id())
+            .chain(self.collapse.rerouted_at(idx).iter().copied())
+            .collect()
+    }
+
+    /// Indexes visible elements and drops hidden ones. Returns visible nodes and edges count.
+    fn sync_spatial_index_pass(&mut self) -> (usize, usize) {
+        let mut nodes = 0;
+        for (idx, n) in self.g.node_references() {
+            if !self.is_node_visible(idx) {
+                self.index.remove_node(idx);
                 continue;
+            }
+
+            nodes += 1;
+            let (loc, extent) = (n.location(), node_extent(n));
+            if self.index.node_changed(idx, loc, extent) {
+                self.index.upsert_node(idx, loc, extent);
+            }
+        }
+
+        let mut edges = 0;
+        let edge_indices: Vec<_> = self.g.edge_indices().collect();
+        for idx in edge_indices {
+            if self.index_edge(idx) {
+                edges += 1;
+            }
+        }
+
+        (nodes, edges)
+    }
+
+    fn index_node(&mut self, idx: NodeIndex<Ix>) {
+        match self.g.node_weight(idx) {
+            Some(n) if self.is_node_visible(idx) => {
+                self.index.upsert_node(idx, n.location(), node_extent(n));
+            }
+            _ => self.index.remove_node(idx),
+        }
+    }
+
+    /// Indexes the edge if it is visible, drops it from the index otherwise.
+    fn index_edge(&mut self, idx: EdgeIndex<Ix>) -> bool {
+        if let Some(rect) = self.edge_rect(idx) {
+            self.index.upsert_edge(idx, rect);
+            true
+        } else {
+            self.index.remove_edge(idx);
+            false
+        }
+    }
+
+    /// Canvas rect covering edge geometry: endpoints, node extents and edge extra bounds.
+    fn edge_rect(&self, idx: EdgeIndex<Ix>) -> Option<Rect> {
+        let (idx_start, idx_end) = self.visible_edge_endpoints(idx)?;
+        let e = self.g.edge_weight(idx)?;
+        let start = self.g.node_weight(idx_start)?;
+        let end = self.g.node_weight(idx_end)?;
+
+        let pad = node_extent(start).max(node_extent(end));
+        let mut rect = Rect::from_two_pos(start.location(), end.location()).expand(pad);
+        if let Some((min, max)) = e.display().extra_bounds(start, end) {
+            rect = rect.union(Rect::from_min_max(min, max));
+        }
+
+        Some(rect.expand(EDGE_INDEX_PADDING))
+    }
+
+    /// Sets node location keeping the spatial index in sync. Bends of the incident edges are
+    /// dropped as they no longer match the endpoints.
+    ///
+    /// The move is recorded in the history, see [`Graph::set_history_enabled`].
+    pub fn set_node_location(&mut self, idx: NodeIndex<Ix>, loc: Pos2) {
+        let Some(from) = self.g.node_weight(idx).map(Node::location) else {
+            return;
+        };
+        if self.history.enabled() {
+            let bends = self
+                .incident_edges(idx)
+                .into_iter()
+                .filter_map(|e| {
+                    let bends = self.g.edge_weight(e)?.bends();
+                    (!bends.is_empty()).then(|| (e, bends.to_vec()))
+                })
+                .collect();
+            self.history.record(Change::MoveNode {
+                idx,
+                from,
+                to: loc,
+                bends,
+            });
+        }
+        self.move_node_raw(idx, loc);
+    }
+
+    fn move_node_raw(&mut self, idx: NodeIndex<Ix>, loc: Pos2) {
+        let Some(n) = self.g.node_weight_mut(idx) else {
+            return;
+        };
+        n.set_location(loc);
+
+        self.index_node(idx);
+        for e in self.incident_edges(idx) {
+            if let Some(edge) = self.g.edge_weight_mut(e) {
+                edge.set_bends(Vec::new());
+            }
+            self.index_edge(e);
+        }
+        // Edge geometry follows the node display, which catches up when it is drawn.
+        self.dirty.nodes.insert(idx);
+    }
+
+    /// Drops bend points of all edges. Layouts which do not route edges call it before placing
+    /// nodes so that edges bent by a previous layout are drawn straight again.
+    pub fn clear_edge_bends(&mut self) {
+        let bent: Vec<_> = self
+            .g
+            .edge_references()
+            .filter(|e| !e.weight().bends().is_empty())
+            .map(|e| e.id())
+            .collect();
+        for idx in bent {
+            self.g[idx].set_bends(Vec::new());
+            self.dirty.edges.insert(idx);
+        }
+    }
+
+    /// Creates a group of nodes and returns its id.
+    ///
+    /// Returns None if some of the nodes does not exist, already belongs to a group or
+    /// represents a collapsed group.
+    pub fn add_group(&mut self, label: String, members: Vec<NodeIndex<Ix>>) -> Option<GroupId> {
+        let valid = members.iter().all(|m| {
+            self.g.contains_node(*m)
+                && self.groups.group_of(*m).is_none()
+                && self.groups.group_of_meta(*m).is_none()
+        });
+        if !valid {
+            return None;
+        }
+
+        let mut unique = HashSet::new();
+        let members = members.into_iter().filter(|m| unique.insert(*m)).collect();
+
+        let from = self.groups.clone();
+        let id = self.groups.add(label, members);
+        self.record_groups(from);
+
+        Some(id)
+    }
+
+    /// Removes the group expanding it first if it is collapsed. Member nodes are kept.
+    pub fn remove_group(&mut self, id: GroupId) -> bool {
+        if self.groups.get(id).is_none() {
+            return false;
+        }
+
+        self.history.begin();
+        self.expand_group(id);
+        let from = self.groups.clone();
+        self.groups.remove(id);
+        self.record_groups(from);
+        self.history.commit();
+
+        true
+    }
+
+    pub fn group(&self, id: GroupId) -> Option<&Group<Ix>> {
+        self.groups.get(id)
+    }
+
+    /// Provides iterator over all groups and their ids.
+    pub fn groups_iter(&self) -> impl Iterator<Item = (GroupId, &Group<Ix>)> {
+        self.groups.iter()
+    }
+
+    /// Returns the group the node is a member of.
+    pub fn node_group(&self, idx: NodeIndex<Ix>) -> Option<GroupId> {
+        self.groups.group_of(idx)
+    }
+
+    /// Collapses the group into a single meta node placed at the center of the members.
+    ///
+    /// The meta node is a regular node carrying `payload` and the group label. Members are
+    /// hidden and their edges to the rest of the graph are displayed, hit-tested and laid out
+    /// as edges of the meta node, edges between members are hidden. Returns the meta node or
+    /// None if the group does not exist or is already collapsed.
+    pub fn collapse_group(&mut self, id: GroupId, payload: N) -> Option<NodeIndex<Ix>> {
+        let group = self.groups.get(id)?;
+        if group.is_collapsed() {
+            return None;
+        }
+        let members = group.members().to_vec();
+        let label = group.label().to_string();
+
+        let locations: Vec<Pos2> = members
+            .iter()
+            .filter_map(|m| self.g.node_weight(*m))
+            .map(Node::location)
+            .collect();
+        let center = if locations.is_empty() {
+            Pos2::ZERO
+        } else {
+            let sum = locations
+                .iter()
+                .fold(Vec2::ZERO, |acc, l| acc + l.to_vec2());
+            (sum / locations.len() as f32).to_pos2()
+        };
+        let offsets = members
+            .iter()
+            .map(|m| {
+                self.g
+                    .node_weight(*m)
+                    .map_or(Vec2::ZERO, |n| n.location() - center)
+            })
+            .collect();
+
+        self.history.begin();
+        let from = self.groups.clone();
+        let meta = self.add_node_with_label_and_location(payload, label, center);
+        if let Some(group) = self.groups.get_mut(id) {
+            group.collapse(meta, offsets);
+        }
+        self.record_groups(from);
+        self.refresh_groups();
+        self.history.commit();
+
+        Some(meta)
+    }
+
+    /// Expands a collapsed group: removes the meta node and places the members around its
+    /// current location as they were placed around it on collapse. Returns false if the group
+    /// does not exist or is not collapsed.
+    pub fn expand_group(&mut self, id: GroupId) -> bool {
+        if !self.groups.get(id).is_some_and(Group::is_collapsed) {
+            return false;
+        }
+
+        self.expand_group_inner(id);
+        true
+    }
+
+    fn expand_group_inner(&mut self, id: GroupId) -> Option<Node<N, E, Ty, Ix, Dn>> {
+        let group = self.groups.get(id)?;
+        let meta = group.meta_node()?;
+        let members = group.members().to_vec();
+        let offsets = group.member_offsets().unwrap_or_default().to_vec();
+        let center = self.g.node_weight(meta).map(Node::location);
+
+        self.history.begin();
+        let from = self.groups.clone();
+        if let Some(group) = self.groups.get_mut(id) {
+            group.expand();
+        }
+        self.record_groups(from);
+        self.refresh_groups();
+
+        let removed = self.remove_node(meta);
+        if let Some(center) = center {
+            for (m, offset) in members.iter().zip(offsets) {
+                self.set_node_location(*m, center + offset);
+            }
+        }
+        self.history.commit();
+
+        removed
+    }
+
+    /// Distance from the node location to the farthest point of its shape or ports, as of the
+    /// last time the node was drawn. Size aware layouts and forces keep nodes this far apart.
+    /// None if the node does not exist.
+    pub fn node_extent(&self, idx: NodeIndex<Ix>) -> Option<f32> {
+        self.g.node_weight(idx).map(node_extent)
+    }
+
+    /// Canvas rect framing the members of an expanded group.
+    /// None if the group does not exist, is collapsed or has no visible members.
+    pub fn group_rect(&self, id: GroupId) -> Option<Rect> {
+        let group = self.groups.get(id)?;
+        if group.is_collapsed() {
+            return None;
+        }
+
+        group
+            .members()
+            .iter()
+            .filter(|m| self.is_node_visible(**m))
+            .filter_map(|m| self.g.node_weight(*m))
+            .map(|n| Rect::from_center_size(n.location(), Vec2::splat(2. * node_extent(n))))
+            .reduce(Rect::union)
+    }
+
+    /// Returns false for nodes which do not exist, are hidden, rejected by the node filter or
+    /// hidden in a collapsed group.
+    pub fn is_node_visible(&self, idx: NodeIndex<Ix>) -> bool {
+        let Some(n) = self.g.node_weight(idx) else {
+            return false;
+        };
+        !n.hidden()
+            && self.filters.node.as_ref().is_none_or(|f| f(n.props()))
+            && !self.collapse.node_hidden(idx)
+    }
+
+    /// Provides iterator over the indices of visible nodes, see [`Graph::is_node_visible`].
+    pub fn visible_node_indices(&self) -> impl Iterator<Item = NodeIndex<Ix>> + '_ {
+        self.g
+            .node_indices()
+            .filter(|idx| self.is_node_visible(*idx))
+    }
+
+    /// Endpoints the edge is displayed with. Edges of collapsed group members are rerouted
+    /// to the meta node of the group. None if the edge does not exist, is hidden, rejected by
+    /// the edge filter or any of its displayed endpoints is not visible.
+    pub fn visible_edge_endpoints(
+        &self,
+        idx: EdgeIndex<Ix>,
+    ) -> Option<(NodeIndex<Ix>, NodeIndex<Ix>)> {
+        let e = self.g.edge_weight(idx)?;
+        if e.hidden() || !self.filters.edge.as_ref().is_none_or(|f| f(e.props())) {
+            return None;
+        }
+
+        let real = self.g.edge_endpoints(idx)?;
+        let (start, end) = self.collapse.edge_endpoints(idx, real)?;
+        (self.is_node_visible(start) && self.is_node_visible(end)).then_some((start, end))
+    }
+
+    /// Hides or shows the node keeping the spatial index in sync. Edges of a hidden node are
+    /// hidden as well.
+    pub fn set_node_hidden(&mut self, idx: NodeIndex<Ix>, hidden: bool) {
+        let Some(n) = self.g.node_weight_mut(idx) else {
+            return;
+        };
+        n.set_hidden(hidden);
+        self.touch();
+        self.index_node(idx);
+        for e in self.incident_edges(idx) {
+            self.index_edge(e);
+        }
+    }
+
+    /// Hides or shows the edge keeping the spatial index in sync.
+    pub fn set_edge_hidden(&mut self, idx: EdgeIndex<Ix>, hidden: bool) {
+        let Some(e) = self.g.edge_weight_mut(idx) else {
+            return;
+        };
+        e.set_hidden(hidden);
+        self.touch();
+        self.index_edge(idx);
+    }
+
+    /// Sets the predicate deciding which nodes are visible. Nodes rejected by the filter are
+    /// treated as hidden: they are not drawn, hit-tested or laid out.
+    pub fn set_node_filter<F>(&mut self, f: F)
+    where
+        F: Fn(&NodeProps<N>) -> bool + Send + Sync + 'static,
+    {
+        self.filters.node = Some(Arc::new(f));
+        self.resync_spatial_index();
+    }
+
+    /// Sets the predicate deciding which edges are visible, see [`Graph::set_node_filter`].
+    pub fn set_edge_filter<F>(&mut self, f: F)
+    where
+        F: Fn(&EdgeProps<E>) -> bool + Send + Sync + 'static,
+    {
+        self.filters.edge = Some(Arc::new(f));
+        self.resync_spatial_index();
+    }
+
+    /// Removes both node and edge filters.
+    pub fn clear_filters(&mut self) {
+        self.filters = Filters::default();
+        self.resync_spatial_index();
+    }
+
+    /// Sets the function reading edge weights from edge props, e.g. a cost stored in the
+    /// payload. Takes precedence over [`EdgeProps::weight`]. Weight aware layouts scale ideal
+    /// edge lengths or attraction by it.
+    pub fn set_edge_weight_fn<F>(&mut self, f: F)
+    where
+        F: Fn(&EdgeProps<E>) -> f32 + Send + Sync + 'static,
+    {
+        self.edge_weights = EdgeWeights(Some(Arc::new(f)));
+        self.touch();
+    }
+
+    /// Removes the edge weight function, edges weigh [`EdgeProps::weight`] again.
+    pub fn clear_edge_weight_fn(&mut self) {
+        self.edge_weights = EdgeWeights::default();
+        self.touch();
+    }
+
+    /// Weight of the edge given by the edge weight function, without one the
+    /// [`EdgeProps::weight`] of the edge or 1. None if the edge does not exist.
+    pub fn edge_weight(&self, idx: EdgeIndex<Ix>) -> Option<f32> {
+        let e = self.g.edge_weight(idx)?;
+        Some(match &self.edge_weights.0 {
+            Some(f) => f(e.props()),
+            None => e.weight().unwrap_or(1.),
+        })
+    }
+
+    /// Sets the function assigning nodes to clusters, e.g. a community id stored in the payload
+    /// or looked up in a shared table. Nodes mapped to None belong to no cluster. Cluster
+    /// aware layouts query it on every step, so the assignment may change between steps.
+    pub fn set_node_cluster_fn<F>(&mut self, f: F)
+    where
+        F: Fn(&NodeProps<N>) -> Option<u64> + Send + Sync + 'static,
+    {
+        self.node_clusters = NodeClusters(Some(Arc::new(f)));
+        self.touch();
+    }
+
+    /// Removes the node cluster function, nodes are clustered by their groups again.
+    pub fn clear_node_cluster_fn(&mut self) {
+        self.node_clusters = NodeClusters::default();
+        self.touch();
+    }
+
+    /// Cluster of the node given by the node cluster function, without one the index of the
+    /// group the node is a member of. None for nodes in no cluster or which do not exist.
+    pub fn node_cluster(&self, idx: NodeIndex<Ix>) -> Option<u64> {
+        let n = self.g.node_weight(idx)?;
+        match &self.node_clusters.0 {
+            Some(f) => f(n.props()),
+            None => self.groups.group_of(idx).map(|id| id.index() as u64),
+        }
+    }
+
+    /// Clusters of the nodes in one pass, see [`Graph::node_cluster`]. Cluster aware layouts
+    /// call it once per step.
+    pub fn node_clusters(&self, indices: &[NodeIndex<Ix>]) -> Vec<Option<u64>> {
+        match &self.node_clusters.0 {
+            Some(f) => indices
+                .iter()
+                .map(|&idx| self.g.node_weight(idx).and_then(|n| f(n.props())))
+                .collect(),
+            None => indices
+                .iter()
+                .map(|&idx| self.groups.group_of(idx).map(|id| id.index() as u64))
+                .collect(),
+        }
+    }
+
+    /// Parallel edges order the edge is displayed with. Differs from [`Edge::order`] for
+    /// edges rerouted to a collapsed group.
+    pub fn visible_edge_order(&self, idx: EdgeIndex<Ix>) -> Option<usize> {
+        self.collapse
+            .rerouted_order(idx)
+            .or_else(|| self.g.edge_weight(idx).map(Edge::order))
+    }
+
+    /// Neighbors of a visible node as displayed, see [`Graph::visible_edge_endpoints`].
+    /// With `dir` set to None neighbors in both directions are returned.
+    pub fn visible_neighbors(
+        &self,
+        idx: NodeIndex<Ix>,
+        dir: Option<Direction>,
+    ) -> Vec<NodeIndex<Ix>> {
+        self.visible_neighbor_edges(idx, dir)
+            .into_iter()
+            .map(|(_, other)| other)
+            .collect()
+    }
+
+    /// Like [`Graph::visible_neighbors`] together with the edge leading to every neighbor, e.g.
+    /// to look up its [`Graph::edge_weight`].
+    pub fn visible_neighbor_edges(
+        &self,
+        idx: NodeIndex<Ix>,
+        dir: Option<Direction>,
+    ) -> Vec<(EdgeIndex<Ix>, NodeIndex<Ix>)> {
+        if !self.is_node_visible(idx) {
+            return Vec::new();
+        }
+
+        let mut edges: Vec<_> = self
+            .g
+            .edges_directed(idx, Direction::Outgoing)
+            .map(|e| e.id())
+            .collect();
+        if self.g.is_directed() {
+            edges.extend(
+                self.g
+                    .edges_directed(idx, Direction::Incoming)
+                    .map(|e| e.id()),
+            );
+        }
+        // Edges rerouted away from the node are listed at their displayed endpoints instead.
+        edges.retain(|e| self.collapse.rerouted_order(*e).is_none());
+        edges.extend_from_slice(self.collapse.rerouted_at(idx));
+
+        let dir = dir.filter(|_| self.g.is_directed());
+        let mut res = Vec::with_capacity(edges.len());
+        for e in edges {
+            let Some((start, end)) = self.visible_edge_endpoints(e) else {
+                continue;
+            };
+            let other = match dir {
+                Some(Direction::Outgoing) if start == idx => end,
+                Some(Direction::Incoming) if end == idx => start,
+                None if start == idx => end,
+                None => start,
+                _ => continue,
             };
-            let start = self.g.node_weight(idx_start).unwrap();
-            let end = self.g.node_weight(idx_end).unwrap();
-            if e.display().is_inside(start, end, pos_in_graph) {
-                return Some(idx);
+            res.push((e, other));
+        }
+
+        res
+    }
+
+    fn record_groups(&mut self, from: GroupSet<Ix>) {
+        self.touch();
+        if self.history.enabled() {
+            self.history.record(Change::Groups {
+                from,
+                to: self.groups.clone(),
+            });
+        }
+    }
+
+    /// Hides or reroutes an added edge if it touches a collapsed group.
+    fn route_added_edge(&mut self, idx: EdgeIndex<Ix>) {
+        if self.collapse.is_empty() {
+            return;
+        }
+        let Some(real) = self.g.edge_endpoints(idx) else {
+            return;
+        };
+        let directed = self.g.is_directed();
+        let reordered = self.collapse.add_edge(idx, real, directed);
+        self.dirty.edges.extend(reordered);
+    }
+
+    /// Drops a removed edge from the collapsed groups view.
+    fn route_removed_edge(&mut self, idx: EdgeIndex<Ix>) {
+        if self.collapse.is_empty() {
+            return;
+        }
+        let directed = self.g.is_directed();
+        let reordered = self.collapse.remove_edge(idx, directed);
+        self.dirty.edges.extend(reordered);
+    }
+
+    /// Recomputes hidden and rerouted elements after groups changed.
+    fn refresh_groups(&mut self) {
+        let was_empty = self.collapse.is_empty();
+        self.collapse.rebuild(&self.groups, &self.g);
+        if !(was_empty && self.collapse.is_empty()) {
+            self.resync_spatial_index();
+        }
+    }
+
+    /// Enables or disables recording of changes for [`Graph::undo`] and [`Graph::redo`].
+    /// Disabling drops the recorded history. Default: `false`.
+    ///
+    /// Recorded are the changes made through [`Graph`] methods: adding and removing nodes and
+    /// edges, [`Graph::set_node_location`] (node dragging in [`super::GraphView`]),
+    /// [`Graph::set_node_label`], [`Graph::set_edge_label`], [`Graph::set_node_pinned`] and
+    /// changes of groups. Selection changes are recorded only if enabled with
+    /// [`Graph::set_history_records_selection`]. Changing the graph through [`Graph::g_mut`] while history
+    /// is enabled invalidates it; call [`Graph::clear_history`] after doing so.
+    pub fn set_history_enabled(&mut self, enabled: bool) {
+        self.history.set_enabled(enabled);
+    }
+
+    pub fn history_enabled(&self) -> bool {
+        self.history.enabled()
+    }
+
+    /// Records [`Graph::set_node_selected`] and [`Graph::set_edge_selected`] changes, e.g.
+    /// clicks in [`super::GraphView`], as undo steps. Default: `false`.
+    pub fn set_history_records_selection(&mut self, enabled: bool) {
+        self.history.set_records_selection(enabled);
+    }
+
+    pub fn history_records_selection(&self) -> bool {
+        self.history.records_selection()
+    }
+
+    /// Sets the maximum number of undo steps kept. Default: `256`.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.set_limit(limit);
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Opens a transaction. Changes recorded until the matching [`Graph::commit_transaction`]
+    /// are undone and redone as a single step. Transactions can be nested, only the outermost
+    /// one produces a step.
+    pub fn begin_transaction(&mut self) {
+        self.history.begin();
+    }
+
+    /// Closes the transaction opened by [`Graph::begin_transaction`].
+    pub fn commit_transaction(&mut self) {
+        self.history.commit();
+    }
+
+    /// Opens the transaction of a node drag in [`super::GraphView`].
+    pub(crate) fn begin_drag_transaction(&mut self) {
+        self.history.begin_drag();
+    }
+
+    /// Closes the drag transaction, does nothing if the drag ended already.
+    pub(crate) fn commit_drag_transaction(&mut self) {
+        self.history.commit_drag();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Reverts the last recorded step. Closes an open transaction first.
+    /// Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(tx) = self.history.pop_undo() else {
+            return false;
+        };
+
+        self.touch();
+        for change in tx.iter().rev() {
+            self.apply_change(change, false);
+        }
+        self.history.push_redo(tx);
+        self.refresh_groups();
+        self.refresh_interaction_state();
+
+        true
+    }
+
+    /// Reapplies the last undone step. Returns false if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(tx) = self.history.pop_redo() else {
+            return false;
+        };
+
+        self.touch();
+        for change in &tx {
+            self.apply_change(change, true);
+        }
+        self.history.push_undo(tx);
+        self.refresh_groups();
+        self.refresh_interaction_state();
+
+        true
+    }
+
+    /// Applies the change (`forward`) or reverts it, bypassing the history.
+    ///
+    /// Elements are reinserted in the reverse order of their removal, so the stable graph hands
+    /// out the same indices from its free lists.
+    fn apply_change(&mut self, change: &Change<N, E, Ty, Ix, Dn, De>, forward: bool) {
+        match change {
+            Change::AddNode { idx, node } | Change::RemoveNode { idx, node } => {
+                let add = forward == matches!(change, Change::AddNode { .. });
+                if add {
+                    let restored = self.g.add_node(node.clone());
+                    debug_assert_eq!(restored, *idx, "history is out of sync with the graph");
+                    self.index_node(restored);
+                } else {
+                    self.index.remove_node(*idx);
+                    self.g.remove_node(*idx);
+                }
+            }
+            Change::AddEdge {
+                idx,
+                start,
+                end,
+                edge,
+                orders_before,
+                orders_after,
+            }
+            | Change::RemoveEdge {
+                idx,
+                start,
+                end,
+                edge,
+                orders_before,
+                orders_after,
+            } => {
+                let add = forward == matches!(change, Change::AddEdge { .. });
+                if add {
+                    let restored = self.g.add_edge(*start, *end, edge.clone());
+                    debug_assert_eq!(restored, *idx, "history is out of sync with the graph");
+                } else {
+                    self.index.remove_edge(*idx);
+                    self.g.remove_edge(*idx);
+                }
+
+                let orders = if forward { orders_after } else { orders_before };
+                for (e, order) in orders {
+                    if let Some(w) = self.g.edge_weight_mut(*e) {
+                        w.set_order(*order);
+                    }
+                    self.index_edge(*e);
+                }
+            }
+            Change::MoveNode {
+                idx,
+                from,
+                to,
+                bends,
+            } => {
+                self.move_node_raw(*idx, if forward { *to } else { *from });
+                if !forward {
+                    for (e, points) in bends {
+                        if let Some(edge) = self.g.edge_weight_mut(*e) {
+                            edge.set_bends(points.clone());
+                            self.dirty.edges.insert(*e);
+                        }
+                    }
+                }
+            }
+            Change::NodeLabel { idx, from, to } => {
+                if let Some(n) = self.g.node_weight_mut(*idx) {
+                    n.set_label(if forward { to } else { from }.clone());
+                }
+            }
+            Change::EdgeLabel { idx, from, to } => {
+                if let Some(e) = self.g.edge_weight_mut(*idx) {
+                    e.set_label(if forward { to } else { from }.clone());
+                }
+            }
+            Change::NodeSelected { idx, from, to } => {
+                if let Some(n) = self.g.node_weight_mut(*idx) {
+                    n.set_selected(if forward { *to } else { *from });
+                }
+            }
+            Change::NodePinned { idx, from, to } => {
+                if let Some(n) = self.g.node_weight_mut(*idx) {
+                    n.set_pinned(if forward { *to } else { *from });
+                }
+            }
+            Change::EdgeSelected { idx, from, to } => {
+                if let Some(e) = self.g.edge_weight_mut(*idx) {
+                    e.set_selected(if forward { *to } else { *from });
+                }
+            }
+            Change::Groups { from, to } => {
+                self.groups = if forward { to } else { from }.clone();
             }
         }
+    }
 
-        None
+    /// Rebuilds selection lists from element flags and forgets removed dragged/hovered nodes.
+    fn refresh_interaction_state(&mut self) {
+        self.selected_nodes = self
+            .g
+            .node_references()
+            .filter(|(_, n)| n.selected())
+            .map(|(idx, _)| idx)
+            .collect();
+        self.selected_edges = self
+            .g
+            .edge_references()
+            .filter(|e| e.weight().selected())
+            .map(|e| e.id())
+            .collect();
+        if self
+            .dragged_node
+            .is_some_and(|idx| !self.g.contains_node(idx))
+        {
+            self.dragged_node = None;
+        }
+        if self
+            .hovered_node
+            .is_some_and(|idx| !self.g.contains_node(idx))
+        {
+            self.hovered_node = None;
+        }
+    }
+
+    /// Orders of all edges between `a` and `b` in both directions.
+    fn edge_orders(&self, a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> EdgeOrders<Ix> {
+        let mut visited = HashSet::new();
+        self.g
+            .edges_connecting(a, b)
+            .chain(self.g.edges_connecting(b, a))
+            .filter(|e| visited.insert(e.id()))
+            .map(|e| (e.id(), e.weight().order()))
+            .collect()
     }
 
+    /// Raw access to the underlying graph. The next [`Graph::sync_spatial_index`] reindexes
+    /// all elements, prefer [`Graph::node_mut`] and [`Graph::edge_mut`] in code running on
+    /// every frame.
+    ///
+    /// Changes made here bypass the history, which is cleared as it can no longer be replayed
+    /// against the graph.
     pub fn g_mut(&mut self) -> &mut StableGraphType<N, E, Ty, Ix, Dn, De> {
+        self.touch();
+        self.dirty.all = true;
+        self.history.clear();
         &mut self.g
     }
 
@@ -150,6 +1258,14 @@ where
 
         node_transform(graph_node);
 
+        self.touch();
+        self.index_node(idx);
+
+        if self.history.enabled() {
+            let node = self.g[idx].clone();
+            self.history.record(Change::AddNode { idx, node });
+        }
+
         idx
     }
 
@@ -183,7 +1299,15 @@ where
     }
 
     /// Removes node by index. Returns removed node and None if it does not exist.
+    ///
+    /// Removing the meta node of a collapsed group expands the group.
     pub fn remove_node(&mut self, idx: NodeIndex<Ix>) -> Option<Node<N, E, Ty, Ix, Dn>> {
+        if let Some(id) = self.groups.group_of_meta(idx) {
+            return self.expand_group_inner(id);
+        }
+
+        self.history.begin();
+
         // before removing nodes we need to remove all edges connected to it
         let neighbors = self.g.neighbors_undirected(idx).collect::<Vec<_>>();
         for n in &neighbors {
@@ -191,7 +1315,30 @@ where
             self.remove_edges_between(*n, idx);
         }
 
-        self.g.remove_node(idx)
+        self.touch();
+        self.index.remove_node(idx);
+        let removed = self.g.remove_node(idx);
+        if let Some(node) = &removed {
+            if self.history.enabled() {
+                self.history.record(Change::RemoveNode {
+                    idx,
+                    node: node.clone(),
+                });
+            }
+        }
+
+        if let Some(id) = self.groups.group_of(idx) {
+            let collapsed = self.groups.get(id).is_some_and(Group::is_collapsed);
+            let from = self.groups.clone();
+            self.groups.remove_member(idx);
+            self.record_groups(from);
+            if collapsed {
+                self.refresh_groups();
+            }
+        }
+
+        self.history.commit();
+        removed
     }
 
     /// Removes all edges between start and end node. Returns removed edges count.
@@ -206,12 +1353,29 @@ where
             return 0;
         }
 
+        self.history.begin();
+
         let mut removed = 0;
         for e in &idxs {
-            self.g.remove_edge(*e).unwrap();
+            let orders_before = self.edge_orders(start, end);
+            let edge = self.g.remove_edge(*e).unwrap();
+            self.index.remove_edge(*e);
+            self.route_removed_edge(*e);
             removed += 1;
+
+            if self.history.enabled() {
+                self.history.record(Change::RemoveEdge {
+                    idx: *e,
+                    start,
+                    end,
+                    edge,
+                    orders_before,
+                    orders_after: self.edge_orders(start, end),
+                });
+            }
         }
 
+        self.history.commit();
         removed
     }
 
@@ -226,6 +1390,21 @@ where
         self.add_edge_custom(start, end, payload, default_edge_transform)
     }
 
+    /// Adds edge attached to the named ports of the start and end nodes, see [`crate::Port`].
+    pub fn add_edge_with_ports(
+        &mut self,
+        start: NodeIndex<Ix>,
+        end: NodeIndex<Ix>,
+        payload: E,
+        source_port: String,
+        target_port: String,
+    ) -> EdgeIndex<Ix> {
+        self.add_edge_custom(start, end, payload, |e: &mut Edge<N, E, Ty, Ix, Dn, De>| {
+            e.set_source_port(Some(source_port));
+            e.set_target_port(Some(target_port));
+        })
+    }
+
     /// Adds edge between start and end node with custom label setting correct order.
     #[allow(clippy::missing_panics_doc)] // TODO: add panics doc
     pub fn add_edge_with_label(
@@ -248,6 +1427,8 @@ where
         payload: E,
         edge_transform: impl FnOnce(&mut Edge<N, E, Ty, Ix, Dn, De>),
     ) -> EdgeIndex<Ix> {
+        let orders_before = self.edge_orders(start, end);
+
         // Choose the smallest non-negative order not yet used by edges in the SAME direction
         // to avoid multiple edges sharing the same visual offset (stacking).
         let used_orders: std::collections::HashSet<usize> = self
@@ -304,6 +1485,21 @@ where
             }
         }
 
+        self.touch();
+        self.route_added_edge(idx);
+        self.index_edge(idx);
+
+        if self.history.enabled() {
+            self.history.record(Change::AddEdge {
+                idx,
+                start,
+                end,
+                edge: self.g[idx].clone(),
+                orders_before,
+                orders_after: self.edge_orders(start, end),
+            });
+        }
+
         idx
     }
 
@@ -312,8 +1508,12 @@ where
     pub fn remove_edge(&mut self, idx: EdgeIndex<Ix>) -> Option<Edge<N, E, Ty, Ix, Dn, De>> {
         let (start, end) = self.g.edge_endpoints(idx)?;
         let order = self.g.edge_weight(idx)?.order();
+        let orders_before = self.edge_orders(start, end);
 
         let payload = self.g.remove_edge(idx)?;
+        self.touch();
+        self.index.remove_edge(idx);
+        self.route_removed_edge(idx);
 
         let siblings = self
             .g
@@ -330,6 +1530,17 @@ where
             self.g.edge_weight_mut(*s_idx)?.set_order(sibling_order - 1);
         }
 
+        if self.history.enabled() {
+            self.history.record(Change::RemoveEdge {
+                idx,
+                start,
+                end,
+                edge: payload.clone(),
+                orders_before,
+                orders_after: self.edge_orders(start, end),
+            });
+        }
+
         Some(payload)
     }
 
@@ -368,14 +1579,143 @@ where
         self.g.edge_endpoints(i)
     }
 
+    /// Mutable access to the node. The node is reindexed on the next
+    /// [`Graph::sync_spatial_index`].
     pub fn node_mut(&mut self, i: NodeIndex<Ix>) -> Option<&mut Node<N, E, Ty, Ix, Dn>> {
-        self.g.node_weight_mut(i)
+        self.touch();
+        self.node_mut_moved(i)
     }
 
+    /// Like [`Graph::node_mut`] keeping [`Graph::revision`]. For layouts which only move nodes.
+    pub(crate) fn node_mut_moved(
+        &mut self,
+        i: NodeIndex<Ix>,
+    ) -> Option<&mut Node<N, E, Ty, Ix, Dn>> {
+        let n = self.g.node_weight_mut(i)?;
+        self.dirty.nodes.insert(i);
+        Some(n)
+    }
+
+    /// Mutable access to the edge. The edge is reindexed on the next
+    /// [`Graph::sync_spatial_index`].
     pub fn edge_mut(&mut self, i: EdgeIndex<Ix>) -> Option<&mut Edge<N, E, Ty, Ix, Dn, De>> {
+        self.touch();
+        let e = self.g.edge_weight_mut(i)?;
+        self.dirty.edges.insert(i);
+        Some(e)
+    }
+
+    /// Like [`Graph::node_mut`] without scheduling the node for reindexing. For drawing, which
+    /// refreshes display state from props every frame.
+    pub(crate) fn node_mut_untracked(
+        &mut self,
+        i: NodeIndex<Ix>,
+    ) -> Option<&mut Node<N, E, Ty, Ix, Dn>> {
+        self.g.node_weight_mut(i)
+    }
+
+    /// Like [`Graph::edge_mut`] without scheduling the edge for reindexing.
+    pub(crate) fn edge_mut_untracked(
+        &mut self,
+        i: EdgeIndex<Ix>,
+    ) -> Option<&mut Edge<N, E, Ty, Ix, Dn, De>> {
         self.g.edge_weight_mut(i)
     }
 
+    /// Sets node label. The change is recorded in the history.
+    pub fn set_node_label(&mut self, idx: NodeIndex<Ix>, label: String) {
+        let Some(n) = self.g.node_weight_mut(idx) else {
+            return;
+        };
+        let from = n.label();
+        if from == label {
+            return;
+        }
+        n.set_label(label.clone());
+        self.history.record(Change::NodeLabel {
+            idx,
+            from,
+            to: label,
+        });
+    }
+
+    /// Sets edge label. The change is recorded in the history.
+    pub fn set_edge_label(&mut self, idx: EdgeIndex<Ix>, label: String) {
+        let Some(e) = self.g.edge_weight_mut(idx) else {
+            return;
+        };
+        let from = e.label();
+        if from == label {
+            return;
+        }
+        e.set_label(label.clone());
+        self.history.record(Change::EdgeLabel {
+            idx,
+            from,
+            to: label,
+        });
+    }
+
+    /// Selects or deselects node. The change is recorded in the history if
+    /// [`Graph::set_history_records_selection`] is enabled.
+    pub fn set_node_selected(&mut self, idx: NodeIndex<Ix>, selected: bool) {
+        let Some(n) = self.g.node_weight_mut(idx) else {
+            return;
+        };
+        let from = n.selected();
+        if from == selected {
+            return;
+        }
+        n.set_selected(selected);
+        self.history.record(Change::NodeSelected {
+            idx,
+            from,
+            to: selected,
+        });
+    }
+
+    /// Pins or unpins node, pinned nodes are not moved by layouts. The change is recorded in
+    /// the history.
+    pub fn set_node_pinned(&mut self, idx: NodeIndex<Ix>, pinned: bool) {
+        let Some(n) = self.g.node_weight_mut(idx) else {
+            return;
+        };
+        let from = n.pinned();
+        if from == pinned {
+            return;
+        }
+        n.set_pinned(pinned);
+        self.touch();
+        self.history.record(Change::NodePinned {
+            idx,
+            from,
+            to: pinned,
+        });
+    }
+
+    /// Returns false for nodes which do not exist.
+    pub fn is_node_pinned(&self, idx: NodeIndex<Ix>) -> bool {
+        self.g.node_weight(idx).is_some_and(Node::pinned)
+    }
+
+    /// Selects or deselects edge. The change is recorded in the history if
+    /// [`Graph::set_history_records_selection`] is enabled.
+    pub fn set_edge_selected(&mut self, idx: EdgeIndex<Ix>, selected: bool) {
+        let Some(e) = self.g.edge_weight_mut(idx) else {
+            return;
+        };
+        let from = e.selected();
+        if from == selected {
+            return;
+        }
+        e.set_selected(selected);
+        self.history.record(Change::EdgeSelected {
+            idx,
+            from,
+            to: selected,
+        });
+    }
+
     pub fn is_directed(&self) -> bool {
         self.g.is_directed()
     }
@@ -441,6 +1781,23 @@ where
     }
 }
 
+/// Half size of the node: the largest of its horizontal and vertical extents, widened to
+/// cover port markers placed outside of the shape.
+fn node_extent<N, E, Ty, Ix, Dn>(n: &Node<N, E, Ty, Ix, Dn>) -> f32
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+{
+    let shape = node_size(n, Vec2::new(1., 0.)).max(node_size(n, Vec2::new(0., 1.)));
+    n.ports()
+        .iter()
+        .map(|p| p.offset.length() + PORT_RADIUS)
+        .fold(shape, f32::max)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -482,4 +1839,414 @@ mod tests {
         let o4 = g.edge(e4).unwrap().order();
         assert_eq!(o4, 2, "Third A->B edge should get order 2");
     }
+
+    fn orders(g: &Graph<(), (), Directed>) -> Vec<(EdgeIndex, usize)> {
+        let mut res: Vec<_> = g.edges_iter().map(|(idx, e)| (idx, e.order())).collect();
+        res.sort_unstable();
+        res
+    }
+
+    #[test]
+    fn history_is_disabled_by_default() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        g.add_node(());
+        assert!(!g.can_undo());
+        assert!(!g.undo());
+    }
+
+    #[test]
+    fn selection_is_not_recorded_by_default() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        let a = g.add_node(());
+        g.set_history_enabled(true);
+
+        g.set_node_selected(a, true);
+        assert!(!g.can_undo());
+
+        g.set_history_records_selection(true);
+        g.set_node_selected(a, false);
+        assert!(g.undo());
+        assert!(g.node(a).unwrap().selected());
+    }
+
+    #[test]
+    fn drag_transaction_is_committed_once() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        let a = g.add_node(());
+        g.set_history_enabled(true);
+
+        // Drag start seen twice without a drag end in between.
+        g.begin_drag_transaction();
+        g.begin_drag_transaction();
+        g.set_node_location(a, Pos2::new(1., 0.));
+        g.set_node_location(a, Pos2::new(2., 0.));
+        g.commit_drag_transaction();
+        g.commit_drag_transaction();
+
+        // Later changes are steps of their own.
+        g.set_node_location(a, Pos2::new(3., 0.));
+        assert!(g.undo());
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(2., 0.));
+        assert!(g.undo());
+        assert_eq!(g.node(a).unwrap().location(), Pos2::ZERO);
+    }
+
+    #[test]
+    fn raw_mutation_drops_history() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        g.set_history_enabled(true);
+        let a = g.add_node(());
+        g.remove_node(a);
+
+        // Takes the slot undo would reinsert `a` into.
+        let raw = g.g_mut().add_node(Node::new(()));
+        assert!(!g.can_undo());
+        assert!(!g.undo());
+        assert_eq!(g.node_count(), 1);
+        assert!(g.node(raw).is_some());
+    }
+
+    #[test]
+    fn unchanged_label_is_not_recorded() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let e = g.add_edge(a, b, ());
+        g.set_history_enabled(true);
+
+        g.set_node_label(a, g.node(a).unwrap().label());
+        g.set_edge_label(e, g.edge(e).unwrap().label());
+        assert!(!g.can_undo());
+    }
+
+    #[test]
+    fn undo_redo_edges_restores_orders() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        g.set_history_enabled(true);
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let e1 = g.add_edge(a, b, ());
+        g.add_edge(b, a, ());
+        g.add_edge(a, b, ());
+        let full = orders(&g);
+
+        g.remove_edge(e1);
+        let after_remove = orders(&g);
+
+        assert!(g.undo());
+        assert_eq!(orders(&g), full);
+
+        // Undo the third and the second edge: the first edge goes back to order 0.
+        assert!(g.undo());
+        assert!(g.undo());
+        assert_eq!(orders(&g), vec![(e1, 0)]);
+
+        assert!(g.redo());
+        assert!(g.redo());
+        assert_eq!(orders(&g), full);
+        assert!(g.redo());
+        assert_eq!(orders(&g), after_remove);
+        assert!(!g.redo());
+    }
+
+    #[test]
+    fn undo_remove_node_restores_incident_edges() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        g.set_history_enabled(true);
+        let a = g.add_node_with_label_and_location((), "a".to_string(), Pos2::new(1., 2.));
+        let b = g.add_node(());
+        let c = g.add_node(());
+        g.add_edge(a, b, ());
+        g.add_edge(b, a, ());
+        g.add_edge(c, a, ());
+        let full = orders(&g);
+
+        g.remove_node(a);
+        assert_eq!(g.node_count(), 2);
+        assert_eq!(g.edge_count(), 0);
+
+        // Removing a node with its edges is a single step.
+        assert!(g.undo());
+        assert_eq!(g.node_count(), 3);
+        assert_eq!(orders(&g), full);
+        assert_eq!(g.node(a).unwrap().label(), "a");
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(1., 2.));
+        assert_eq!(
+            g.node_by_screen_pos(&MetadataFrame::default(), Pos2::new(1., 2.)),
+            Some(a)
+        );
+
+        assert!(g.redo());
+        assert!(g.node(a).is_none());
+        assert_eq!(g.edge_count(), 0);
+    }
+
+    #[test]
+    fn transaction_groups_moves_labels_and_selection() {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        let a = g.add_node(());
+        g.set_history_enabled(true);
+        g.set_history_records_selection(true);
+
+        g.begin_transaction();
+        for i in 1..=10 {
+            g.set_node_location(a, Pos2::new(i as f32, 0.));
+        }
+        g.set_node_label(a, "moved".to_string());
+        g.set_node_selected(a, true);
+        g.set_node_pinned(a, true);
+        g.commit_transaction();
+
+        assert!(g.undo());
+        assert!(!g.can_undo());
+        let n = g.node(a).unwrap();
+        assert_eq!(n.location(), Pos2::ZERO);
+        assert_eq!(n.label(), "node 0");
+        assert!(!n.selected());
+        assert!(!g.is_node_pinned(a));
+        assert!(g.selected_nodes().is_empty());
+
+        assert!(g.redo());
+        let n = g.node(a).unwrap();
+        assert_eq!(n.location(), Pos2::new(10., 0.));
+        assert_eq!(n.label(), "moved");
+        assert_eq!(g.selected_nodes(), &[a]);
+        assert!(g.is_node_pinned(a));
+
+        // A new change drops the redo stack.
+        assert!(g.undo());
+        g.set_edge_label(EdgeIndex::new(0), "missing".to_string());
+        assert!(g.can_redo());
+        g.set_node_label(a, "other".to_string());
+        assert!(!g.can_redo());
+    }
+
+    fn grouped_graph() -> (Graph<(), (), Directed>, [NodeIndex; 4], GroupId) {
+        let mut g: Graph<(), (), Directed> = Graph::new(StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(0., 0.));
+        let b = g.add_node_with_location((), Pos2::new(10., 0.));
+        let c = g.add_node_with_location((), Pos2::new(100., 0.));
+        let d = g.add_node_with_location((), Pos2::new(200., 0.));
+        g.add_edge(a, b, ());
+        g.add_edge(b, c, ());
+        g.add_edge(c, a, ());
+        g.add_edge(c, d, ());
+        let group = g.add_group("ab".to_string(), vec![a, b]).unwrap();
+        (g, [a, b, c, d], group)
+    }
+
+    #[test]
+    fn collapse_group_reroutes_edges_to_meta_node() {
+        let (mut g, [a, b, c, d], group) = grouped_graph();
+        assert!(g.add_group("again".to_string(), vec![b, c]).is_none());
+
+        let meta = g.collapse_group(group, ()).unwrap();
+        assert_eq!(g.node(meta).unwrap().location(), Pos2::new(5., 0.));
+        assert_eq!(g.node(meta).unwrap().label(), "ab");
+        assert!(!g.is_node_visible(a) && !g.is_node_visible(b));
+        assert_eq!(g.visible_node_indices().count(), 3);
+
+        let ab = g.edges_connecting(a, b).next().unwrap().0;
+        let bc = g.edges_connecting(b, c).next().unwrap().0;
+        let ca = g.edges_connecting(c, a).next().unwrap().0;
+        assert_eq!(g.visible_edge_endpoints(ab), None);
+        assert_eq!(g.visible_edge_endpoints(bc), Some((meta, c)));
+        assert_eq!(g.visible_edge_endpoints(ca), Some((c, meta)));
+        // Opposite rerouted edges are bumped like regular ones.
+        assert_eq!(g.visible_edge_order(bc), Some(1));
+        assert_eq!(g.visible_edge_order(ca), Some(1));
+
+        let mut nbrs = g.visible_neighbors(c, None);
+        nbrs.sort_unstable();
+        // One neighbor per edge, as with petgraph neighbors.
+        assert_eq!(nbrs, vec![d, meta, meta]);
+        assert_eq!(
+            g.visible_neighbors(meta, Some(Direction::Outgoing)),
+            vec![c]
+        );
+
+        let meta_frame = MetadataFrame::default();
+        assert_eq!(
+            g.node_by_screen_pos(&meta_frame, Pos2::new(5., 0.)),
+            Some(meta)
+        );
+        let around_members = Rect::from_min_max(Pos2::new(-1., -1.), Pos2::new(11., 1.));
+        assert_eq!(g.nodes_in_rect(around_members), vec![meta]);
+    }
+
+    #[test]
+    fn edges_of_collapsed_group_are_rerouted_incrementally() {
+        let (mut g, [a, b, c, d], group) = grouped_graph();
+        let meta = g.collapse_group(group, ()).unwrap();
+        let view = |g: &Graph<(), (), Directed>| {
+            g.edges_iter()
+                .map(|(idx, _)| {
+                    (
+                        idx,
+                        g.visible_edge_endpoints(idx),
+                        g.visible_edge_order(idx),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let ac = g.add_edge(a, c, ());
+        let ab = g.add_edge(b, a, ());
+        g.add_edge(d, b, ());
+        assert_eq!(g.visible_edge_endpoints(ac), Some((meta, c)));
+        assert_eq!(g.visible_edge_endpoints(ab), None);
+        let bc = g.edges_connecting(b, c).next().unwrap().0;
+        g.remove_edge(bc);
+        g.remove_node(d);
+
+        let incremental = view(&g);
+        g.refresh_groups();
+        assert_eq!(view(&g), incremental);
+        assert_eq!(g.node_group(a), Some(group));
+    }
+
+    #[test]
+    fn expand_group_restores_members_around_meta_node() {
+        let (mut g, [a, b, c, _], group) = grouped_graph();
+        let meta = g.collapse_group(group, ()).unwrap();
+        g.set_node_location(meta, Pos2::new(55., 50.));
+
+        assert!(g.expand_group(group));
+        assert!(g.node(meta).is_none());
+        assert!(!g.group(group).unwrap().is_collapsed());
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(50., 50.));
+        assert_eq!(g.node(b).unwrap().location(), Pos2::new(60., 50.));
+        let bc = g.edges_connecting(b, c).next().unwrap().0;
+        assert_eq!(g.visible_edge_endpoints(bc), Some((b, c)));
+        assert_eq!(
+            g.group_rect(group),
+            Some(Rect::from_min_max(Pos2::new(45., 45.), Pos2::new(65., 55.)))
+        );
+    }
+
+    #[test]
+    fn undo_collapse_and_member_removal() {
+        let (mut g, [a, b, _, _], group) = grouped_graph();
+        g.set_history_enabled(true);
+
+        let meta = g.collapse_group(group, ()).unwrap();
+        g.remove_node(b);
+        assert_eq!(g.group(group).unwrap().members(), &[a]);
+
+        assert!(g.undo());
+        assert_eq!(g.group(group).unwrap().members(), &[a, b]);
+        assert!(!g.is_node_visible(b));
+
+        assert!(g.undo());
+        assert!(g.node(meta).is_none());
+        assert!(g.is_node_visible(a) && g.is_node_visible(b));
+
+        assert!(g.redo());
+        assert_eq!(g.group(group).unwrap().meta_node(), Some(meta));
+        assert!(!g.is_node_visible(a));
+    }
+
+    #[test]
+    fn sync_reindexes_only_changed_nodes() {
+        let (mut g, [a, b, _, _], _) = grouped_graph();
+        let around = |p: Pos2| Rect::from_center_size(p, Vec2::splat(2.));
+        let (far, other) = (Pos2::new(500., 500.), Pos2::new(-500., -500.));
+
+        g.node_mut(a).unwrap().set_location(far);
+        // Bypasses the tracked accessors, so the sync does not pick the move up.
+        g.g[b].set_location(other);
+        g.sync_spatial_index();
+        assert_eq!(g.nodes_in_rect(around(far)), vec![a]);
+        assert!(g.nodes_in_rect(around(other)).is_empty());
+        assert_eq!(g.nodes_in_rect(around(Pos2::new(10., 0.))), vec![b]);
+
+        g.g_mut();
+        g.sync_spatial_index();
+        assert_eq!(g.nodes_in_rect(around(other)), vec![b]);
+    }
+
+    #[test]
+    fn ports_outside_of_the_shape_are_hit() {
+        let mut g: Graph = Graph::new(StableGraph::default());
+        let a = g.add_node_custom((), |n| {
+            n.add_port(crate::Port::output("out".to_string(), Vec2::new(200., 0.)));
+            let props = n.props().clone();
+            DisplayNode::<(), (), Directed, DefaultIx>::update(n.display_mut(), &props);
+        });
+
+        let meta = MetadataFrame::default();
+        assert_eq!(
+            g.port_by_screen_pos(&meta, Pos2::new(201., 0.)),
+            Some((a, "out".to_string()))
+        );
+    }
+
+    #[test]
+    fn moving_node_drops_incident_bends() {
+        let (mut g, [a, b, c, d], _) = grouped_graph();
+        let bends = vec![Pos2::new(50., 50.)];
+        let edges: Vec<_> = g.edges_iter().map(|(idx, _)| idx).collect();
+        for idx in edges {
+            g.edge_mut(idx).unwrap().set_bends(bends.clone());
+        }
+
+        g.set_node_location(a, Pos2::new(0., 100.));
+        let bends_of = |g: &Graph<(), (), Directed>, s, t| {
+            let idx = g.edges_connecting(s, t).next().unwrap().0;
+            g.edge(idx).unwrap().bends().to_vec()
+        };
+        assert!(bends_of(&g, a, b).is_empty());
+        assert!(bends_of(&g, c, a).is_empty());
+        assert_eq!(bends_of(&g, b, c), bends);
+        assert_eq!(bends_of(&g, c, d), bends);
+    }
+
+    #[test]
+    fn hidden_node_is_skipped_by_hit_testing_and_neighbors() {
+        let (mut g, [a, b, c, d], _) = grouped_graph();
+        let meta = MetadataFrame::default();
+        assert_eq!(g.node_by_screen_pos(&meta, Pos2::ZERO), Some(a));
+
+        g.set_node_hidden(a, true);
+        assert!(!g.is_node_visible(a));
+        assert_eq!(g.visible_node_indices().count(), 3);
+        let ca = g.edges_connecting(c, a).next().unwrap().0;
+        assert_eq!(g.visible_edge_endpoints(ca), None);
+
+        let mut nbrs = g.visible_neighbors(c, None);
+        nbrs.sort_unstable();
+        assert_eq!(nbrs, vec![b, d]);
+        assert!(g.visible_neighbors(a, None).is_empty());
+
+        assert_ne!(g.node_by_screen_pos(&meta, Pos2::ZERO), Some(a));
+        let around = Rect::from_min_max(Pos2::new(-1., -1.), Pos2::new(11., 1.));
+        assert_eq!(g.nodes_in_rect(around), vec![b]);
+
+        g.set_node_hidden(a, false);
+        assert_eq!(g.node_by_screen_pos(&meta, Pos2::ZERO), Some(a));
+        assert_eq!(g.visible_edge_endpoints(ca), Some((c, a)));
+    }
+
+    #[test]
+    fn filters_hide_rejected_elements() {
+        let (mut g, [a, b, c, d], _) = grouped_graph();
+        let cd = g.edges_connecting(c, d).next().unwrap().0;
+        g.set_edge_hidden(cd, true);
+        assert_eq!(g.visible_neighbors(c, Some(Direction::Outgoing)), vec![a]);
+
+        g.set_node_filter(|props| props.location().x < 50.);
+        assert_eq!(g.visible_node_indices().collect::<Vec<_>>(), vec![a, b]);
+        let ab = g.edges_connecting(a, b).next().unwrap().0;
+        assert_eq!(g.visible_edge_endpoints(ab), Some((a, b)));
+
+        g.set_edge_filter(|props| props.order > 0);
+        assert_eq!(g.visible_edge_endpoints(ab), None);
+        assert!(g.visible_neighbors(a, None).is_empty());
+
+        g.clear_filters();
+        assert_eq!(g.visible_node_indices().count(), 4);
+        let mut nbrs = g.visible_neighbors(c, None);
+        nbrs.sort_unstable();
+        assert_eq!(nbrs, vec![a, b]);
+    }
 }
@@ -2,9 +2,9 @@ use std::marker::PhantomData;
 
 use crate::{
     draw::{drawer::Drawer, DefaultEdgeShape, DefaultNodeShape, DrawContext},
-    layouts::{self, Layout, LayoutState},
+    layouts::{self, Layout, LayoutState, Transition},
     metadata::{reset_metadata, MetadataFrame, MetadataInstance},
-    settings::{SettingsInteraction, SettingsNavigation, SettingsStyle},
+    settings::{SettingsAnimation, SettingsInteraction, SettingsNavigation, SettingsStyle},
     DisplayEdge, DisplayNode, Graph,
 };
 
@@ -163,6 +163,8 @@ use crate::events::{
     PayloadNodeHoverEnter, PayloadNodeHoverLeave, PayloadNodeMove, PayloadNodeSelect, PayloadPan,
     PayloadZoom,
 };
+#[cfg(feature = "background")]
+use crate::SyncLayout;
 
 // Effective interaction flags after applying master->child rules.
 #[derive(Clone, Copy, Debug, Default)]
@@ -217,12 +219,16 @@ pub struct GraphView<
     settings_interaction: SettingsInteraction,
     settings_navigation: SettingsNavigation,
     settings_style: SettingsStyle,
+    settings_animation: SettingsAnimation,
 
     custom_id: Option<String>,
 
     #[cfg(feature = "events")]
     events_sink: Option<&'a dyn EventSink>,
 
+    #[cfg(feature = "background")]
+    background: Option<&'a mut dyn SyncLayout<N, E, Ty, Ix, Nd, Ed>>,
+
     _marker: PhantomData<(Nd, Ed, L, S)>,
 }
 
@@ -301,7 +307,10 @@ where
         self.handle_node_drag(&resp, &mut view, eff);
 
         self.handle_navigation(ui, &resp, &mut view.frame, eff);
+        // Selection changes caused by a single click are undone as one step when recorded.
+        self.g.begin_transaction();
         self.handle_click(&resp, &mut view.frame, eff);
+        self.g.commit_transaction();
 
         // Measure draw time (exclude layout step): start after layout, stop after draw
         let t_draw0 = Instant::now();
@@ -356,6 +365,7 @@ where
             g,
 
             settings_style: SettingsStyle::default(),
+            settings_animation: SettingsAnimation::default(),
             settings_interaction: SettingsInteraction::default(),
             settings_navigation: SettingsNavigation::default(),
 
@@ -363,11 +373,28 @@ where
 
             #[cfg(feature = "events")]
             events_sink: Option::default(),
+            #[cfg(feature = "background")]
+            background: None,
 
             _marker: PhantomData,
         }
     }
 
+    #[cfg(feature = "background")]
+    /// Lays the graph out with a runner stepping on another thread, e.g. a
+    /// [`crate::BackgroundLayout`], instead of the layout `L`. The layout state of the view
+    /// is left untouched.
+    ///
+    /// Positions streamed by the runner are applied as they are, without the transitions of
+    /// [`SettingsAnimation::with_transition_duration`].
+    pub fn with_background_layout(
+        mut self,
+        runner: &'a mut dyn SyncLayout<N, E, Ty, Ix, Dn, De>,
+    ) -> Self {
+        self.background = Some(runner);
+        self
+    }
+
     #[cfg(feature = "events")]
     /// Supply a generic sink that will receive interaction events.
     /// Works with crossbeam::Sender<Event>, closures `Fn(Event)`, or custom implementations.
@@ -538,6 +565,12 @@ where
         self
     }
 
+    /// Animates layout changes according to the provided settings.
+    pub fn with_animations(mut self, settings_animation: &SettingsAnimation) -> Self {
+        self.settings_animation = settings_animation.clone();
+        self
+    }
+
     /// Sets a custom unique ID for this widget instance. Useful when you have multiple graph views
     /// in the same UI and want to keep their state (layout, metadata) separate.
     pub fn with_id(mut self, custom_id: Option<String>) -> Self {
@@ -667,12 +700,37 @@ where
     }
 
     fn sync_layout(&mut self, ui: &mut Ui) {
+        #[cfg(feature = "background")]
+        if let Some(runner) = self.background.as_mut() {
+            // Runners move nodes a little every frame, there is nothing to tween.
+            runner.sync(self.g, ui.ctx().content_rect());
+            return;
+        }
+
         let id = self.custom_id.clone();
 
         let state = S::load(ui, id.clone());
 
         let mut layout = L::from_state(state);
-        layout.next(self.g, ui);
+        let duration = self.settings_animation.transition_duration;
+        if duration > 0. {
+            // Tweens live next to the layout state of the view.
+            let key = Id::new(("egui_graphs_transition", id.clone()));
+            let mut transition: Transition = ui.data(|d| d.get_temp(key)).unwrap_or_default();
+            let before = transition.restore(self.g);
+            layout.next(self.g, ui);
+            let now = ui.input(|i| i.time);
+            transition.step(
+                self.g,
+                &before,
+                now,
+                duration,
+                self.settings_animation.transition_easing,
+            );
+            ui.data_mut(|d| d.insert_temp(key, transition));
+        } else {
+            layout.next(self.g, ui);
+        }
         let new_state = layout.state();
 
         new_state.save(ui, id);
@@ -683,6 +741,9 @@ where
         let mut selected_edges = Vec::new();
         let mut dragged = None;
 
+        // Layout step may have moved nodes, refresh hit-testing index before interactions.
+        self.g.sync_spatial_index();
+
         meta.reset_bounds();
         self.g.nodes_iter().for_each(|(idx, n)| {
             if n.dragged() {
@@ -692,14 +753,16 @@ where
                 selected_nodes.push(idx);
             }
 
-            meta.process_bounds(n);
+            if self.g.is_node_visible(idx) {
+                meta.process_bounds(n);
+            }
         });
 
         self.g.edges_iter().for_each(|(idx, e)| {
             if e.selected() {
                 selected_edges.push(idx);
             }
-            if let Some((start_idx, end_idx)) = self.g.edge_endpoints(e.id()) {
+            if let Some((start_idx, end_idx)) = self.g.visible_edge_endpoints(idx) {
                 if let (Some(start), Some(end)) = (self.g.node(start_idx), self.g.node(end_idx)) {
                     if let Some((min, max)) = e.display().extra_bounds(start, end) {
                         meta.expand_bounds(min, max);
@@ -711,6 +774,10 @@ where
         self.g.set_selected_nodes(selected_nodes);
         self.g.set_selected_edges(selected_edges);
         self.g.set_dragged_node(dragged);
+        if dragged.is_none() {
+            // The drag ended without a drag end, e.g. the node was removed or undragged.
+            self.g.commit_drag_transaction();
+        }
         self.g.set_bounds(meta.graph_bounds());
     }
 
@@ -785,7 +852,12 @@ where
                 self.handle_node_double_click(idx, eff);
                 return;
             }
-            self.handle_node_click(idx, eff);
+            let port = self
+                .g
+                .port_by_screen_pos(meta, local_pos)
+                .filter(|(port_node, _)| *port_node == idx)
+                .map(|(_, port)| port);
+            self.handle_node_click(idx, port, eff);
             return;
         }
 
@@ -804,13 +876,18 @@ where
         }
     }
 
-    fn handle_node_click(&mut self, idx: NodeIndex<Ix>, eff: EffectiveInteraction) {
+    fn handle_node_click(
+        &mut self,
+        idx: NodeIndex<Ix>,
+        port: Option<String>,
+        eff: EffectiveInteraction,
+    ) {
         if !eff.node_clicking && !eff.node_selection {
             return;
         }
 
         if eff.node_clicking {
-            self.set_node_clicked(idx);
+            self.set_node_clicked(idx, port);
         }
 
         if !eff.node_selection {
@@ -915,6 +992,9 @@ where
             let n_idx_dragged = self.g.dragged_node().unwrap();
             let delta_in_graph_coords = resp.drag_delta() / meta.zoom;
             self.move_node(n_idx_dragged, delta_in_graph_coords);
+            if self.settings_interaction.pin_dragged_nodes {
+                self.g.set_node_pinned(n_idx_dragged, true);
+            }
         }
 
         // compensate movement of the node which is not caused by dragging
@@ -1043,25 +1123,26 @@ where
     }
 
     fn select_node(&mut self, idx: NodeIndex<Ix>) {
-        let n = self.g.node_mut(idx).unwrap();
-        n.set_selected(true);
+        self.g.set_node_selected(idx, true);
 
         #[cfg(feature = "events")]
         self.publish_event(Event::NodeSelect(PayloadNodeSelect { id: idx.index() }));
     }
 
     fn deselect_node(&mut self, idx: NodeIndex<Ix>) {
-        let n = self.g.node_mut(idx).unwrap();
-        n.set_selected(false);
+        self.g.set_node_selected(idx, false);
 
         #[cfg(feature = "events")]
         self.publish_event(Event::NodeDeselect(PayloadNodeDeselect { id: idx.index() }));
     }
 
     #[allow(unused_variables, clippy::unused_self)]
-    fn set_node_clicked(&self, idx: NodeIndex<Ix>) {
+    fn set_node_clicked(&self, idx: NodeIndex<Ix>, port: Option<String>) {
         #[cfg(feature = "events")]
-        self.publish_event(Event::NodeClick(PayloadNodeClick { id: idx.index() }));
+        self.publish_event(Event::NodeClick(PayloadNodeClick {
+            id: idx.index(),
+            port,
+        }));
     }
 
     #[allow(unused_variables, clippy::unused_self)]
@@ -1079,16 +1160,14 @@ where
     }
 
     fn select_edge(&mut self, idx: EdgeIndex<Ix>) {
-        let e = self.g.edge_mut(idx).unwrap();
-        e.set_selected(true);
+        self.g.set_edge_selected(idx, true);
 
         #[cfg(feature = "events")]
         self.publish_event(Event::EdgeSelect(PayloadEdgeSelect { id: idx.index() }));
     }
 
     fn deselect_edge(&mut self, idx: EdgeIndex<Ix>) {
-        let e = self.g.edge_mut(idx).unwrap();
-        e.set_selected(false);
+        self.g.set_edge_selected(idx, false);
 
         #[cfg(feature = "events")]
         self.publish_event(Event::EdgeDeselect(PayloadEdgeDeselect { id: idx.index() }));
@@ -1115,9 +1194,8 @@ where
     }
 
     fn move_node(&mut self, idx: NodeIndex<Ix>, delta: Vec2) {
-        let n = self.g.node_mut(idx).unwrap();
-        let new_loc = n.location() + delta;
-        n.set_location(new_loc);
+        let new_loc = self.g.node(idx).unwrap().location() + delta;
+        self.g.set_node_location(idx, new_loc);
 
         #[cfg(feature = "events")]
         self.publish_event(Event::NodeMove(PayloadNodeMove {
@@ -1128,6 +1206,10 @@ where
     }
 
     fn set_drag_start(&mut self, idx: NodeIndex<Ix>) {
+        // The whole drag is undone as one step, the transaction is committed in set_drag_end
+        // or by sync_state once no node is dragged anymore.
+        self.g.begin_drag_transaction();
+
         let n = self.g.node_mut(idx).unwrap();
         n.set_dragged(true);
 
@@ -1141,6 +1223,8 @@ where
         let n = self.g.node_mut(idx).unwrap();
         n.set_dragged(false);
 
+        self.g.commit_drag_transaction();
+
         #[cfg(feature = "events")]
         self.publish_event(Event::NodeDragEnd(PayloadNodeDragEnd { id: idx.index() }));
     }
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::Vec2;
+use petgraph::{
+    stable_graph::{EdgeIndex, IndexType, NodeIndex},
+    visit::{EdgeRef, IntoEdgeReferences},
+    EdgeType,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{graph::StableGraphType, DisplayEdge, DisplayNode};
+
+/// Identifier of a [`Group`] inside a [`crate::Graph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GroupId(usize);
+
+impl GroupId {
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Named set of nodes. Expanded groups are drawn as a frame behind their members, collapsed
+/// groups are represented by a single meta node which takes over the edges of the members.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Group<Ix: IndexType> {
+    label: String,
+    members: Vec<NodeIndex<Ix>>,
+    collapsed: Option<Collapsed<Ix>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Collapsed<Ix: IndexType> {
+    meta: NodeIndex<Ix>,
+    /// Member offsets from the meta node location, in the order of `members`.
+    offsets: Vec<Vec2>,
+}
+
+impl<Ix: IndexType> Group<Ix> {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn members(&self) -> &[NodeIndex<Ix>] {
+        &self.members
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed.is_some()
+    }
+
+    /// Node representing the group while it is collapsed.
+    pub fn meta_node(&self) -> Option<NodeIndex<Ix>> {
+        self.collapsed.as_ref().map(|c| c.meta)
+    }
+
+    /// Offsets of the members from the meta node location captured on collapse.
+    pub(crate) fn member_offsets(&self) -> Option<&[Vec2]> {
+        self.collapsed.as_ref().map(|c| c.offsets.as_slice())
+    }
+
+    pub(crate) fn collapse(&mut self, meta: NodeIndex<Ix>, offsets: Vec<Vec2>) {
+        self.collapsed = Some(Collapsed { meta, offsets });
+    }
+
+    pub(crate) fn expand(&mut self) {
+        self.collapsed = None;
+    }
+}
+
+/// Groups of a graph. Membership is exclusive: a node belongs to at most one group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "GroupList<Ix>", into = "GroupList<Ix>")]
+pub(crate) struct GroupSet<Ix: IndexType> {
+    next_id: usize,
+    groups: Vec<(GroupId, Group<Ix>)>,
+    /// Group of every member node.
+    member_of: HashMap<NodeIndex<Ix>, GroupId>,
+}
+
+/// Serialized form of [`GroupSet`], the member map is derived on load.
+#[derive(Serialize, Deserialize)]
+struct GroupList<Ix: IndexType> {
+    next_id: usize,
+    groups: Vec<(GroupId, Group<Ix>)>,
+}
+
+impl<Ix: IndexType> From<GroupList<Ix>> for GroupSet<Ix> {
+    fn from(list: GroupList<Ix>) -> Self {
+        let member_of = list
+            .groups
+            .iter()
+            .flat_map(|(id, g)| g.members.iter().map(|m| (*m, *id)))
+            .collect();
+        Self {
+            next_id: list.next_id,
+            groups: list.groups,
+            member_of,
+        }
+    }
+}
+
+impl<Ix: IndexType> From<GroupSet<Ix>> for GroupList<Ix> {
+    fn from(set: GroupSet<Ix>) -> Self {
+        Self {
+            next_id: set.next_id,
+            groups: set.groups,
+        }
+    }
+}
+
+impl<Ix: IndexType> Default for GroupSet<Ix> {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            groups: Vec::new(),
+            member_of: HashMap::new(),
+        }
+    }
+}
+
+impl<Ix: IndexType> GroupSet<Ix> {
+    pub fn add(&mut self, label: String, members: Vec<NodeIndex<Ix>>) -> GroupId {
+        let id = GroupId(self.next_id);
+        self.next_id += 1;
+        self.member_of.extend(members.iter().map(|m| (*m, id)));
+        self.groups.push((
+            id,
+            Group {
+                label,
+                members,
+                collapsed: None,
+            },
+        ));
+        id
+    }
+
+    pub fn remove(&mut self, id: GroupId) -> Option<Group<Ix>> {
+        let pos = self.groups.iter().position(|(gid, _)| *gid == id)?;
+        let group = self.groups.remove(pos).1;
+        for m in &group.members {
+            self.member_of.remove(m);
+        }
+        Some(group)
+    }
+
+    pub fn get(&self, id: GroupId) -> Option<&Group<Ix>> {
+        self.groups
+            .iter()
+            .find(|(gid, _)| *gid == id)
+            .map(|(_, g)| g)
+    }
+
+    pub fn get_mut(&mut self, id: GroupId) -> Option<&mut Group<Ix>> {
+        self.groups
+            .iter_mut()
+            .find(|(gid, _)| *gid == id)
+            .map(|(_, g)| g)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GroupId, &Group<Ix>)> {
+        self.groups.iter().map(|(id, g)| (*id, g))
+    }
+
+    /// Group the node is a member of.
+    pub fn group_of(&self, idx: NodeIndex<Ix>) -> Option<GroupId> {
+        self.member_of.get(&idx).copied()
+    }
+
+    /// Group the node represents while collapsed.
+    pub fn group_of_meta(&self, idx: NodeIndex<Ix>) -> Option<GroupId> {
+        self.iter()
+            .find(|(_, g)| g.meta_node() == Some(idx))
+            .map(|(id, _)| id)
+    }
+
+    /// Drops the node from its group. Returns false if the node is not grouped.
+    pub fn remove_member(&mut self, idx: NodeIndex<Ix>) -> bool {
+        let Some(id) = self.member_of.remove(&idx) else {
+            return false;
+        };
+        let Some(g) = self.get_mut(id) else {
+            return false;
+        };
+        if let Some(pos) = g.members.iter().position(|m| *m == idx) {
+            g.members.remove(pos);
+            if let Some(c) = &mut g.collapsed {
+                c.offsets.remove(pos);
+            }
+        }
+        true
+    }
+}
+
+type NodePair<Ix> = (NodeIndex<Ix>, NodeIndex<Ix>);
+
+/// Derived view of the graph with collapsed groups applied.
+///
+/// Members of collapsed groups are hidden. Edges between a member and a node outside of the
+/// group are rerouted: they stay in the graph under their indices but are displayed, hit-tested
+/// and laid out as edges of the meta node. Edges between members of the same collapsed group
+/// are hidden.
+#[derive(Debug, Clone)]
+pub(crate) struct CollapseState<Ix: IndexType> {
+    built: bool,
+    /// Meta node standing in for every hidden member.
+    representative: HashMap<NodeIndex<Ix>, NodeIndex<Ix>>,
+    hidden_nodes: HashSet<NodeIndex<Ix>>,
+    hidden_edges: HashSet<EdgeIndex<Ix>>,
+    /// Displayed endpoints and order of rerouted edges.
+    rerouted: HashMap<EdgeIndex<Ix>, (NodeIndex<Ix>, NodeIndex<Ix>, usize)>,
+    /// Rerouted edges by their displayed endpoints.
+    rerouted_at: HashMap<NodeIndex<Ix>, Vec<EdgeIndex<Ix>>>,
+}
+
+impl<Ix: IndexType> Default for CollapseState<Ix> {
+    fn default() -> Self {
+        Self {
+            built: false,
+            representative: HashMap::new(),
+            hidden_nodes: HashSet::new(),
+            hidden_edges: HashSet::new(),
+            rerouted: HashMap::new(),
+            rerouted_at: HashMap::new(),
+        }
+    }
+}
+
+impl<Ix: IndexType> CollapseState<Ix> {
+    pub fn is_built(&self) -> bool {
+        self.built
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hidden_nodes.is_empty()
+    }
+
+    pub fn node_hidden(&self, idx: NodeIndex<Ix>) -> bool {
+        self.hidden_nodes.contains(&idx)
+    }
+
+    /// Displayed endpoints of the edge given its real endpoints. None if the edge is hidden.
+    pub fn edge_endpoints(
+        &self,
+        idx: EdgeIndex<Ix>,
+        real: (NodeIndex<Ix>, NodeIndex<Ix>),
+    ) -> Option<(NodeIndex<Ix>, NodeIndex<Ix>)> {
+        if self.hidden_edges.contains(&idx) {
+            return None;
+        }
+        if let Some((start, end, _)) = self.rerouted.get(&idx) {
+            return Some((*start, *end));
+        }
+        if self.hidden_nodes.contains(&real.0) || self.hidden_nodes.contains(&real.1) {
+            // Added bypassing the graph methods, stays hidden until the next rebuild.
+            return None;
+        }
+        Some(real)
+    }
+
+    /// Parallel edges order to display a rerouted edge with.
+    pub fn rerouted_order(&self, idx: EdgeIndex<Ix>) -> Option<usize> {
+        self.rerouted.get(&idx).map(|(_, _, order)| *order)
+    }
+
+    /// Rerouted edges displayed as incident to the node.
+    pub fn rerouted_at(&self, idx: NodeIndex<Ix>) -> &[EdgeIndex<Ix>] {
+        self.rerouted_at.get(&idx).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn rebuild<N, E, Ty, Dn, De>(
+        &mut self,
+        groups: &GroupSet<Ix>,
+        g: &StableGraphType<N, E, Ty, Ix, Dn, De>,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        *self = Self {
+            built: true,
+            ..Self::default()
+        };
+
+        for (_, group) in groups.iter() {
+            let Some(meta) = group.meta_node() else {
+                continue;
+            };
+            for m in &group.members {
+                if g.contains_node(*m) {
+                    self.representative.insert(*m, meta);
+                    self.hidden_nodes.insert(*m);
+                }
+            }
+        }
+        if self.representative.is_empty() {
+            return;
+        }
+
+        // Rerouted edges grouped by the unordered pair of displayed endpoints.
+        let mut pairs: HashMap<NodePair<Ix>, Vec<EdgeIndex<Ix>>> = HashMap::new();
+        for e in g.edge_references() {
+            if let Some((ru, rv)) = self.route(e.id(), (e.source(), e.target())) {
+                pairs
+                    .entry((ru.min(rv), ru.max(rv)))
+                    .or_default()
+                    .push(e.id());
+            }
+        }
+
+        let directed = g.is_directed();
+        for ((a, _), edges) in pairs {
+            self.assign_orders(a, &edges, directed);
+        }
+    }
+
+    /// Applies the view to an edge added since the last rebuild. Returns the rerouted edges
+    /// whose order changed.
+    pub fn add_edge(
+        &mut self,
+        idx: EdgeIndex<Ix>,
+        real: NodePair<Ix>,
+        directed: bool,
+    ) -> Vec<EdgeIndex<Ix>> {
+        match self.route(idx, real) {
+            Some((ru, rv)) => self.reorder_pair(ru, rv, directed),
+            None => Vec::new(),
+        }
+    }
+
+    /// Drops an edge removed since the last rebuild from the view. Returns the rerouted edges
+    /// whose order changed.
+    pub fn remove_edge(&mut self, idx: EdgeIndex<Ix>, directed: bool) -> Vec<EdgeIndex<Ix>> {
+        self.hidden_edges.remove(&idx);
+        let Some((ru, rv, _)) = self.rerouted.remove(&idx) else {
+            return Vec::new();
+        };
+        for n in [ru, rv] {
+            if let Some(edges) = self.rerouted_at.get_mut(&n) {
+                edges.retain(|e| *e != idx);
+                if edges.is_empty() {
+                    self.rerouted_at.remove(&n);
+                }
+            }
+        }
+        self.reorder_pair(ru, rv, directed)
+    }
+
+    /// Hides or reroutes the edge with the given real endpoints. Returns the displayed
+    /// endpoints of a rerouted edge.
+    fn route(&mut self, idx: EdgeIndex<Ix>, (u, v): NodePair<Ix>) -> Option<NodePair<Ix>> {
+        let ru = self.representative.get(&u).copied().unwrap_or(u);
+        let rv = self.representative.get(&v).copied().unwrap_or(v);
+        if ru == u && rv == v {
+            return None;
+        }
+        if ru == rv {
+            self.hidden_edges.insert(idx);
+            return None;
+        }
+
+        self.rerouted.insert(idx, (ru, rv, 0));
+        self.rerouted_at.entry(ru).or_default().push(idx);
+        self.rerouted_at.entry(rv).or_default().push(idx);
+        Some((ru, rv))
+    }
+
+    /// Reassigns orders of the rerouted edges displayed between `a` and `b` and returns them.
+    fn reorder_pair(
+        &mut self,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+        directed: bool,
+    ) -> Vec<EdgeIndex<Ix>> {
+        let mut edges: Vec<_> = self
+            .rerouted_at(a)
+            .iter()
+            .copied()
+            .filter(|e| {
+                let (start, end, _) = self.rerouted[e];
+                start == b || end == b
+            })
+            .collect();
+        // Same order a rebuild visits them in.
+        edges.sort_unstable_by_key(|e| e.index());
+        self.assign_orders(a.min(b), &edges, directed);
+        edges
+    }
+
+    /// Same bookkeeping as for regular parallel edges: orders are unique per direction and
+    /// bumped when both directions are present so straight lines do not overlap. `a` is the
+    /// smaller endpoint of the pair.
+    fn assign_orders(&mut self, a: NodeIndex<Ix>, edges: &[EdgeIndex<Ix>], directed: bool) {
+        let (mut forward, mut backward) = (0, 0);
+        for e in edges {
+            let entry = self.rerouted.get_mut(e).unwrap();
+            if !directed || entry.0 == a {
+                entry.2 = forward;
+                forward += 1;
+            } else {
+                entry.2 = backward;
+                backward += 1;
+            }
+        }
+        if forward > 0 && backward > 0 {
+            for e in edges {
+                self.rerouted.get_mut(e).unwrap().2 += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::VecDeque;
+
+use egui::Pos2;
+use petgraph::{
+    stable_graph::{EdgeIndex, IndexType, NodeIndex},
+    EdgeType,
+};
+
+use crate::{groups::GroupSet, DisplayEdge, DisplayNode, Edge, Node};
+
+/// Default number of transactions kept for undo.
+pub(crate) const DEFAULT_HISTORY_LIMIT: usize = 256;
+
+/// Orders of the edges connecting a pair of nodes (both directions).
+pub(crate) type EdgeOrders<Ix> = Vec<(EdgeIndex<Ix>, usize)>;
+
+/// Single reversible mutation of a [`crate::Graph`].
+///
+/// Structural changes keep full copies of the affected elements so they can be reinserted
+/// under the same indices. Edge changes additionally keep the orders of all sibling edges
+/// before and after the change, which is enough to restore the parallel edges bookkeeping
+/// in both directions.
+#[derive(Debug, Clone)]
+pub(crate) enum Change<N, E, Ty, Ix, Dn, De>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    AddNode {
+        idx: NodeIndex<Ix>,
+        node: Node<N, E, Ty, Ix, Dn>,
+    },
+    RemoveNode {
+        idx: NodeIndex<Ix>,
+        node: Node<N, E, Ty, Ix, Dn>,
+    },
+    AddEdge {
+        idx: EdgeIndex<Ix>,
+        start: NodeIndex<Ix>,
+        end: NodeIndex<Ix>,
+        edge: Edge<N, E, Ty, Ix, Dn, De>,
+        orders_before: EdgeOrders<Ix>,
+        orders_after: EdgeOrders<Ix>,
+    },
+    RemoveEdge {
+        idx: EdgeIndex<Ix>,
+        start: NodeIndex<Ix>,
+        end: NodeIndex<Ix>,
+        edge: Edge<N, E, Ty, Ix, Dn, De>,
+        orders_before: EdgeOrders<Ix>,
+        orders_after: EdgeOrders<Ix>,
+    },
+    /// `bends` are the bend points of the incident edges dropped by the move.
+    MoveNode {
+        idx: NodeIndex<Ix>,
+        from: Pos2,
+        to: Pos2,
+        bends: Vec<(EdgeIndex<Ix>, Vec<Pos2>)>,
+    },
+    NodeLabel {
+        idx: NodeIndex<Ix>,
+        from: String,
+        to: String,
+    },
+    EdgeLabel {
+        idx: EdgeIndex<Ix>,
+        from: String,
+        to: String,
+    },
+    NodeSelected {
+        idx: NodeIndex<Ix>,
+        from: bool,
+        to: bool,
+    },
+    NodePinned {
+        idx: NodeIndex<Ix>,
+        from: bool,
+        to: bool,
+    },
+    EdgeSelected {
+        idx: EdgeIndex<Ix>,
+        from: bool,
+        to: bool,
+    },
+    Groups {
+        from: GroupSet<Ix>,
+        to: GroupSet<Ix>,
+    },
+}
+
+pub(crate) type Transaction<N, E, Ty, Ix, Dn, De> = Vec<Change<N, E, Ty, Ix, Dn, De>>;
+
+/// Undo and redo stacks of a [`crate::Graph`].
+///
+/// Changes recorded while a transaction is open are collected and pushed as a single undo
+/// step once the outermost transaction is committed. Changes recorded outside of a
+/// transaction form a step of their own.
+#[derive(Debug, Clone)]
+pub(crate) struct History<N, E, Ty, Ix, Dn, De>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    enabled: bool,
+    limit: usize,
+    /// Whether selection changes are recorded.
+    selection: bool,
+
+    depth: usize,
+    /// Whether a drag transaction is open, see [`History::begin_drag`].
+    drag: bool,
+    pending: Transaction<N, E, Ty, Ix, Dn, De>,
+
+    undo: VecDeque<Transaction<N, E, Ty, Ix, Dn, De>>,
+    redo: Vec<Transaction<N, E, Ty, Ix, Dn, De>>,
+}
+
+impl<N, E, Ty, Ix, Dn, De> Default for History<N, E, Ty, Ix, Dn, De>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            limit: DEFAULT_HISTORY_LIMIT,
+            selection: false,
+            depth: 0,
+            drag: false,
+            pending: Vec::new(),
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+}
+
+impl<N, E, Ty, Ix, Dn, De> History<N, E, Ty, Ix, Dn, De>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.clear();
+        }
+        self.enabled = enabled;
+    }
+
+    pub fn records_selection(&self) -> bool {
+        self.selection
+    }
+
+    pub fn set_records_selection(&mut self, selection: bool) {
+        self.selection = selection;
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    pub fn clear(&mut self) {
+        self.depth = 0;
+        self.drag = false;
+        self.pending.clear();
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || !self.pending.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn begin(&mut self) {
+        if self.enabled {
+            self.depth += 1;
+        }
+    }
+
+    pub fn commit(&mut self) {
+        if self.depth == 0 {
+            return;
+        }
+        self.depth -= 1;
+        if self.depth == 0 {
+            self.flush();
+        }
+    }
+
+    /// Opens the transaction of a node drag unless one is already open.
+    pub fn begin_drag(&mut self) {
+        if self.enabled && !self.drag {
+            self.drag = true;
+            self.begin();
+        }
+    }
+
+    /// Commits the transaction opened by [`History::begin_drag`] if it is still open.
+    pub fn commit_drag(&mut self) {
+        if self.drag {
+            self.drag = false;
+            self.commit();
+        }
+    }
+
+    pub fn record(&mut self, change: Change<N, E, Ty, Ix, Dn, De>) {
+        if !self.enabled {
+            return;
+        }
+        if !self.selection
+            && matches!(
+                change,
+                Change::NodeSelected { .. } | Change::EdgeSelected { .. }
+            )
+        {
+            return;
+        }
+
+        // A drag produces a move every frame, keep only the first and the last position.
+        if let (
+            Change::MoveNode { idx, to, .. },
+            Some(Change::MoveNode {
+                idx: last_idx,
+                to: last_to,
+                ..
+            }),
+        ) = (&change, self.pending.last_mut())
+        {
+            if idx == last_idx {
+                *last_to = *to;
+                return;
+            }
+        }
+
+        self.pending.push(change);
+        if self.depth == 0 {
+            self.flush();
+        }
+    }
+
+    /// Closes any open transaction and returns the last undo step.
+    pub fn pop_undo(&mut self) -> Option<Transaction<N, E, Ty, Ix, Dn, De>> {
+        self.depth = 0;
+        self.drag = false;
+        self.flush();
+        self.undo.pop_back()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Transaction<N, E, Ty, Ix, Dn, De>> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, tx: Transaction<N, E, Ty, Ix, Dn, De>) {
+        self.undo.push_back(tx);
+        self.trim();
+    }
+
+    pub fn push_redo(&mut self, tx: Transaction<N, E, Ty, Ix, Dn, De>) {
+        self.redo.push(tx);
+    }
+
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let tx = std::mem::take(&mut self.pending);
+        self.redo.clear();
+        self.push_undo(tx);
+    }
+
+    fn trim(&mut self) {
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
@@ -0,0 +1,1156 @@
+//! Graphviz DOT documents.
+//!
+//! Export writes node `label`, `pos` and `color`, `pin=true` for pinned nodes, edge `label`
+//! and ports, `style=invis` for hidden elements, `style=bold` for selected edges and one
+//! `cluster_<id>` subgraph per group.
+//!
+//! Import understands the full DOT grammar: `node` / `edge` default attributes, edge chains,
+//! subgraph operands, quoted, HTML and concatenated ids and comments. Named subgraphs become
+//! groups; a node joins the most deeply nested named subgraph it is mentioned in, the first
+//! one of them if there are several at that depth. `pos` attributes
+//! become locations and `color` (or `fillcolor`) node colors; `pin=true` or a `pos` ending in
+//! `!` pins the node. Payloads are not imported.
+//!
+//! Layouts computed by Graphviz are loaded from `dot -Tplain` output with
+//! [`import_plain_as`] or [`apply_plain_positions`].
+//!
+//! Graphviz puts the y axis upwards, so y coordinates are negated in both directions.
+//! Plain output coordinates are in inches and are scaled to points to match `pos`.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use egui::{Color32, Pos2};
+use petgraph::{
+    stable_graph::{DefaultIx, IndexType, NodeIndex, StableGraph},
+    EdgeType,
+};
+
+use super::{color_from_hex, color_to_hex, ImportError, ImportedGraph};
+use crate::{default_edge_transform, default_node_transform, DisplayEdge, DisplayNode, Graph};
+
+/// Points per inch, the unit ratio between `pos` attributes and plain output.
+const POINTS_PER_INCH: f32 = 72.;
+
+/// Writes the graph as a DOT document. Node ids are `n<index>`.
+pub fn export_graph_to_string<N, E, Ty, Ix, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>) -> String
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut out = String::new();
+    write_graph(&mut out, g).expect("writing to a String never fails");
+    out
+}
+
+fn write_graph<N, E, Ty, Ix, Dn, De>(
+    out: &mut String,
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+) -> std::fmt::Result
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let (kind, op) = if g.is_directed() {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    writeln!(out, "{kind} {{")?;
+
+    for (idx, n) in g.nodes_iter() {
+        let loc = n.location();
+        write!(
+            out,
+            "  n{} [label={}, pos=\"{},{}\"",
+            idx.index(),
+            quote(&n.label()),
+            loc.x,
+            -loc.y
+        )?;
+        if let Some(c) = n.color() {
+            write!(out, ", color=\"{}\"", color_to_hex(c))?;
+        }
+        if n.hidden() {
+            write!(out, ", style=invis")?;
+        }
+        if n.pinned() {
+            write!(out, ", pin=true")?;
+        }
+        writeln!(out, "];")?;
+    }
+
+    for (id, group) in g.groups_iter() {
+        writeln!(out, "  subgraph cluster_{} {{", id.index())?;
+        writeln!(out, "    label={};", quote(group.label()))?;
+        for m in group.members() {
+            writeln!(out, "    n{};", m.index())?;
+        }
+        writeln!(out, "  }}")?;
+    }
+
+    for (idx, e) in g.edges_iter() {
+        let Some((start, end)) = g.edge_endpoints(idx) else {
+            continue;
+        };
+        write!(out, "  n{}", start.index())?;
+        if let Some(port) = e.source_port() {
+            write!(out, ":{}", quote(port))?;
+        }
+        write!(out, " {op} n{}", end.index())?;
+        if let Some(port) = e.target_port() {
+            write!(out, ":{}", quote(port))?;
+        }
+        write!(out, " [label={}", quote(&e.label()))?;
+        if e.hidden() {
+            write!(out, ", style=invis")?;
+        } else if e.selected() {
+            write!(out, ", style=bold")?;
+        }
+        writeln!(out, "];")?;
+    }
+
+    writeln!(out, "}}")
+}
+
+fn quote(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            _ => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Reads a DOT document into a directed or undirected graph according to its header.
+///
+/// # Errors
+///
+/// [`ImportError::Syntax`] when the text is not valid DOT.
+pub fn import_graph_from_str(text: &str) -> Result<ImportedGraph, ImportError> {
+    let doc = Parser::new(text)?.parse()?;
+    if doc.directed {
+        Ok(ImportedGraph::Directed(doc.to_graph()))
+    } else {
+        Ok(ImportedGraph::Undirected(doc.to_graph()))
+    }
+}
+
+/// Reads a DOT document into a graph with the given directedness ignoring the header.
+///
+/// # Errors
+///
+/// Same as [`import_graph_from_str`].
+pub fn import_graph_as<Ty: EdgeType>(text: &str) -> Result<Graph<(), (), Ty>, ImportError> {
+    Ok(Parser::new(text)?.parse()?.to_graph())
+}
+
+/// Reads `dot -Tplain` output. Plain output does not record directedness, so it is chosen
+/// by the caller.
+///
+/// # Errors
+///
+/// [`ImportError::Syntax`] for malformed lines, [`ImportError::Schema`] when an edge
+/// references an unknown node.
+pub fn import_plain_as<Ty: EdgeType>(text: &str) -> Result<Graph<(), (), Ty>, ImportError> {
+    let plain = parse_plain(text)?;
+    let mut g: Graph<(), (), Ty> = Graph::from(&StableGraph::default());
+    let mut ids = HashMap::new();
+
+    for n in &plain.nodes {
+        let idx = g.add_node_custom((), |node| {
+            default_node_transform(node);
+            node.set_label(n.label.clone());
+            node.set_location(n.location);
+            if let Some(c) = n.color {
+                node.set_color(c);
+            }
+            node.set_hidden(n.hidden);
+        });
+        ids.insert(n.name.as_str(), idx);
+    }
+
+    for e in &plain.edges {
+        let endpoint = |name: &str| {
+            ids.get(name)
+                .copied()
+                .ok_or_else(|| ImportError::Schema(format!("edge references unknown node {name}")))
+        };
+        let (start, end) = (endpoint(&e.tail)?, endpoint(&e.head)?);
+        g.add_edge_custom(start, end, (), |edge| {
+            default_edge_transform(edge);
+            if let Some(label) = &e.label {
+                edge.set_label(label.clone());
+            }
+            edge.set_hidden(e.hidden);
+        });
+    }
+
+    Ok(g)
+}
+
+/// Moves the nodes of a graph exported with [`export_graph_to_string`] to the locations
+/// computed by `dot -Tplain`. Returns the number of moved nodes.
+///
+/// # Errors
+///
+/// [`ImportError::Syntax`] for malformed lines.
+pub fn apply_plain_positions<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    text: &str,
+) -> Result<usize, ImportError>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let plain = parse_plain(text)?;
+    let mut moved = 0;
+    for n in &plain.nodes {
+        let idx = n
+            .name
+            .strip_prefix('n')
+            .and_then(|i| i.parse::<usize>().ok())
+            .map(NodeIndex::<Ix>::new);
+        if let Some(idx) = idx.filter(|idx| g.node(*idx).is_some()) {
+            g.set_node_location(idx, n.location);
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+type Attrs = HashMap<String, String>;
+
+/// Parsed document before it is turned into a [`Graph`].
+#[derive(Debug, Default)]
+struct DotDocument {
+    directed: bool,
+    nodes: Vec<DotNode>,
+    ids: HashMap<String, usize>,
+    edges: Vec<DotEdge>,
+    groups: Vec<DotGroup>,
+}
+
+#[derive(Debug)]
+struct DotNode {
+    name: String,
+    attrs: Attrs,
+    group: Option<usize>,
+    /// Nesting depth of `group`.
+    group_depth: usize,
+}
+
+#[derive(Debug, Clone)]
+struct NodeRef {
+    node: usize,
+    port: Option<String>,
+}
+
+#[derive(Debug)]
+struct DotEdge {
+    tail: NodeRef,
+    head: NodeRef,
+    attrs: Attrs,
+}
+
+#[derive(Debug)]
+struct DotGroup {
+    name: String,
+    label: Option<String>,
+    members: Vec<usize>,
+}
+
+/// Defaults in effect inside a graph or subgraph body.
+#[derive(Debug, Clone, Default)]
+struct Scope {
+    node: Attrs,
+    edge: Attrs,
+    group: Option<usize>,
+    /// Number of named subgraphs enclosing the body.
+    depth: usize,
+}
+
+impl DotDocument {
+    fn to_graph<Ty: EdgeType>(&self) -> Graph<(), (), Ty, DefaultIx> {
+        let mut g: Graph<(), (), Ty, DefaultIx> = Graph::from(&StableGraph::default());
+
+        let mut indices = Vec::with_capacity(self.nodes.len());
+        for n in &self.nodes {
+            let label = n
+                .attrs
+                .get("label")
+                .map_or_else(|| n.name.clone(), |l| unescape_label(l, &n.name));
+            let location = n.attrs.get("pos").and_then(|p| parse_pos(p));
+            let color = n
+                .attrs
+                .get("color")
+                .or_else(|| n.attrs.get("fillcolor"))
+                .and_then(|c| parse_color(c));
+            let hidden = n.attrs.get("style").is_some_and(|s| is_invisible(s));
+            let pinned = n.attrs.get("pin").is_some_and(|p| p == "true")
+                || n.attrs
+                    .get("pos")
+                    .is_some_and(|p| p.trim_end().ends_with('!'));
+
+            indices.push(g.add_node_custom((), |node| {
+                default_node_transform(node);
+                node.set_label(label);
+                if let Some(loc) = location {
+                    node.set_location(loc);
+                }
+                if let Some(c) = color {
+                    node.set_color(c);
+                }
+                node.set_hidden(hidden);
+                node.set_pinned(pinned);
+            }));
+        }
+
+        for e in &self.edges {
+            let (start, end) = (indices[e.tail.node], indices[e.head.node]);
+            let source_port = e
+                .tail
+                .port
+                .clone()
+                .or_else(|| e.attrs.get("tailport").cloned());
+            let target_port = e
+                .head
+                .port
+                .clone()
+                .or_else(|| e.attrs.get("headport").cloned());
+            let hidden = e.attrs.get("style").is_some_and(|s| is_invisible(s));
+
+            g.add_edge_custom(start, end, (), |edge| {
+                default_edge_transform(edge);
+                if let Some(label) = e.attrs.get("label") {
+                    edge.set_label(unescape_label(label, ""));
+                }
+                edge.set_source_port(source_port);
+                edge.set_target_port(target_port);
+                edge.set_hidden(hidden);
+            });
+        }
+
+        for group in &self.groups {
+            if group.members.is_empty() {
+                continue;
+            }
+            let label = group.label.clone().unwrap_or_else(|| group.name.clone());
+            let members = group.members.iter().map(|m| indices[*m]).collect();
+            g.add_group(label, members);
+        }
+
+        g
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Identifier, numeral, quoted or HTML string. Quoted ids are never keywords.
+    Id(String, bool),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Eq,
+    Semi,
+    Comma,
+    Colon,
+    /// Edge operator, `true` for `->`.
+    EdgeOp(bool),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    doc: DotDocument,
+    group_names: HashMap<String, usize>,
+    /// Every node mention in order, used to collect the nodes of subgraph operands.
+    mentions: Vec<usize>,
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, ImportError> {
+        Ok(Self {
+            tokens: tokenize(text)?,
+            pos: 0,
+            doc: DotDocument::default(),
+            group_names: HashMap::new(),
+            mentions: Vec::new(),
+        })
+    }
+
+    fn parse(mut self) -> Result<DotDocument, ImportError> {
+        if self.peek_keyword("strict") {
+            self.pos += 1;
+        }
+        if self.peek_keyword("digraph") {
+            self.doc.directed = true;
+        } else if !self.peek_keyword("graph") {
+            return Err(self.error("expected graph or digraph"));
+        }
+        self.pos += 1;
+        if matches!(self.peek(), Some(Token::Id(..))) {
+            self.pos += 1;
+        }
+        self.expect(&Token::LBrace)?;
+        self.stmt_list(&mut Scope::default())?;
+        self.expect(&Token::RBrace)?;
+        if self.peek().is_some() {
+            return Err(self.error("unexpected content after graph"));
+        }
+        Ok(self.doc)
+    }
+
+    fn stmt_list(&mut self, scope: &mut Scope) -> Result<(), ImportError> {
+        while !matches!(self.peek(), Some(Token::RBrace) | None) {
+            self.stmt(scope)?;
+            if self.peek() == Some(&Token::Semi) {
+                self.pos += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self, scope: &mut Scope) -> Result<(), ImportError> {
+        let is_attr_stmt = self.peek_at(1) == Some(&Token::LBracket);
+        if is_attr_stmt && self.peek_keyword("graph") {
+            self.pos += 1;
+            let attrs = self.attr_lists()?;
+            self.set_group_label(scope, &attrs);
+            return Ok(());
+        }
+        if is_attr_stmt && self.peek_keyword("node") {
+            self.pos += 1;
+            scope.node.extend(self.attr_lists()?);
+            return Ok(());
+        }
+        if is_attr_stmt && self.peek_keyword("edge") {
+            self.pos += 1;
+            scope.edge.extend(self.attr_lists()?);
+            return Ok(());
+        }
+        if matches!(self.peek(), Some(Token::Id(..))) && self.peek_at(1) == Some(&Token::Eq) {
+            let key = self.id()?;
+            self.pos += 1;
+            let value = self.id()?;
+            self.set_group_label(scope, &HashMap::from([(key, value)]));
+            return Ok(());
+        }
+
+        let is_node_stmt =
+            matches!(self.peek(), Some(Token::Id(..))) && !self.peek_keyword("subgraph");
+        let mut operands = vec![self.operand(scope)?];
+        while let Some(Token::EdgeOp(directed)) = self.peek() {
+            if *directed != self.doc.directed {
+                return Err(self.error("edge operator does not match the graph kind"));
+            }
+            self.pos += 1;
+            operands.push(self.operand(scope)?);
+        }
+        let attrs = if self.peek() == Some(&Token::LBracket) {
+            self.attr_lists()?
+        } else {
+            Attrs::new()
+        };
+
+        if operands.len() == 1 {
+            if is_node_stmt {
+                let node = operands[0][0].node;
+                self.doc.nodes[node].attrs.extend(attrs);
+            }
+            return Ok(());
+        }
+
+        for pair in operands.windows(2) {
+            for tail in &pair[0] {
+                for head in &pair[1] {
+                    let mut edge_attrs = scope.edge.clone();
+                    edge_attrs.extend(attrs.clone());
+                    self.doc.edges.push(DotEdge {
+                        tail: tail.clone(),
+                        head: head.clone(),
+                        attrs: edge_attrs,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Node id or subgraph, returns the referenced nodes.
+    fn operand(&mut self, scope: &Scope) -> Result<Vec<NodeRef>, ImportError> {
+        if self.peek_keyword("subgraph") || self.peek() == Some(&Token::LBrace) {
+            return self.subgraph(scope);
+        }
+
+        let name = self.id()?;
+        let mut port = None;
+        if self.peek() == Some(&Token::Colon) {
+            self.pos += 1;
+            port = Some(self.id()?);
+            // Compass point after the port name.
+            if self.peek() == Some(&Token::Colon) {
+                self.pos += 1;
+                self.id()?;
+            }
+        }
+        let node = self.mention(name, scope);
+        Ok(vec![NodeRef { node, port }])
+    }
+
+    fn subgraph(&mut self, scope: &Scope) -> Result<Vec<NodeRef>, ImportError> {
+        let mut inner = scope.clone();
+        if self.peek_keyword("subgraph") {
+            self.pos += 1;
+            if matches!(self.peek(), Some(Token::Id(..))) {
+                let name = self.id()?;
+                let doc = &mut self.doc;
+                let group = *self.group_names.entry(name.clone()).or_insert_with(|| {
+                    doc.groups.push(DotGroup {
+                        name,
+                        label: None,
+                        members: Vec::new(),
+                    });
+                    doc.groups.len() - 1
+                });
+                inner.group = Some(group);
+                inner.depth += 1;
+            }
+        }
+
+        let first_mention = self.mentions.len();
+        self.expect(&Token::LBrace)?;
+        self.stmt_list(&mut inner)?;
+        self.expect(&Token::RBrace)?;
+
+        let mut seen = HashSet::new();
+        Ok(self.mentions[first_mention..]
+            .iter()
+            .filter(|n| seen.insert(**n))
+            .map(|n| NodeRef {
+                node: *n,
+                port: None,
+            })
+            .collect())
+    }
+
+    /// Returns the node with the given name creating it with the scope defaults.
+    fn mention(&mut self, name: String, scope: &Scope) -> usize {
+        let doc = &mut self.doc;
+        let node = *doc.ids.entry(name.clone()).or_insert_with(|| {
+            doc.nodes.push(DotNode {
+                name,
+                attrs: scope.node.clone(),
+                group: None,
+                group_depth: 0,
+            });
+            doc.nodes.len() - 1
+        });
+        let n = &mut doc.nodes[node];
+        if let Some(group) = scope
+            .group
+            .filter(|_| n.group.is_none() || scope.depth > n.group_depth)
+        {
+            if let Some(outer) = n.group.replace(group) {
+                doc.groups[outer].members.retain(|&m| m != node);
+            }
+            n.group_depth = scope.depth;
+            doc.groups[group].members.push(node);
+        }
+        self.mentions.push(node);
+        node
+    }
+
+    fn set_group_label(&mut self, scope: &Scope, attrs: &Attrs) {
+        if let (Some(group), Some(label)) = (scope.group, attrs.get("label")) {
+            self.doc.groups[group].label = Some(unescape_label(label, ""));
+        }
+    }
+
+    fn attr_lists(&mut self) -> Result<Attrs, ImportError> {
+        let mut attrs = Attrs::new();
+        while self.peek() == Some(&Token::LBracket) {
+            self.pos += 1;
+            while self.peek() != Some(&Token::RBracket) {
+                let key = self.id()?;
+                self.expect(&Token::Eq)?;
+                let value = self.id()?;
+                attrs.insert(key, value);
+                if matches!(self.peek(), Some(Token::Comma | Token::Semi)) {
+                    self.pos += 1;
+                }
+            }
+            self.pos += 1;
+        }
+        Ok(attrs)
+    }
+
+    fn id(&mut self) -> Result<String, ImportError> {
+        match self.peek() {
+            Some(Token::Id(id, _)) => {
+                let id = id.clone();
+                self.pos += 1;
+                Ok(id)
+            }
+            _ => Err(self.error("expected identifier")),
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), ImportError> {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {token:?}")))
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset).map(|(t, _)| t)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(id, false)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn error(&self, msg: &str) -> ImportError {
+        match self.tokens.get(self.pos) {
+            Some((_, line)) => ImportError::Syntax(format!("line {line}: {msg}")),
+            None => ImportError::Syntax(format!("end of input: {msg}")),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, ImportError> {
+    let chars: Vec<char> = text.chars().collect();
+    let at = |i: usize| chars.get(i).copied();
+    let error = |line: usize, msg: &str| ImportError::Syntax(format!("line {line}: {msg}"));
+
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut line_start) = (0, 1, true);
+    while let Some(c) = at(i) {
+        if c == '\n' {
+            line += 1;
+            line_start = true;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        // Comments and preprocessor output lines.
+        if (c == '#' && line_start) || (c == '/' && at(i + 1) == Some('/')) {
+            while at(i).is_some_and(|c| c != '\n') {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && at(i + 1) == Some('*') {
+            i += 2;
+            loop {
+                match at(i) {
+                    Some('*') if at(i + 1) == Some('/') => break,
+                    Some('\n') => line += 1,
+                    Some(_) => {}
+                    None => return Err(error(line, "unterminated comment")),
+                }
+                i += 1;
+            }
+            i += 2;
+            continue;
+        }
+        line_start = false;
+
+        let token = match c {
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '=' => Token::Eq,
+            ';' => Token::Semi,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '-' if at(i + 1) == Some('>') => {
+                i += 1;
+                Token::EdgeOp(true)
+            }
+            '-' if at(i + 1) == Some('-') => {
+                i += 1;
+                Token::EdgeOp(false)
+            }
+            '"' => {
+                let mut id = String::new();
+                loop {
+                    i += 1;
+                    loop {
+                        match at(i) {
+                            Some('"') => break,
+                            Some('\\') if at(i + 1) == Some('"') => {
+                                id.push('"');
+                                i += 1;
+                            }
+                            // Line continuation.
+                            Some('\\') if at(i + 1) == Some('\n') => {
+                                line += 1;
+                                i += 1;
+                            }
+                            Some(c) => {
+                                line += usize::from(c == '\n');
+                                id.push(c);
+                            }
+                            None => return Err(error(line, "unterminated string")),
+                        }
+                        i += 1;
+                    }
+                    // `"a" + "b"` concatenation.
+                    let mut j = i + 1;
+                    while at(j).is_some_and(char::is_whitespace) {
+                        j += 1;
+                    }
+                    if at(j) != Some('+') {
+                        break;
+                    }
+                    j += 1;
+                    while at(j).is_some_and(char::is_whitespace) {
+                        j += 1;
+                    }
+                    if at(j) != Some('"') {
+                        break;
+                    }
+                    line += chars[i..j].iter().filter(|c| **c == '\n').count();
+                    i = j;
+                }
+                Token::Id(id, true)
+            }
+            '<' => {
+                let mut depth = 0;
+                let start = i + 1;
+                loop {
+                    match at(i) {
+                        Some('<') => depth += 1,
+                        Some('>') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        Some('\n') => line += 1,
+                        Some(_) => {}
+                        None => return Err(error(line, "unterminated HTML string")),
+                    }
+                    i += 1;
+                }
+                Token::Id(chars[start..i].iter().collect(), true)
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' => {
+                let start = i;
+                i += 1;
+                while at(i).is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    i += 1;
+                }
+                let id: String = chars[start..i].iter().collect();
+                if id.parse::<f64>().is_err() {
+                    return Err(error(line, &format!("invalid numeral {id}")));
+                }
+                tokens.push((Token::Id(id, false), line));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' || !c.is_ascii() => {
+                let start = i;
+                while at(i).is_some_and(|c| c.is_alphanumeric() || c == '_' || !c.is_ascii()) {
+                    i += 1;
+                }
+                tokens.push((Token::Id(chars[start..i].iter().collect(), false), line));
+                continue;
+            }
+            c => return Err(error(line, &format!("unexpected character {c:?}"))),
+        };
+        tokens.push((token, line));
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+/// Resolves the escapes of a label: `\n`, `\l`, `\r` become line breaks and `\N` the node
+/// name.
+fn unescape_label(s: &str, name: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'l' | 'r') => res.push('\n'),
+            Some('N') => res.push_str(name),
+            Some(c) => res.push(c),
+            None => res.push('\\'),
+        }
+    }
+    res.trim_end_matches('\n').to_string()
+}
+
+fn is_invisible(style: &str) -> bool {
+    style.split(',').any(|s| s.trim() == "invis")
+}
+
+/// `x,y` in points with an optional trailing `!`, y pointing up.
+fn parse_pos(s: &str) -> Option<Pos2> {
+    let mut coords = s.trim().trim_end_matches('!').split(',');
+    let x = coords.next()?.trim().parse::<f32>().ok()?;
+    let y = coords.next()?.trim().parse::<f32>().ok()?;
+    Some(Pos2::new(x, -y))
+}
+
+/// Hex colors and the most common X11 color names.
+fn parse_color(s: &str) -> Option<Color32> {
+    let s = s.trim();
+    if s.starts_with('#') {
+        return color_from_hex(s);
+    }
+    let c = match s.to_ascii_lowercase().as_str() {
+        "black" => Color32::BLACK,
+        "white" => Color32::WHITE,
+        "red" => Color32::RED,
+        "green" => Color32::GREEN,
+        "blue" => Color32::BLUE,
+        "yellow" => Color32::YELLOW,
+        "orange" => Color32::ORANGE,
+        "brown" => Color32::BROWN,
+        "gold" => Color32::GOLD,
+        "gray" | "grey" => Color32::GRAY,
+        "cyan" => Color32::from_rgb(0, 255, 255),
+        "magenta" => Color32::from_rgb(255, 0, 255),
+        "purple" => Color32::from_rgb(160, 32, 240),
+        "pink" => Color32::from_rgb(255, 192, 203),
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// Content of `dot -Tplain` output.
+struct Plain {
+    nodes: Vec<PlainNode>,
+    edges: Vec<PlainEdge>,
+}
+
+struct PlainNode {
+    name: String,
+    label: String,
+    location: Pos2,
+    color: Option<Color32>,
+    hidden: bool,
+}
+
+struct PlainEdge {
+    tail: String,
+    head: String,
+    label: Option<String>,
+    hidden: bool,
+}
+
+fn parse_plain(text: &str) -> Result<Plain, ImportError> {
+    let mut plain = Plain {
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+
+    for (n, line) in text.lines().enumerate() {
+        let error = |msg: &str| ImportError::Syntax(format!("line {}: {msg}", n + 1));
+        let fields = split_plain_line(line);
+        let field = |i: usize| {
+            fields
+                .get(i)
+                .map(String::as_str)
+                .ok_or_else(|| error("missing field"))
+        };
+        let coord = |i: usize| {
+            field(i)?
+                .parse::<f32>()
+                .map(|v| v * POINTS_PER_INCH)
+                .map_err(|_| error("invalid coordinate"))
+        };
+
+        match fields.first().map(String::as_str) {
+            Some("node") => {
+                // node name x y width height label style shape color fillcolor
+                let color = field(10)
+                    .ok()
+                    .filter(|c| *c != "none")
+                    .and_then(parse_color)
+                    .or_else(|| {
+                        field(9)
+                            .ok()
+                            .filter(|c| *c != "black")
+                            .and_then(parse_color)
+                    });
+                plain.nodes.push(PlainNode {
+                    name: field(1)?.to_string(),
+                    label: unescape_label(field(6)?, field(1)?),
+                    location: Pos2::new(coord(2)?, -coord(3)?),
+                    color,
+                    hidden: is_invisible(field(7)?),
+                });
+            }
+            Some("edge") => {
+                // edge tail head n x1 y1 .. xn yn [label xl yl] style color
+                let points: usize = field(3)?
+                    .parse()
+                    .map_err(|_| error("invalid point count"))?;
+                let rest = 4 + 2 * points;
+                let label = (fields.len() >= rest + 5).then(|| unescape_label(&fields[rest], ""));
+                plain.edges.push(PlainEdge {
+                    tail: field(1)?.to_string(),
+                    head: field(2)?.to_string(),
+                    label,
+                    hidden: fields
+                        .get(fields.len().saturating_sub(2))
+                        .is_some_and(|s| is_invisible(s)),
+                });
+            }
+            Some("graph" | "stop") | None => {}
+            Some(other) => return Err(error(&format!("unknown statement {other}"))),
+        }
+    }
+
+    Ok(plain)
+}
+
+/// Splits a plain output line on whitespace keeping quoted fields together.
+fn split_plain_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut field = String::new();
+        if c == '"' {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' if chars.peek() == Some(&'"') => {
+                        field.push('"');
+                        chars.next();
+                    }
+                    _ => field.push(c),
+                }
+            }
+        } else {
+            field.push(c);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                field.push(c);
+            }
+        }
+        fields.push(field);
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::Directed;
+
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_visuals_groups_and_ports() {
+        let mut g: Graph<(), (), Directed> = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_label_and_location((), "say \"hi\"".to_string(), Pos2::new(1., 2.));
+        let b = g.add_node_with_location((), Pos2::new(-3., 4.5));
+        let c = g.add_node(());
+        g.node_mut(a)
+            .unwrap()
+            .set_color(Color32::from_rgb(10, 20, 30));
+        g.set_node_hidden(c, true);
+        g.set_node_pinned(b, true);
+        g.add_edge_with_label(a, b, (), "first".to_string());
+        g.add_edge(a, b, ());
+        let ported = g.add_edge_with_ports(b, a, (), "out".to_string(), "in".to_string());
+        g.set_edge_hidden(ported, true);
+        g.add_group("pair".to_string(), vec![a, b]).unwrap();
+
+        let text = export_graph_to_string(&g);
+        let ImportedGraph::Directed(imported) = import_graph_from_str(&text).unwrap() else {
+            panic!("expected directed graph");
+        };
+
+        assert_eq!(imported.node_count(), 3);
+        for (idx, n) in g.nodes_iter() {
+            let m = imported.node(idx).unwrap();
+            assert_eq!(m.label(), n.label());
+            assert_eq!(m.location(), n.location());
+            assert_eq!(m.color(), n.color());
+            assert_eq!(m.hidden(), n.hidden());
+            assert_eq!(m.pinned(), n.pinned());
+        }
+        for (idx, e) in g.edges_iter() {
+            let f = imported.edge(idx).unwrap();
+            assert_eq!(imported.edge_endpoints(idx), g.edge_endpoints(idx));
+            assert_eq!(f.label(), e.label());
+            assert_eq!(f.order(), e.order());
+            assert_eq!(f.hidden(), e.hidden());
+            assert_eq!(f.source_port(), e.source_port());
+            assert_eq!(f.target_port(), e.target_port());
+        }
+        let (_, group) = imported.groups_iter().next().unwrap();
+        assert_eq!(group.label(), "pair");
+        assert_eq!(group.members(), &[a, b]);
+    }
+
+    #[test]
+    fn parses_full_grammar() {
+        let text = r#"
+            /* header */
+            strict graph "G" {
+                node [color=red]; // default for following nodes
+                a [label="A\nline" pos="10,20!"];
+                subgraph cluster_x {
+                    label = "X" + " group";
+                    node [color=blue]
+                    b; c
+                    subgraph inner { d }
+                }
+                a -- { b c } -- d [label=<<b>bold</b>>];
+                e:p1:n -- a
+            # preprocessor line
+            }
+        "#;
+        let ImportedGraph::Undirected(g) = import_graph_from_str(text).unwrap() else {
+            panic!("expected undirected graph");
+        };
+
+        assert_eq!(g.node_count(), 5);
+        assert_eq!(g.edge_count(), 5);
+        let a = g
+            .nodes_iter()
+            .find(|(_, n)| n.label() == "A\nline")
+            .unwrap()
+            .1;
+        assert_eq!(a.location(), Pos2::new(10., -20.));
+        assert_eq!(a.color(), Some(Color32::RED));
+        let d = g.nodes_iter().find(|(_, n)| n.label() == "d").unwrap().1;
+        assert_eq!(d.color(), Some(Color32::BLUE));
+        let e = g.nodes_iter().find(|(_, n)| n.label() == "e").unwrap().1;
+        assert_eq!(e.color(), Some(Color32::RED));
+
+        let groups: Vec<_> = g
+            .groups_iter()
+            .map(|(_, gr)| (gr.label().to_string(), gr.members().len()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![("X group".to_string(), 2), ("inner".to_string(), 1)]
+        );
+
+        let bold = g
+            .edges_iter()
+            .filter(|(_, e)| e.label() == "<b>bold</b>")
+            .count();
+        assert_eq!(bold, 4);
+        let ported = g
+            .edges_iter()
+            .find(|(_, e)| e.source_port().is_some())
+            .unwrap()
+            .1;
+        assert_eq!(ported.source_port(), Some("p1"));
+    }
+
+    #[test]
+    fn nodes_join_the_innermost_subgraph() {
+        let text = r#"
+            digraph {
+                subgraph outer {
+                    a; b
+                    subgraph inner { a; c }
+                    c
+                }
+                subgraph other { b }
+            }
+        "#;
+        let g = import_graph_as::<Directed>(text).unwrap();
+        let members = |label: &str| {
+            let (_, group) = g.groups_iter().find(|(_, gr)| gr.label() == label).unwrap();
+            let mut names: Vec<_> = group
+                .members()
+                .iter()
+                .map(|&m| g.node(m).unwrap().label())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(members("outer"), vec!["b"]);
+        assert_eq!(members("inner"), vec!["a", "c"]);
+        // Left without members, so no group is created.
+        assert_eq!(g.groups_iter().count(), 2);
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        for text in [
+            "",
+            "digraph { a -> }",
+            "graph { a -> b }",
+            "digraph { \"a }",
+            "digraph {} x",
+        ] {
+            assert!(
+                matches!(import_graph_from_str(text), Err(ImportError::Syntax(_))),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn loads_plain_layouts() {
+        let plain = "graph 1 2.5 1.5\n\
+            node n0 0.5 1 0.75 0.5 \"first node\" solid ellipse black lightgrey\n\
+            node n1 2 0.5 0.75 0.5 n1 filled box black \"#FF0000\"\n\
+            edge n0 n1 4 0.5 1 1 1 1.5 0.5 2 0.5 lbl 1.2 0.8 solid black\n\
+            stop\n";
+
+        let g = import_plain_as::<Directed>(plain).unwrap();
+        assert_eq!(g.edge_count(), 1);
+        let (_, first) = g.nodes_iter().next().unwrap();
+        assert_eq!(first.label(), "first node");
+        assert_eq!(first.location(), Pos2::new(36., -72.));
+        assert_eq!(first.color(), None);
+        let (_, e) = g.edges_iter().next().unwrap();
+        assert_eq!(e.label(), "lbl");
+
+        let mut exported: Graph<(), (), Directed> = Graph::from(&StableGraph::default());
+        let a = exported.add_node(());
+        let b = exported.add_node(());
+        assert_eq!(apply_plain_positions(&mut exported, plain), Ok(2));
+        assert_eq!(exported.node(a).unwrap().location(), Pos2::new(36., -72.));
+        assert_eq!(exported.node(b).unwrap().color(), None);
+        assert_eq!(exported.node(b).unwrap().location(), Pos2::new(144., -36.));
+    }
+}
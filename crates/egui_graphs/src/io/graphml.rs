@@ -0,0 +1,778 @@
+//! GraphML graph documents as written by Gephi and yEd.
+//!
+//! Besides the topology the following attributes round-trip:
+//! - node labels, locations and colors, read either from Gephi style `label`, `x`, `y`,
+//!   `r`, `g`, `b` (or `color` as `#RRGGBB`) keys or from yEd `nodegraphics`
+//!   (`y:Geometry`, `y:Fill`, `y:NodeLabel`);
+//! - edge labels, from a `label` key or yEd `edgegraphics` (`y:EdgeLabel`);
+//! - node `<port>` declarations with their kind and offset (`port_kind`, `port_x` and
+//!   `port_y` keys) and edge `sourceport` / `targetport` attributes.
+//!
+//! Export writes both flavours so either tool picks the visuals up. Any other `<key>` is
+//! handed to the payload type through [`GraphMlPayload`]. Payload keys are exported with ids
+//! `node:<name>` and `edge:<name>`, so payloads may use attribute names of the visual keys
+//! without the values getting mixed up.
+//!
+//! Directedness follows `edgedefault` of the first `<graph>`. Parallel edges are added in
+//! document order, so [`crate::Edge::order`] is reproduced for graphs exported by this module.
+//! Nodes of nested graphs are flattened into the top level graph.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use egui::{Color32, Pos2, Vec2};
+use petgraph::{
+    stable_graph::{DefaultIx, IndexType, NodeIndex, StableGraph},
+    EdgeType,
+};
+
+use super::{color_from_hex, color_to_hex, escape_xml, ImportError, ImportedGraph};
+use crate::{
+    default_edge_transform, default_node_transform, node_size, DisplayEdge, DisplayNode, Graph,
+    Port, PortKind,
+};
+
+const GRAPHML_NS: &str = "http://graphml.graphdrawing.org/xmlns";
+const YED_NS: &str = "http://www.yworks.com/xml/graphml";
+
+/// Ids of the keys carrying visuals, their values are not handed to payloads.
+const VISUAL_KEYS: [&str; 8] = ["label", "x", "y", "r", "g", "b", "color", "edge_label"];
+
+/// `<data>` values of a single element keyed by the `attr.name` of their `<key>`, or by
+/// the key id when the key has no name.
+pub type GraphMlData = HashMap<String, String>;
+
+/// Value type of a GraphML `<key>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphMlType {
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    #[default]
+    String,
+}
+
+impl GraphMlType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GraphMlType::Boolean => "boolean",
+            GraphMlType::Int => "int",
+            GraphMlType::Long => "long",
+            GraphMlType::Float => "float",
+            GraphMlType::Double => "double",
+            GraphMlType::String => "string",
+        }
+    }
+}
+
+/// Attribute declared by a [`GraphMlPayload`] for export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphMlKey {
+    pub name: String,
+    pub ty: GraphMlType,
+}
+
+impl GraphMlKey {
+    pub fn new(name: String, ty: GraphMlType) -> Self {
+        Self { name, ty }
+    }
+}
+
+/// Maps GraphML `<data>` attributes to node or edge payloads and back.
+///
+/// ```
+/// use egui_graphs::io::graphml::{GraphMlData, GraphMlKey, GraphMlPayload, GraphMlType};
+///
+/// #[derive(Clone)]
+/// struct City {
+///     population: u64,
+/// }
+///
+/// impl GraphMlPayload for City {
+///     fn keys() -> Vec<GraphMlKey> {
+///         vec![GraphMlKey::new("population".to_string(), GraphMlType::Long)]
+///     }
+///
+///     fn from_data(data: &GraphMlData) -> Self {
+///         let population = data.get("population").and_then(|v| v.parse().ok());
+///         Self { population: population.unwrap_or_default() }
+///     }
+///
+///     fn to_data(&self) -> Vec<(String, String)> {
+///         vec![("population".to_string(), self.population.to_string())]
+///     }
+/// }
+/// ```
+pub trait GraphMlPayload: Sized {
+    /// Keys written to the document header. Keys returned by [`GraphMlPayload::to_data`]
+    /// but not declared here are written as strings.
+    fn keys() -> Vec<GraphMlKey> {
+        Vec::new()
+    }
+
+    /// Builds a payload from all textual `<data>` of an element, key defaults included.
+    fn from_data(data: &GraphMlData) -> Self;
+
+    /// Attribute name and value pairs written as `<data>` of the element.
+    fn to_data(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+impl GraphMlPayload for () {
+    fn from_data(_: &GraphMlData) -> Self {}
+}
+
+/// Reads a document into a directed or undirected graph according to its `edgedefault`.
+///
+/// # Errors
+///
+/// [`ImportError::Syntax`] for malformed XML, [`ImportError::Schema`] when the document has
+/// no `<graph>` or an edge references an unknown node.
+pub fn import_graph_from_str<N, E>(text: &str) -> Result<ImportedGraph<N, E>, ImportError>
+where
+    N: GraphMlPayload + Clone,
+    E: GraphMlPayload + Clone,
+{
+    let doc = roxmltree::Document::parse(text).map_err(|e| ImportError::Syntax(e.to_string()))?;
+    let graph = find_graph(&doc)?;
+    if graph.attribute("edgedefault") == Some("undirected") {
+        Ok(ImportedGraph::Undirected(read_graph(&doc, graph)?))
+    } else {
+        Ok(ImportedGraph::Directed(read_graph(&doc, graph)?))
+    }
+}
+
+/// Reads a document into a graph with the given directedness ignoring `edgedefault`.
+///
+/// # Errors
+///
+/// Same as [`import_graph_from_str`].
+pub fn import_graph_as<N, E, Ty>(text: &str) -> Result<Graph<N, E, Ty, DefaultIx>, ImportError>
+where
+    N: GraphMlPayload + Clone,
+    E: GraphMlPayload + Clone,
+    Ty: EdgeType,
+{
+    let doc = roxmltree::Document::parse(text).map_err(|e| ImportError::Syntax(e.to_string()))?;
+    read_graph(&doc, find_graph(&doc)?)
+}
+
+/// Writes the graph as a GraphML document. Node ids are `n<index>`, edge ids `e<index>`.
+pub fn export_graph_to_string<N, E, Ty, Ix, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>) -> String
+where
+    N: GraphMlPayload + Clone,
+    E: GraphMlPayload + Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut out = String::new();
+    write_graph(&mut out, g).expect("writing to a String never fails");
+    out
+}
+
+fn write_graph<N, E, Ty, Ix, Dn, De>(
+    out: &mut String,
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+) -> std::fmt::Result
+where
+    N: GraphMlPayload + Clone,
+    E: GraphMlPayload + Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<graphml xmlns="{GRAPHML_NS}" xmlns:y="{YED_NS}">"#)?;
+    for (id, name, ty) in [
+        ("label", "label", GraphMlType::String),
+        ("x", "x", GraphMlType::Float),
+        ("y", "y", GraphMlType::Float),
+        ("r", "r", GraphMlType::Int),
+        ("g", "g", GraphMlType::Int),
+        ("b", "b", GraphMlType::Int),
+    ] {
+        write_key(out, id, "node", name, ty)?;
+    }
+    writeln!(
+        out,
+        r#"  <key id="graphics" for="node" yfiles.type="nodegraphics"/>"#
+    )?;
+    write_key(out, "edge_label", "edge", "label", GraphMlType::String)?;
+    write_key(out, "port_kind", "port", "kind", GraphMlType::String)?;
+    write_key(out, "port_x", "port", "x", GraphMlType::Float)?;
+    write_key(out, "port_y", "port", "y", GraphMlType::Float)?;
+    writeln!(
+        out,
+        r#"  <key id="edge_graphics" for="edge" yfiles.type="edgegraphics"/>"#
+    )?;
+    let node_keys = declared_keys(N::keys(), g.nodes_iter().map(|(_, n)| n.payload()));
+    let edge_keys = declared_keys(E::keys(), g.edges_iter().map(|(_, e)| e.payload()));
+    for key in node_keys {
+        write_key(
+            out,
+            &format!("node:{}", key.name),
+            "node",
+            &key.name,
+            key.ty,
+        )?;
+    }
+    for key in edge_keys {
+        write_key(
+            out,
+            &format!("edge:{}", key.name),
+            "edge",
+            &key.name,
+            key.ty,
+        )?;
+    }
+
+    let edgedefault = if g.is_directed() {
+        "directed"
+    } else {
+        "undirected"
+    };
+    writeln!(out, r#"  <graph id="G" edgedefault="{edgedefault}">"#)?;
+
+    for (idx, n) in g.nodes_iter() {
+        writeln!(out, r#"    <node id="n{}">"#, idx.index())?;
+        let loc = n.location();
+        let label = escape_xml(&n.label());
+        writeln!(out, r#"      <data key="label">{label}</data>"#)?;
+        writeln!(out, r#"      <data key="x">{}</data>"#, loc.x)?;
+        writeln!(out, r#"      <data key="y">{}</data>"#, loc.y)?;
+        if let Some(c) = n.color() {
+            let [r, g_, b, _] = c.to_srgba_unmultiplied();
+            writeln!(out, r#"      <data key="r">{r}</data>"#)?;
+            writeln!(out, r#"      <data key="g">{g_}</data>"#)?;
+            writeln!(out, r#"      <data key="b">{b}</data>"#)?;
+        }
+        for (name, value) in n.payload().to_data() {
+            let (name, value) = (escape_xml(&name), escape_xml(&value));
+            writeln!(out, r#"      <data key="node:{name}">{value}</data>"#)?;
+        }
+
+        let size = Vec2::new(node_size(n, Vec2::X), node_size(n, Vec2::Y)) * 2.;
+        let corner = loc - size / 2.;
+        writeln!(out, r#"      <data key="graphics">"#)?;
+        writeln!(out, "        <y:ShapeNode>")?;
+        writeln!(
+            out,
+            r#"          <y:Geometry x="{}" y="{}" width="{}" height="{}"/>"#,
+            corner.x, corner.y, size.x, size.y
+        )?;
+        if let Some(c) = n.color() {
+            writeln!(out, r#"          <y:Fill color="{}"/>"#, color_to_hex(c))?;
+        }
+        writeln!(out, "          <y:NodeLabel>{label}</y:NodeLabel>")?;
+        writeln!(out, "        </y:ShapeNode>")?;
+        writeln!(out, "      </data>")?;
+
+        for port in n.ports() {
+            let kind = match port.kind {
+                PortKind::Input => "input",
+                PortKind::Output => "output",
+            };
+            writeln!(out, r#"      <port name="{}">"#, escape_xml(&port.name))?;
+            writeln!(out, r#"        <data key="port_kind">{kind}</data>"#)?;
+            writeln!(
+                out,
+                r#"        <data key="port_x">{}</data>"#,
+                port.offset.x
+            )?;
+            writeln!(
+                out,
+                r#"        <data key="port_y">{}</data>"#,
+                port.offset.y
+            )?;
+            writeln!(out, "      </port>")?;
+        }
+        writeln!(out, "    </node>")?;
+    }
+
+    // Edges keep index order so parallel edges get the same order back on import.
+    for (idx, e) in g.edges_iter() {
+        let Some((start, end)) = g.edge_endpoints(idx) else {
+            continue;
+        };
+        write!(
+            out,
+            r#"    <edge id="e{}" source="n{}" target="n{}""#,
+            idx.index(),
+            start.index(),
+            end.index()
+        )?;
+        if let Some(port) = e.source_port() {
+            write!(out, r#" sourceport="{}""#, escape_xml(port))?;
+        }
+        if let Some(port) = e.target_port() {
+            write!(out, r#" targetport="{}""#, escape_xml(port))?;
+        }
+        writeln!(out, ">")?;
+        let label = escape_xml(&e.label());
+        writeln!(out, r#"      <data key="edge_label">{label}</data>"#)?;
+        for (name, value) in e.payload().to_data() {
+            let (name, value) = (escape_xml(&name), escape_xml(&value));
+            writeln!(out, r#"      <data key="edge:{name}">{value}</data>"#)?;
+        }
+        writeln!(out, r#"      <data key="edge_graphics">"#)?;
+        writeln!(
+            out,
+            "        <y:PolyLineEdge><y:EdgeLabel>{label}</y:EdgeLabel></y:PolyLineEdge>"
+        )?;
+        writeln!(out, "      </data>")?;
+        writeln!(out, "    </edge>")?;
+    }
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
+
+/// `declared` followed by string keys for any other names payloads write.
+fn declared_keys<'a, P: GraphMlPayload + 'a>(
+    mut declared: Vec<GraphMlKey>,
+    payloads: impl Iterator<Item = &'a P>,
+) -> Vec<GraphMlKey> {
+    for p in payloads {
+        for (name, _) in p.to_data() {
+            if !declared.iter().any(|k| k.name == name) {
+                declared.push(GraphMlKey::new(name, GraphMlType::String));
+            }
+        }
+    }
+    declared
+}
+
+fn write_key(
+    out: &mut String,
+    id: &str,
+    domain: &str,
+    name: &str,
+    ty: GraphMlType,
+) -> std::fmt::Result {
+    writeln!(
+        out,
+        r#"  <key id="{}" for="{domain}" attr.name="{}" attr.type="{}"/>"#,
+        escape_xml(id),
+        escape_xml(name),
+        ty.as_str()
+    )
+}
+
+/// Declared `<key>`: name the value is stored under and optional default.
+struct KeyDef {
+    domain: String,
+    name: String,
+    default: Option<String>,
+}
+
+/// Visual attributes read from either Gephi keys or yEd graphics.
+#[derive(Default)]
+struct Visuals {
+    label: Option<String>,
+    location: Option<Pos2>,
+    color: Option<Color32>,
+}
+
+fn find_graph<'a, 'input>(
+    doc: &'a roxmltree::Document<'input>,
+) -> Result<roxmltree::Node<'a, 'input>, ImportError> {
+    let root = doc.root_element();
+    if !is_graphml(root, "graphml") {
+        return Err(ImportError::Schema(format!(
+            "expected <graphml> root, found <{}>",
+            root.tag_name().name()
+        )));
+    }
+    root.children()
+        .find(|n| is_graphml(*n, "graph"))
+        .ok_or_else(|| ImportError::Schema("document has no <graph>".to_string()))
+}
+
+fn read_graph<N, E, Ty>(
+    doc: &roxmltree::Document,
+    graph: roxmltree::Node,
+) -> Result<Graph<N, E, Ty, DefaultIx>, ImportError>
+where
+    N: GraphMlPayload + Clone,
+    E: GraphMlPayload + Clone,
+    Ty: EdgeType,
+{
+    let keys: HashMap<&str, KeyDef> = doc
+        .root_element()
+        .children()
+        .filter(|n| is_graphml(*n, "key"))
+        .filter_map(|k| {
+            let id = k.attribute("id")?;
+            let def = KeyDef {
+                domain: k.attribute("for").unwrap_or("all").to_string(),
+                name: k.attribute("attr.name").unwrap_or(id).to_string(),
+                default: k
+                    .children()
+                    .find(|n| is_graphml(*n, "default"))
+                    .map(|d| d.text().unwrap_or_default().to_string()),
+            };
+            Some((id, def))
+        })
+        .collect();
+
+    let mut g: Graph<N, E, Ty, DefaultIx> = Graph::from(&StableGraph::default());
+    let mut ids: HashMap<&str, NodeIndex<DefaultIx>> = HashMap::new();
+
+    for node in graph.descendants().filter(|n| is_graphml(*n, "node")) {
+        let id = node
+            .attribute("id")
+            .ok_or_else(|| ImportError::Schema("<node> without id".to_string()))?;
+        let (data, visuals) = read_data(node, &keys, "node");
+        let ports: Vec<Port> = node
+            .children()
+            .filter(|n| is_graphml(*n, "port"))
+            .filter_map(|p| read_port(p, &keys))
+            .collect();
+
+        let idx = g.add_node_custom(N::from_data(&data), |n| {
+            default_node_transform(n);
+            if let Some(label) = visuals.label {
+                n.set_label(label);
+            }
+            if let Some(loc) = visuals.location {
+                n.set_location(loc);
+            }
+            if let Some(color) = visuals.color {
+                n.set_color(color);
+            }
+            n.set_ports(ports);
+        });
+        ids.insert(id, idx);
+    }
+
+    for edge in graph.descendants().filter(|n| is_graphml(*n, "edge")) {
+        let endpoint = |attr: &str| {
+            let id = edge
+                .attribute(attr)
+                .ok_or_else(|| ImportError::Schema(format!("<edge> without {attr}")))?;
+            ids.get(id)
+                .copied()
+                .ok_or_else(|| ImportError::Schema(format!("edge references unknown node {id}")))
+        };
+        let (start, end) = (endpoint("source")?, endpoint("target")?);
+        let (data, visuals) = read_data(edge, &keys, "edge");
+        let source_port = edge.attribute("sourceport").map(str::to_string);
+        let target_port = edge.attribute("targetport").map(str::to_string);
+
+        g.add_edge_custom(start, end, E::from_data(&data), |e| {
+            default_edge_transform(e);
+            if let Some(label) = visuals.label {
+                e.set_label(label);
+            }
+            e.set_source_port(source_port);
+            e.set_target_port(target_port);
+        });
+    }
+
+    Ok(g)
+}
+
+fn read_data(
+    el: roxmltree::Node,
+    keys: &HashMap<&str, KeyDef>,
+    domain: &str,
+) -> (GraphMlData, Visuals) {
+    // Values of the visual keys are kept apart so payload attributes of the same name do not
+    // shadow them and are not shadowed by them.
+    let (mut data, mut visual) = (GraphMlData::new(), GraphMlData::new());
+    for (id, k) in keys {
+        let Some(default) = k.default.clone() else {
+            continue;
+        };
+        if k.domain == domain || k.domain == "all" {
+            let target = if VISUAL_KEYS.contains(id) {
+                &mut visual
+            } else {
+                &mut data
+            };
+            target.insert(k.name.clone(), default);
+        }
+    }
+    let mut graphics = Visuals::default();
+
+    for d in el.children().filter(|n| is_graphml(*n, "data")) {
+        if d.children().any(|n| n.is_element()) {
+            read_yed_graphics(d, &mut graphics);
+            continue;
+        }
+        let Some(key) = d.attribute("key") else {
+            continue;
+        };
+        let name = keys.get(key).map_or(key, |k| k.name.as_str());
+        let target = if VISUAL_KEYS.contains(&key) {
+            &mut visual
+        } else {
+            &mut data
+        };
+        target.insert(name.to_string(), d.text().unwrap_or_default().to_string());
+    }
+
+    // Documents of other tools may declare visuals under keys of their own ids.
+    let value = |name: &str| visual.get(name).or_else(|| data.get(name));
+    let num = |name: &str| value(name).and_then(|v| v.trim().parse::<f32>().ok());
+    let channel = |name: &str| value(name).and_then(|v| v.trim().parse::<u8>().ok());
+    let location = match (num("x"), num("y")) {
+        (Some(x), Some(y)) => Some(Pos2::new(x, y)),
+        _ => None,
+    };
+    let color = match (channel("r"), channel("g"), channel("b")) {
+        (Some(r), Some(g), Some(b)) => Some(Color32::from_rgb(r, g, b)),
+        _ => value("color").and_then(|c| color_from_hex(c)),
+    };
+    // yEd graphics win over plain attributes when a document carries both.
+    let visuals = Visuals {
+        label: graphics.label.or_else(|| value("label").cloned()),
+        location: graphics.location.or(location),
+        color: graphics.color.or(color),
+    };
+    (data, visuals)
+}
+
+/// Port declared by a `<port>` element, inputs at the node center unless its data say
+/// otherwise.
+fn read_port(port: roxmltree::Node, keys: &HashMap<&str, KeyDef>) -> Option<Port> {
+    let name = port.attribute("name")?;
+    let mut kind = PortKind::Input;
+    let mut offset = Vec2::ZERO;
+    for d in port.children().filter(|n| is_graphml(*n, "data")) {
+        let Some(key) = d.attribute("key") else {
+            continue;
+        };
+        let value = d.text().unwrap_or_default().trim();
+        match keys.get(key).map_or(key, |k| k.name.as_str()) {
+            "kind" if value == "output" => kind = PortKind::Output,
+            "x" => offset.x = value.parse().unwrap_or_default(),
+            "y" => offset.y = value.parse().unwrap_or_default(),
+            _ => {}
+        }
+    }
+    Some(Port::new(name.to_string(), kind, offset))
+}
+
+fn read_yed_graphics(data: roxmltree::Node, visuals: &mut Visuals) {
+    for n in data.descendants().filter(|n| n.is_element()) {
+        match n.tag_name().name() {
+            "Geometry" => {
+                let attr = |name| n.attribute(name).and_then(|v| v.parse::<f32>().ok());
+                if let (Some(x), Some(y)) = (attr("x"), attr("y")) {
+                    let size = Vec2::new(attr("width").unwrap_or(0.), attr("height").unwrap_or(0.));
+                    visuals.location = Some(Pos2::new(x, y) + size / 2.);
+                }
+            }
+            "Fill" => {
+                if let Some(c) = n.attribute("color").and_then(color_from_hex) {
+                    visuals.color = Some(c);
+                }
+            }
+            "NodeLabel" | "EdgeLabel" => {
+                visuals.label = Some(n.text().unwrap_or_default().trim().to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_graphml(n: roxmltree::Node, name: &str) -> bool {
+    n.is_element()
+        && n.tag_name().name() == name
+        && n.tag_name().namespace().is_none_or(|ns| ns == GRAPHML_NS)
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{Directed, Undirected};
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Weight(f64);
+
+    impl GraphMlPayload for Weight {
+        fn keys() -> Vec<GraphMlKey> {
+            vec![GraphMlKey::new("weight".to_string(), GraphMlType::Double)]
+        }
+
+        fn from_data(data: &GraphMlData) -> Self {
+            Self(
+                data.get("weight")
+                    .and_then(|w| w.parse().ok())
+                    .unwrap_or(1.),
+            )
+        }
+
+        fn to_data(&self) -> Vec<(String, String)> {
+            vec![("weight".to_string(), self.0.to_string())]
+        }
+    }
+
+    #[test]
+    fn round_trip_keeps_visuals_payloads_and_parallel_edges() {
+        let mut g: Graph<(), Weight, Directed> = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_label_and_location((), "a & b".to_string(), Pos2::new(1., 2.));
+        let b = g.add_node_with_location((), Pos2::new(-3., 4.5));
+        g.node_mut(a)
+            .unwrap()
+            .set_color(Color32::from_rgb(10, 20, 30));
+        g.node_mut(a)
+            .unwrap()
+            .add_port(Port::output("out".to_string(), Vec2::new(5., -2.5)));
+        g.node_mut(b)
+            .unwrap()
+            .add_port(Port::input("in".to_string(), Vec2::X));
+        g.add_edge_with_label(a, b, Weight(2.5), "<first>".to_string());
+        g.add_edge(a, b, Weight(1.));
+        g.add_edge(b, a, Weight(0.5));
+        g.add_edge_with_ports(a, b, Weight(3.), "out".to_string(), "in".to_string());
+
+        let text = export_graph_to_string(&g);
+        let ImportedGraph::Directed(imported) = import_graph_from_str::<(), Weight>(&text).unwrap()
+        else {
+            panic!("expected directed graph");
+        };
+
+        assert_eq!(imported.node_count(), 2);
+        assert_eq!(imported.edge_count(), 4);
+        for (idx, n) in g.nodes_iter() {
+            let m = imported.node(idx).unwrap();
+            assert_eq!(m.label(), n.label());
+            assert_eq!(m.location(), n.location());
+            assert_eq!(m.color(), n.color());
+            assert_eq!(m.ports(), n.ports());
+        }
+        for (idx, e) in g.edges_iter() {
+            let f = imported.edge(idx).unwrap();
+            assert_eq!(imported.edge_endpoints(idx), g.edge_endpoints(idx));
+            assert_eq!(f.payload(), e.payload());
+            assert_eq!(f.label(), e.label());
+            assert_eq!(f.order(), e.order());
+            assert_eq!(f.source_port(), e.source_port());
+            assert_eq!(f.target_port(), e.target_port());
+        }
+    }
+
+    /// Payload using the attribute names of the visual keys.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Shadowing {
+        x: String,
+        label: String,
+    }
+
+    impl GraphMlPayload for Shadowing {
+        fn from_data(data: &GraphMlData) -> Self {
+            let get = |name: &str| data.get(name).cloned().unwrap_or_default();
+            Self {
+                x: get("x"),
+                label: get("label"),
+            }
+        }
+
+        fn to_data(&self) -> Vec<(String, String)> {
+            vec![
+                ("x".to_string(), self.x.clone()),
+                ("label".to_string(), self.label.clone()),
+            ]
+        }
+    }
+
+    #[test]
+    fn payload_keys_named_like_visuals_stay_apart() {
+        let mut g: Graph<Shadowing, Shadowing, Directed> = Graph::from(&StableGraph::default());
+        let payload = Shadowing {
+            x: "east".to_string(),
+            label: "payload".to_string(),
+        };
+        let a = g.add_node_with_label_and_location(
+            payload.clone(),
+            "visual".to_string(),
+            Pos2::new(7., 8.),
+        );
+        let e = g.add_edge_with_label(a, a, payload.clone(), "visual".to_string());
+
+        let text = export_graph_to_string(&g);
+        let imported = import_graph_as::<Shadowing, Shadowing, Directed>(&text).unwrap();
+
+        let n = imported.node(a).unwrap();
+        assert_eq!(n.payload(), &payload);
+        assert_eq!(n.label(), "visual");
+        assert_eq!(n.location(), Pos2::new(7., 8.));
+        let f = imported.edge(e).unwrap();
+        assert_eq!(f.payload(), &payload);
+        assert_eq!(f.label(), "visual");
+    }
+
+    #[test]
+    fn reads_yed_graphics_and_key_defaults() {
+        let text = r##"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:y="http://www.yworks.com/xml/graphml">
+  <key id="d0" for="node" yfiles.type="nodegraphics"/>
+  <key id="d1" for="edge" attr.name="weight" attr.type="double"><default>4</default></key>
+  <graph edgedefault="undirected">
+    <node id="a">
+      <data key="d0">
+        <y:ShapeNode>
+          <y:Geometry x="10" y="20" width="30" height="40"/>
+          <y:Fill color="#FFCC00" transparent="false"/>
+          <y:NodeLabel>Alpha</y:NodeLabel>
+        </y:ShapeNode>
+      </data>
+    </node>
+    <node id="b"/>
+    <edge source="a" target="b"/>
+    <edge source="b" target="a"><data key="d1">7</data></edge>
+  </graph>
+</graphml>"##;
+
+        let imported = import_graph_from_str::<(), Weight>(text).unwrap();
+        let ImportedGraph::Undirected(g) = imported else {
+            panic!("expected undirected graph");
+        };
+        let (a, n) = g.nodes_iter().next().unwrap();
+        assert_eq!(n.label(), "Alpha");
+        assert_eq!(n.location(), Pos2::new(25., 40.));
+        assert_eq!(n.color(), Some(Color32::from_rgb(0xFF, 0xCC, 0x00)));
+
+        let mut weights: Vec<_> = g.edges_iter().map(|(_, e)| e.payload().0).collect();
+        weights.sort_by(f64::total_cmp);
+        assert_eq!(weights, vec![4., 7.]);
+        let orders: Vec<_> = g.edges_iter().map(|(_, e)| e.order()).collect();
+        assert_eq!(orders, vec![0, 1]);
+        assert_eq!(g.edges_directed(a, petgraph::Outgoing).count(), 2);
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert!(matches!(
+            import_graph_from_str::<(), ()>("<graphml><graph>"),
+            Err(ImportError::Syntax(_))
+        ));
+        assert!(matches!(
+            import_graph_from_str::<(), ()>("<gexf/>"),
+            Err(ImportError::Schema(_))
+        ));
+        let dangling =
+            r#"<graphml><graph><node id="a"/><edge source="a" target="b"/></graph></graphml>"#;
+        assert!(matches!(
+            import_graph_from_str::<(), ()>(dangling),
+            Err(ImportError::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn import_as_overrides_edgedefault() {
+        let text = r#"<graphml><graph edgedefault="directed"><node id="a"/><node id="b"/><edge source="a" target="b"/></graph></graphml>"#;
+        let g = import_graph_as::<(), (), Undirected>(text).unwrap();
+        assert!(!g.is_directed());
+        assert_eq!(g.edge_count(), 1);
+    }
+}
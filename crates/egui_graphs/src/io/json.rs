@@ -0,0 +1,941 @@
+//! JSON graph documents.
+//!
+//! Three forms are accepted on import:
+//! 1. Edges-only array of id pairs: `[[0,1],[1,2]]`;
+//! 2. Graph object: `{"nodes":[0,1,2], "edges":[[0,1],[1,2]], "directed":false}`;
+//! 3. Full document: `{"version":1, "graph":{...}, "layout":{"type":"hierarchical", ...}}`.
+//!
+//! Export always produces the full document form.
+
+use std::collections::{BTreeSet, HashMap};
+
+use egui::{Color32, Pos2, Rect};
+use petgraph::{
+    stable_graph::{DefaultIx, IndexType, NodeIndex, StableGraph},
+    Directed, EdgeType, Undirected,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{ImportError, ImportedGraph};
+use crate::{
+    DisplayEdge, DisplayNode, EdgeWeighting, FruchtermanReingoldWithBuiltinExtrasState, Graph,
+    LayoutHierarchicalCrossingHeuristic, LayoutHierarchicalLayering, LayoutHierarchicalMode,
+    LayoutHierarchicalOrientation, LayoutStateHierarchical, RadialMetric,
+};
+
+/// Version written to exported documents.
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// Graph part of a [`JsonDocument`]. Nodes are referenced by arbitrary integer ids.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JsonGraph {
+    #[serde(default)]
+    pub nodes: Vec<i64>,
+    /// Pairs of node ids. Ids missing from `nodes` are added on import.
+    #[serde(default)]
+    pub edges: Vec<(i64, i64)>,
+    /// Defaults to directed when missing.
+    #[serde(default)]
+    pub directed: Option<bool>,
+    /// Node locations as `(id, x, y)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positions: Option<Vec<(i64, f32, f32)>>,
+    /// Node labels as `(id, label)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<(i64, String)>>,
+    /// Node colors as `(id, [r, g, b, a])` in unmultiplied sRGBA.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colors: Option<Vec<(i64, [u8; 4])>>,
+    /// Edge labels as `(position in edges, label)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_labels: Option<Vec<(usize, String)>>,
+}
+
+impl JsonGraph {
+    /// Captures topology, locations, labels and colors of the graph. Node ids are node indices.
+    /// Payloads are not exported.
+    pub fn from_graph<N, E, Ty, Ix, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let mut res = Self {
+            directed: Some(g.is_directed()),
+            ..Self::default()
+        };
+
+        let (mut positions, mut labels, mut colors) = (Vec::new(), Vec::new(), Vec::new());
+        for (idx, n) in g.nodes_iter() {
+            let id = node_id(idx);
+            res.nodes.push(id);
+            positions.push((id, n.location().x, n.location().y));
+            labels.push((id, n.label()));
+            if let Some(c) = n.color() {
+                colors.push((id, c.to_srgba_unmultiplied()));
+            }
+        }
+
+        let mut edge_labels = Vec::new();
+        for (idx, e) in g.edges_iter() {
+            let Some((start, end)) = g.edge_endpoints(idx) else {
+                continue;
+            };
+            // Undirected edges are written with the smaller id first.
+            let (start, end) = if g.is_directed() || start <= end {
+                (start, end)
+            } else {
+                (end, start)
+            };
+            edge_labels.push((res.edges.len(), e.label()));
+            res.edges.push((node_id(start), node_id(end)));
+        }
+
+        res.positions = Some(positions);
+        res.labels = Some(labels);
+        res.colors = (!colors.is_empty()).then_some(colors);
+        res.edge_labels = Some(edge_labels);
+        res
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed.unwrap_or(true)
+    }
+
+    /// Builds a graph with the given directedness ignoring [`JsonGraph::directed`]. Nodes
+    /// without a position keep the default location. Self-loops are skipped, parallel edges
+    /// are kept.
+    pub fn to_graph<Ty: EdgeType>(&self) -> Graph<(), (), Ty, DefaultIx> {
+        let mut g: Graph<(), (), Ty, DefaultIx> = Graph::from(&StableGraph::default());
+        let mut ids: HashMap<i64, NodeIndex<DefaultIx>> = HashMap::new();
+        let mut node = |g: &mut Graph<(), (), Ty, DefaultIx>, id: i64| {
+            *ids.entry(id).or_insert_with(|| g.add_node(()))
+        };
+
+        for id in &self.nodes {
+            node(&mut g, *id);
+        }
+        let mut edges = Vec::with_capacity(self.edges.len());
+        for (a, b) in &self.edges {
+            let (start, end) = (node(&mut g, *a), node(&mut g, *b));
+            edges.push((start != end).then(|| g.add_edge(start, end, ())));
+        }
+
+        for (id, x, y) in self.positions.iter().flatten() {
+            if let Some(idx) = ids.get(id) {
+                g.set_node_location(*idx, Pos2::new(*x, *y));
+            }
+        }
+        for (id, label) in self.labels.iter().flatten() {
+            if let Some(n) = ids.get(id).and_then(|idx| g.node_mut(*idx)) {
+                n.set_label(label.clone());
+            }
+        }
+        for (id, [r, g_, b, a]) in self.colors.iter().flatten() {
+            if let Some(n) = ids.get(id).and_then(|idx| g.node_mut(*idx)) {
+                n.set_color(Color32::from_rgba_unmultiplied(*r, *g_, *b, *a));
+            }
+        }
+        for (pos, label) in self.edge_labels.iter().flatten() {
+            if let Some(e) = edges
+                .get(*pos)
+                .copied()
+                .flatten()
+                .and_then(|idx| g.edge_mut(idx))
+            {
+                e.set_label(label.clone());
+            }
+        }
+
+        g
+    }
+
+    /// Builds a directed or undirected graph according to [`JsonGraph::is_directed`].
+    pub fn to_imported_graph(&self) -> ImportedGraph {
+        if self.is_directed() {
+            ImportedGraph::Directed(self.to_graph::<Directed>())
+        } else {
+            ImportedGraph::Undirected(self.to_graph::<Undirected>())
+        }
+    }
+}
+
+/// Top level JSON document: optional graph and optional layout.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JsonDocument {
+    #[serde(default)]
+    pub version: Option<u32>,
+    #[serde(default)]
+    pub graph: Option<JsonGraph>,
+    #[serde(default)]
+    pub layout: Option<LayoutSpec>,
+}
+
+impl JsonDocument {
+    pub fn new(graph: Option<JsonGraph>, layout: Option<LayoutSpec>) -> Self {
+        Self {
+            version: Some(JSON_FORMAT_VERSION),
+            graph,
+            layout,
+        }
+    }
+
+    /// Parses any of the accepted forms, see the [module docs](self).
+    ///
+    /// # Errors
+    ///
+    /// [`ImportError::Syntax`] for malformed JSON, [`ImportError::Schema`] when the JSON does
+    /// not match any of the accepted forms.
+    pub fn parse(text: &str) -> Result<Self, ImportError> {
+        let v: Value = serde_json::from_str(text)
+            .map_err(|e| ImportError::Syntax(format!("invalid json: {e}")))?;
+        let schema = |e: serde_json::Error| ImportError::Schema(e.to_string());
+        match v {
+            Value::Array(items) => {
+                let edges = items
+                    .iter()
+                    .map(edge_from_value)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let nodes: BTreeSet<i64> = edges.iter().flat_map(|(a, b)| [*a, *b]).collect();
+                let graph = JsonGraph {
+                    nodes: nodes.into_iter().collect(),
+                    edges,
+                    directed: Some(true),
+                    ..JsonGraph::default()
+                };
+                Ok(Self::new(Some(graph), None))
+            }
+            Value::Object(ref map) if map.contains_key("graph") || map.contains_key("layout") => {
+                serde_json::from_value(v).map_err(schema)
+            }
+            Value::Object(_) => {
+                let graph: JsonGraph = serde_json::from_value(v).map_err(schema)?;
+                Ok(Self::new(Some(graph), None))
+            }
+            _ => Err(ImportError::Schema(
+                "expected an array of edges or an object".to_string(),
+            )),
+        }
+    }
+
+    pub fn to_string_pretty(&self) -> String {
+        // The document has no maps with non-string keys so serialization cannot fail.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Result of [`import_graph_from_str`].
+#[derive(Debug, Clone)]
+pub struct JsonImport {
+    /// Graph of the document, empty directed graph if the document has only a layout.
+    pub g: ImportedGraph,
+    pub layout: Option<LayoutSpec>,
+    /// Whether the document provided node positions.
+    pub positions_applied: bool,
+}
+
+/// Parses a JSON document and builds the graph it describes.
+///
+/// # Errors
+///
+/// See [`JsonDocument::parse`].
+pub fn import_graph_from_str(text: &str) -> Result<JsonImport, ImportError> {
+    let doc = JsonDocument::parse(text)?;
+    let graph = doc.graph.unwrap_or_default();
+    Ok(JsonImport {
+        g: graph.to_imported_graph(),
+        layout: doc.layout,
+        positions_applied: graph.positions.is_some(),
+    })
+}
+
+/// Serializes the graph and optionally a layout into a pretty printed JSON document.
+pub fn export_graph_to_string<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    layout: Option<LayoutSpec>,
+) -> String
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    JsonDocument::new(Some(JsonGraph::from_graph(g)), layout).to_string_pretty()
+}
+
+fn edge_from_value(v: &Value) -> Result<(i64, i64), ImportError> {
+    let schema = |msg: &str| ImportError::Schema(msg.to_string());
+    let pair = v.as_array().ok_or_else(|| schema("edge must be [a,b]"))?;
+    let [a, b] = pair.as_slice() else {
+        return Err(schema("edge must have 2 items"));
+    };
+    let endpoint = |v: &Value| {
+        v.as_i64()
+            .ok_or_else(|| schema("edge endpoints must be integers"))
+    };
+    Ok((endpoint(a)?, endpoint(b)?))
+}
+
+fn node_id<Ix: IndexType>(idx: NodeIndex<Ix>) -> i64 {
+    i64::try_from(idx.index()).unwrap_or(i64::MAX)
+}
+
+/// Serializable layout settings. Missing values keep the layout defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LayoutSpec {
+    FruchtermanReingold {
+        #[serde(default)]
+        running: Option<bool>,
+        #[serde(default)]
+        dt: Option<f32>,
+        #[serde(default)]
+        epsilon: Option<f32>,
+        #[serde(default)]
+        damping: Option<f32>,
+        #[serde(default)]
+        max_step: Option<f32>,
+        #[serde(default)]
+        k_scale: Option<f32>,
+        #[serde(default)]
+        c_attract: Option<f32>,
+        #[serde(default)]
+        c_repulse: Option<f32>,
+        #[serde(default)]
+        barnes_hut: Option<bool>,
+        #[serde(default)]
+        theta: Option<f32>,
+        #[serde(default)]
+        edge_weighting: Option<EdgeWeightingSpec>,
+        #[serde(default)]
+        extras: Option<Vec<ExtrasSpec>>,
+    },
+    Hierarchical {
+        #[serde(default)]
+        row_dist: Option<f32>,
+        #[serde(default)]
+        col_dist: Option<f32>,
+        #[serde(default)]
+        center_parent: Option<bool>,
+        #[serde(default)]
+        orientation: Option<HierarchicalOrientationSpec>,
+        #[serde(default)]
+        mode: Option<HierarchicalModeSpec>,
+        #[serde(default)]
+        layering: Option<HierarchicalLayeringSpec>,
+        #[serde(default)]
+        crossing_heuristic: Option<HierarchicalCrossingHeuristicSpec>,
+        #[serde(default)]
+        crossing_sweeps: Option<usize>,
+        #[serde(default)]
+        fit_node_sizes: Option<bool>,
+        #[serde(default)]
+        node_margin: Option<f32>,
+    },
+}
+
+/// Serializable force-directed extras, see [`LayoutSpec::FruchtermanReingold`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExtrasSpec {
+    CenterGravity {
+        enabled: Option<bool>,
+        c: Option<f32>,
+    },
+    Collision {
+        enabled: Option<bool>,
+        padding: Option<f32>,
+        strength: Option<f32>,
+    },
+    BoundingBox {
+        enabled: Option<bool>,
+        /// Missing keeps nodes inside the viewport.
+        bounds: Option<Rect>,
+        margin: Option<f32>,
+        strength: Option<f32>,
+    },
+    RadialConstraint {
+        enabled: Option<bool>,
+        /// Missing uses the viewport center.
+        center: Option<Pos2>,
+        radius: Option<f32>,
+        strength: Option<f32>,
+        metric: Option<RadialMetricSpec>,
+    },
+    ClusterAttraction {
+        enabled: Option<bool>,
+        attraction: Option<f32>,
+        repulsion: Option<f32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RadialMetricSpec {
+    Uniform,
+    Degree,
+    /// `[id, share]` pairs keyed by node index, see [`RadialMetric::Custom`].
+    Custom(Vec<(usize, f32)>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EdgeWeightingSpec {
+    Ignore,
+    Strength,
+    Length,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HierarchicalOrientationSpec {
+    TopDown,
+    LeftRight,
+    BottomUp,
+    RightLeft,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HierarchicalModeSpec {
+    Dfs,
+    Tidy,
+    Sugiyama,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HierarchicalLayeringSpec {
+    LongestPath,
+    NetworkSimplex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HierarchicalCrossingHeuristicSpec {
+    Barycenter,
+    Median,
+}
+
+impl From<&FruchtermanReingoldWithBuiltinExtrasState> for LayoutSpec {
+    fn from(st: &FruchtermanReingoldWithBuiltinExtrasState) -> Self {
+        let (center_gravity, collision, bounding_box, radial, cluster) = (
+            st.center_gravity(),
+            st.collision(),
+            st.bounding_box(),
+            st.radial_constraint(),
+            st.cluster_attraction(),
+        );
+        LayoutSpec::FruchtermanReingold {
+            running: Some(st.base.is_running),
+            dt: Some(st.base.dt),
+            epsilon: Some(st.base.epsilon),
+            damping: Some(st.base.damping),
+            max_step: Some(st.base.max_step),
+            k_scale: Some(st.base.k_scale),
+            c_attract: Some(st.base.c_attract),
+            c_repulse: Some(st.base.c_repulse),
+            barnes_hut: Some(st.base.barnes_hut),
+            theta: Some(st.base.theta),
+            edge_weighting: Some(match st.base.edge_weighting {
+                EdgeWeighting::Ignore => EdgeWeightingSpec::Ignore,
+                EdgeWeighting::Strength => EdgeWeightingSpec::Strength,
+                EdgeWeighting::Length => EdgeWeightingSpec::Length,
+            }),
+            extras: Some(vec![
+                ExtrasSpec::CenterGravity {
+                    enabled: Some(center_gravity.enabled),
+                    c: Some(center_gravity.params.c),
+                },
+                ExtrasSpec::Collision {
+                    enabled: Some(collision.enabled),
+                    padding: Some(collision.params.padding),
+                    strength: Some(collision.params.strength),
+                },
+                ExtrasSpec::BoundingBox {
+                    enabled: Some(bounding_box.enabled),
+                    bounds: bounding_box.params.bounds,
+                    margin: Some(bounding_box.params.margin),
+                    strength: Some(bounding_box.params.strength),
+                },
+                ExtrasSpec::RadialConstraint {
+                    enabled: Some(radial.enabled),
+                    center: radial.params.center,
+                    radius: Some(radial.params.radius),
+                    strength: Some(radial.params.strength),
+                    metric: Some(match &radial.params.metric {
+                        RadialMetric::Uniform => RadialMetricSpec::Uniform,
+                        RadialMetric::Degree => RadialMetricSpec::Degree,
+                        RadialMetric::Custom(shares) => {
+                            let mut shares: Vec<_> =
+                                shares.iter().map(|(&id, &s)| (id, s)).collect();
+                            shares.sort_unstable_by_key(|(id, _)| *id);
+                            RadialMetricSpec::Custom(shares)
+                        }
+                    }),
+                },
+                ExtrasSpec::ClusterAttraction {
+                    enabled: Some(cluster.enabled),
+                    attraction: Some(cluster.params.attraction),
+                    repulsion: Some(cluster.params.repulsion),
+                },
+            ]),
+        }
+    }
+}
+
+impl From<&LayoutStateHierarchical> for LayoutSpec {
+    fn from(st: &LayoutStateHierarchical) -> Self {
+        LayoutSpec::Hierarchical {
+            row_dist: Some(st.row_dist),
+            col_dist: Some(st.col_dist),
+            center_parent: Some(st.center_parent),
+            orientation: Some(match st.orientation {
+                LayoutHierarchicalOrientation::TopDown => HierarchicalOrientationSpec::TopDown,
+                LayoutHierarchicalOrientation::LeftRight => HierarchicalOrientationSpec::LeftRight,
+                LayoutHierarchicalOrientation::BottomUp => HierarchicalOrientationSpec::BottomUp,
+                LayoutHierarchicalOrientation::RightLeft => HierarchicalOrientationSpec::RightLeft,
+            }),
+            mode: Some(match st.mode {
+                LayoutHierarchicalMode::Dfs => HierarchicalModeSpec::Dfs,
+                LayoutHierarchicalMode::Tidy => HierarchicalModeSpec::Tidy,
+                LayoutHierarchicalMode::Sugiyama => HierarchicalModeSpec::Sugiyama,
+            }),
+            layering: Some(match st.layering {
+                LayoutHierarchicalLayering::LongestPath => HierarchicalLayeringSpec::LongestPath,
+                LayoutHierarchicalLayering::NetworkSimplex => {
+                    HierarchicalLayeringSpec::NetworkSimplex
+                }
+            }),
+            crossing_heuristic: Some(match st.crossing_heuristic {
+                LayoutHierarchicalCrossingHeuristic::Barycenter => {
+                    HierarchicalCrossingHeuristicSpec::Barycenter
+                }
+                LayoutHierarchicalCrossingHeuristic::Median => {
+                    HierarchicalCrossingHeuristicSpec::Median
+                }
+            }),
+            crossing_sweeps: Some(st.crossing_sweeps),
+            fit_node_sizes: Some(st.fit_node_sizes),
+            node_margin: Some(st.node_margin),
+        }
+    }
+}
+
+impl LayoutSpec {
+    /// Force-directed state with the spec applied over defaults. None for other layouts.
+    pub fn fruchterman_reingold_state(&self) -> Option<FruchtermanReingoldWithBuiltinExtrasState> {
+        let LayoutSpec::FruchtermanReingold {
+            running,
+            dt,
+            epsilon,
+            damping,
+            max_step,
+            k_scale,
+            c_attract,
+            c_repulse,
+            barnes_hut,
+            theta,
+            edge_weighting,
+            extras,
+        } = self
+        else {
+            return None;
+        };
+
+        let mut st = FruchtermanReingoldWithBuiltinExtrasState::default();
+        let base = &mut st.base;
+        set_if_some(&mut base.is_running, *running);
+        set_if_some(&mut base.dt, *dt);
+        set_if_some(&mut base.epsilon, *epsilon);
+        set_if_some(&mut base.damping, *damping);
+        set_if_some(&mut base.max_step, *max_step);
+        set_if_some(&mut base.k_scale, *k_scale);
+        set_if_some(&mut base.c_attract, *c_attract);
+        set_if_some(&mut base.c_repulse, *c_repulse);
+        set_if_some(&mut base.barnes_hut, *barnes_hut);
+        set_if_some(&mut base.theta, *theta);
+        set_if_some(
+            &mut base.edge_weighting,
+            edge_weighting.map(|w| match w {
+                EdgeWeightingSpec::Ignore => EdgeWeighting::Ignore,
+                EdgeWeightingSpec::Strength => EdgeWeighting::Strength,
+                EdgeWeightingSpec::Length => EdgeWeighting::Length,
+            }),
+        );
+        for extra in extras.iter().flatten() {
+            match extra {
+                ExtrasSpec::CenterGravity { enabled, c } => {
+                    set_if_some(&mut st.center_gravity_mut().enabled, *enabled);
+                    set_if_some(&mut st.center_gravity_mut().params.c, *c);
+                }
+                ExtrasSpec::Collision {
+                    enabled,
+                    padding,
+                    strength,
+                } => {
+                    let collision = st.collision_mut();
+                    set_if_some(&mut collision.enabled, *enabled);
+                    set_if_some(&mut collision.params.padding, *padding);
+                    set_if_some(&mut collision.params.strength, *strength);
+                }
+                ExtrasSpec::BoundingBox {
+                    enabled,
+                    bounds,
+                    margin,
+                    strength,
+                } => {
+                    let bounding_box = st.bounding_box_mut();
+                    set_if_some(&mut bounding_box.enabled, *enabled);
+                    set_if_some(&mut bounding_box.params.bounds, bounds.map(Some));
+                    set_if_some(&mut bounding_box.params.margin, *margin);
+                    set_if_some(&mut bounding_box.params.strength, *strength);
+                }
+                ExtrasSpec::RadialConstraint {
+                    enabled,
+                    center,
+                    radius,
+                    strength,
+                    metric,
+                } => {
+                    let radial = st.radial_constraint_mut();
+                    set_if_some(&mut radial.enabled, *enabled);
+                    set_if_some(&mut radial.params.center, center.map(Some));
+                    set_if_some(&mut radial.params.radius, *radius);
+                    set_if_some(&mut radial.params.strength, *strength);
+                    set_if_some(
+                        &mut radial.params.metric,
+                        metric.as_ref().map(|m| match m {
+                            RadialMetricSpec::Uniform => RadialMetric::Uniform,
+                            RadialMetricSpec::Degree => RadialMetric::Degree,
+                            RadialMetricSpec::Custom(shares) => {
+                                RadialMetric::Custom(shares.iter().copied().collect())
+                            }
+                        }),
+                    );
+                }
+                ExtrasSpec::ClusterAttraction {
+                    enabled,
+                    attraction,
+                    repulsion,
+                } => {
+                    let cluster = st.cluster_attraction_mut();
+                    set_if_some(&mut cluster.enabled, *enabled);
+                    set_if_some(&mut cluster.params.attraction, *attraction);
+                    set_if_some(&mut cluster.params.repulsion, *repulsion);
+                }
+            }
+        }
+        Some(st)
+    }
+
+    /// Hierarchical state with the spec applied over defaults. None for other layouts.
+    ///
+    /// The returned state is not triggered so the layout runs once it is applied.
+    pub fn hierarchical_state(&self) -> Option<LayoutStateHierarchical> {
+        let LayoutSpec::Hierarchical {
+            row_dist,
+            col_dist,
+            center_parent,
+            orientation,
+            mode,
+            layering,
+            crossing_heuristic,
+            crossing_sweeps,
+            fit_node_sizes,
+            node_margin,
+        } = self
+        else {
+            return None;
+        };
+
+        let mut st = LayoutStateHierarchical::default();
+        set_if_some(&mut st.row_dist, *row_dist);
+        set_if_some(&mut st.col_dist, *col_dist);
+        set_if_some(&mut st.center_parent, *center_parent);
+        set_if_some(&mut st.crossing_sweeps, *crossing_sweeps);
+        set_if_some(&mut st.fit_node_sizes, *fit_node_sizes);
+        set_if_some(&mut st.node_margin, *node_margin);
+        if let Some(o) = orientation {
+            st.orientation = match o {
+                HierarchicalOrientationSpec::TopDown => LayoutHierarchicalOrientation::TopDown,
+                HierarchicalOrientationSpec::LeftRight => LayoutHierarchicalOrientation::LeftRight,
+                HierarchicalOrientationSpec::BottomUp => LayoutHierarchicalOrientation::BottomUp,
+                HierarchicalOrientationSpec::RightLeft => LayoutHierarchicalOrientation::RightLeft,
+            };
+        }
+        if let Some(m) = mode {
+            st.mode = match m {
+                HierarchicalModeSpec::Dfs => LayoutHierarchicalMode::Dfs,
+                HierarchicalModeSpec::Tidy => LayoutHierarchicalMode::Tidy,
+                HierarchicalModeSpec::Sugiyama => LayoutHierarchicalMode::Sugiyama,
+            };
+        }
+        if let Some(l) = layering {
+            st.layering = match l {
+                HierarchicalLayeringSpec::LongestPath => LayoutHierarchicalLayering::LongestPath,
+                HierarchicalLayeringSpec::NetworkSimplex => {
+                    LayoutHierarchicalLayering::NetworkSimplex
+                }
+            };
+        }
+        if let Some(h) = crossing_heuristic {
+            st.crossing_heuristic = match h {
+                HierarchicalCrossingHeuristicSpec::Barycenter => {
+                    LayoutHierarchicalCrossingHeuristic::Barycenter
+                }
+                HierarchicalCrossingHeuristicSpec::Median => {
+                    LayoutHierarchicalCrossingHeuristic::Median
+                }
+            };
+        }
+        st.triggered = false;
+        Some(st)
+    }
+}
+
+fn set_if_some<T>(dst: &mut T, v: Option<T>) {
+    if let Some(v) = v {
+        *dst = v;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_edges_array_valid() {
+        let s = "[[0,1],[1,2],[2,0]]";
+        let r = import_graph_from_str(s).expect("should import");
+        match r.g {
+            ImportedGraph::Directed(g) => {
+                assert_eq!(g.node_count(), 3);
+                assert_eq!(g.edge_count(), 3);
+            }
+            _ => panic!("expected directed graph"),
+        }
+    }
+
+    #[test]
+    fn import_non_int_endpoint() {
+        let err = import_graph_from_str("[[\"a\",1]]").unwrap_err();
+        assert!(err.to_string().contains("integers"));
+    }
+
+    #[test]
+    fn import_object_undirected() {
+        let s = r#"{"nodes":[0,1,2],"edges":[[0,1],[1,2],[2,0]],"directed":false}"#;
+        let r = import_graph_from_str(s).expect("should import");
+        match r.g {
+            ImportedGraph::Undirected(g) => {
+                assert_eq!(g.node_count(), 3);
+                // Three undirected edges
+                assert_eq!(g.edge_count(), 3);
+            }
+            _ => panic!("expected undirected graph"),
+        }
+    }
+
+    #[test]
+    fn import_undirected_deduplicates_pairs() {
+        let s = r#"{"edges":[[0,1],[1,0],[0,1],[2,2]],"directed":false}"#;
+        let r = import_graph_from_str(s).expect("should import");
+        match r.g {
+            ImportedGraph::Undirected(g) => {
+                // Duplicates are preserved and self-loops are ignored
+                assert_eq!(g.node_count(), 3);
+                assert_eq!(g.edge_count(), 3);
+            }
+            _ => panic!("expected undirected graph"),
+        }
+    }
+
+    #[test]
+    fn applies_positions_by_id() {
+        // Build a spec with explicit node ids and positions referencing those ids
+        let s = r#"{
+            "version": 1,
+            "graph": {
+                "nodes": [10, 20],
+                "edges": [[10,20]],
+                "directed": true,
+                "positions": [[10, -3.5, 7.25], [20, 11.0, -2.0]]
+            }
+        }"#;
+        let r = import_graph_from_str(s).expect("should import with positions");
+        match r.g {
+            ImportedGraph::Directed(g) => {
+                assert_eq!(g.node_count(), 2);
+                // The nodes may not be in id order; check actual coordinates set
+                let mut coords: Vec<(i32, i32)> = Vec::new();
+                for idx in g.g().node_indices() {
+                    let p = g.g().node_weight(idx).unwrap().location();
+                    // Convert to ints to avoid float exactness issues
+                    coords.push((p.x.round() as i32, p.y.round() as i32));
+                }
+                coords.sort();
+                // Expect approximately the two points (-4,7) and (11,-2) after rounding
+                assert_eq!(coords, vec![(-4, 7), (11, -2)]);
+            }
+            _ => panic!("expected directed graph"),
+        }
+        assert!(r.positions_applied);
+    }
+
+    #[test]
+    fn import_errors() {
+        let err = import_graph_from_str("not-json").unwrap_err();
+        assert!(matches!(err, ImportError::Syntax(_)));
+        assert!(err.to_string().contains("invalid json"));
+        assert!(matches!(
+            import_graph_from_str(r#"[["a",1]]"#),
+            Err(ImportError::Schema(_))
+        ));
+        assert!(matches!(
+            import_graph_from_str("[[0,1,2]]"),
+            Err(ImportError::Schema(_))
+        ));
+        assert!(matches!(
+            import_graph_from_str("42"),
+            Err(ImportError::Schema(_))
+        ));
+    }
+
+    #[test]
+    fn graph_and_layout_round_trip() {
+        let mut g: Graph<(), (), Directed> = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_label_and_location((), "a".to_string(), Pos2::new(1., 2.));
+        let b = g.add_node_with_location((), Pos2::new(-3., 4.));
+        g.node_mut(b)
+            .unwrap()
+            .set_color(Color32::from_rgb(200, 10, 30));
+        g.add_edge_with_label(a, b, (), "a-b".to_string());
+        g.add_edge(b, a, ());
+
+        let hier = LayoutStateHierarchical {
+            col_dist: 80.,
+            orientation: LayoutHierarchicalOrientation::LeftRight,
+            mode: LayoutHierarchicalMode::Sugiyama,
+            crossing_sweeps: 7,
+            ..LayoutStateHierarchical::default()
+        };
+        let text = export_graph_to_string(&g, Some(LayoutSpec::from(&hier)));
+
+        let r = import_graph_from_str(&text).unwrap();
+        let ImportedGraph::Directed(imported) = r.g else {
+            panic!("expected directed graph");
+        };
+        assert_eq!(JsonGraph::from_graph(&imported), JsonGraph::from_graph(&g));
+        assert_eq!(
+            imported.node(b).unwrap().color(),
+            g.node(b).unwrap().color()
+        );
+
+        let st = r.layout.unwrap().hierarchical_state().unwrap();
+        assert_eq!(st.col_dist, 80.);
+        assert_eq!(st.orientation, LayoutHierarchicalOrientation::LeftRight);
+        assert_eq!(st.mode, LayoutHierarchicalMode::Sugiyama);
+        assert_eq!(st.crossing_sweeps, 7);
+    }
+
+    #[test]
+    fn layout_spec_keeps_defaults_for_missing_values() {
+        let doc = JsonDocument::parse(
+            r#"{"layout":{"type":"fruchtermanReingold","dt":0.5,
+                "extras":[{"type":"centerGravity","c":0.7}]}}"#,
+        )
+        .unwrap();
+        assert!(doc.graph.is_none());
+        let layout = doc.layout.unwrap();
+        assert!(layout.hierarchical_state().is_none());
+
+        let st = layout.fruchterman_reingold_state().unwrap();
+        let defaults = FruchtermanReingoldWithBuiltinExtrasState::default();
+        assert_eq!(st.base.dt, 0.5);
+        assert_eq!(st.base.k_scale, defaults.base.k_scale);
+        assert_eq!(st.center_gravity().params.c, 0.7);
+        assert_eq!(
+            st.center_gravity().enabled,
+            defaults.center_gravity().enabled
+        );
+        assert!(!st.collision().enabled);
+    }
+
+    #[test]
+    fn force_directed_spec_roundtrips() {
+        let doc = JsonDocument::parse(
+            r#"{"layout":{"type":"fruchtermanReingold","extras":[
+                {"type":"collision","enabled":true,"padding":12.0},
+                {"type":"clusterAttraction","enabled":true,"repulsion":2.0}]}}"#,
+        )
+        .unwrap();
+        let st = doc.layout.unwrap().fruchterman_reingold_state().unwrap();
+        let collision = st.collision();
+        assert!(collision.enabled);
+        assert_eq!(collision.params.padding, 12.);
+        assert_eq!(collision.params.strength, 1.);
+        let cluster = st.cluster_attraction();
+        assert!(cluster.enabled);
+        assert_eq!(cluster.params.repulsion, 2.);
+        assert_eq!(cluster.params.attraction, 0.5);
+
+        let mut st = st;
+        st.base.edge_weighting = EdgeWeighting::Strength;
+        let back = LayoutSpec::from(&st).fruchterman_reingold_state().unwrap();
+        assert_eq!(back.base.edge_weighting, EdgeWeighting::Strength);
+        assert!(back.collision().enabled);
+        assert_eq!(back.collision().params.padding, 12.);
+        assert_eq!(back.cluster_attraction().params.repulsion, 2.);
+    }
+
+    #[test]
+    fn constraint_extras_from_spec() {
+        let doc = JsonDocument::parse(
+            r#"{"layout":{"type":"fruchtermanReingold","extras":[
+                {"type":"boundingBox","enabled":true,
+                 "bounds":{"min":{"x":0,"y":0},"max":{"x":400,"y":300}}},
+                {"type":"radialConstraint","radius":150,"metric":"degree"}]}}"#,
+        )
+        .unwrap();
+        let st = doc.layout.unwrap().fruchterman_reingold_state().unwrap();
+        let (bounding_box, radial) = (st.bounding_box(), st.radial_constraint());
+        assert!(bounding_box.enabled);
+        assert_eq!(
+            bounding_box.params.bounds,
+            Some(Rect::from_min_max(Pos2::ZERO, Pos2::new(400., 300.)))
+        );
+        assert!(!radial.enabled);
+        assert_eq!(radial.params.radius, 150.);
+        assert_eq!(radial.params.metric, RadialMetric::Degree);
+        assert_eq!(radial.params.center, None);
+
+        let back = LayoutSpec::from(&st).fruchterman_reingold_state().unwrap();
+        assert_eq!(
+            back.bounding_box().params.bounds,
+            bounding_box.params.bounds
+        );
+        assert_eq!(back.radial_constraint().params.metric, RadialMetric::Degree);
+    }
+
+    #[test]
+    fn custom_radial_metric_roundtrips() {
+        let doc = JsonDocument::parse(
+            r#"{"layout":{"type":"fruchtermanReingold","extras":[
+                {"type":"radialConstraint","metric":{"custom":[[3,0.5]]}}]}}"#,
+        )
+        .unwrap();
+        let st = doc.layout.unwrap().fruchterman_reingold_state().unwrap();
+        let metric = RadialMetric::Custom(HashMap::from([(3, 0.5)]));
+        assert_eq!(st.radial_constraint().params.metric, metric);
+
+        let back = LayoutSpec::from(&st).fruchterman_reingold_state().unwrap();
+        assert_eq!(back.radial_constraint().params.metric, metric);
+    }
+}
@@ -0,0 +1,114 @@
+//! Import and export of [`crate::Graph`] in interchange formats.
+//!
+//! Every format lives behind its own cargo feature.
+
+#[cfg(feature = "dot")]
+pub mod dot;
+#[cfg(feature = "graphml")]
+pub mod graphml;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "png")]
+pub mod png;
+#[cfg(feature = "svg")]
+pub mod svg;
+
+use std::fmt::Display;
+
+#[cfg(any(feature = "dot", feature = "graphml"))]
+use egui::Color32;
+use petgraph::{stable_graph::DefaultIx, Directed, Undirected};
+
+use crate::Graph;
+
+/// Graph produced by an import. Directedness is taken from the source document.
+#[derive(Debug, Clone)]
+pub enum ImportedGraph<N: Clone = (), E: Clone = ()> {
+    Directed(Graph<N, E, Directed, DefaultIx>),
+    Undirected(Graph<N, E, Undirected, DefaultIx>),
+}
+
+impl<N: Clone, E: Clone> ImportedGraph<N, E> {
+    pub fn is_directed(&self) -> bool {
+        matches!(self, ImportedGraph::Directed(_))
+    }
+
+    pub fn node_count(&self) -> usize {
+        match self {
+            ImportedGraph::Directed(g) => g.node_count(),
+            ImportedGraph::Undirected(g) => g.node_count(),
+        }
+    }
+
+    pub fn edge_count(&self) -> usize {
+        match self {
+            ImportedGraph::Directed(g) => g.edge_count(),
+            ImportedGraph::Undirected(g) => g.edge_count(),
+        }
+    }
+}
+
+/// Reason an import failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The text is not well-formed in the source format.
+    Syntax(String),
+    /// The text is well-formed but does not describe a graph.
+    Schema(String),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Syntax(msg) => write!(f, "syntax error: {msg}"),
+            ImportError::Schema(msg) => write!(f, "schema error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Parses `#RRGGBB` or `#RRGGBBAA`.
+#[cfg(any(feature = "dot", feature = "graphml"))]
+pub(crate) fn color_from_hex(s: &str) -> Option<Color32> {
+    let hex = s.trim().strip_prefix('#')?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    match hex.len() {
+        6 => Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?)),
+        8 => Some(Color32::from_rgba_unmultiplied(
+            channel(0)?,
+            channel(2)?,
+            channel(4)?,
+            channel(6)?,
+        )),
+        _ => None,
+    }
+}
+
+/// Escapes text for XML content and attribute values.
+#[cfg(any(feature = "graphml", feature = "svg"))]
+pub(crate) fn escape_xml(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&apos;"),
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+/// Formats as `#RRGGBB`, or `#RRGGBBAA` for translucent colors.
+#[cfg(any(feature = "dot", feature = "graphml"))]
+pub(crate) fn color_to_hex(c: Color32) -> String {
+    let [r, g, b, a] = c.to_srgba_unmultiplied();
+    if a == u8::MAX {
+        format!("#{r:02X}{g:02X}{b:02X}")
+    } else {
+        format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+    }
+}
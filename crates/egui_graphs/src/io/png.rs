@@ -0,0 +1,124 @@
+//! PNG export of the rendered graph.
+//!
+//! The graph is rasterized on the CPU with [`crate::render_image`], so the picture matches the
+//! widget including anti-aliased edges and labels, without a window or GPU. Use
+//! [`crate::RenderOptions::with_pixels_per_point`] for high density output.
+
+use std::io;
+
+use egui::ColorImage;
+use petgraph::{stable_graph::IndexType, EdgeType};
+
+use crate::{render_image, DisplayEdge, DisplayNode, Graph, RenderOptions};
+
+/// Renders the graph and encodes it as an 8-bit RGBA PNG of [`RenderOptions::size_px`].
+pub fn export_graph_to_png<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    options: &RenderOptions,
+) -> io::Result<Vec<u8>>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    encode_png(&render_image(g, options))
+}
+
+/// Encodes an image as an 8-bit RGBA PNG, e.g. the output of [`crate::render_image`] after
+/// custom post-processing.
+pub fn encode_png(image: &ColorImage) -> io::Result<Vec<u8>> {
+    let [w, h] = image.size;
+    let (Ok(w), Ok(h)) = (u32::try_from(w), u32::try_from(h)) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "image is too large for PNG",
+        ));
+    };
+
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, w, h);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    let data: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|c| c.to_srgba_unmultiplied())
+        .collect();
+    writer.write_image_data(&data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{Pos2, Vec2, Visuals};
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+    use crate::{MetadataFrame, RenderView, SettingsStyle};
+
+    fn graph() -> Graph {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_label_and_location((), "alpha".to_string(), Pos2::new(0., 0.));
+        let b = g.add_node_with_location((), Pos2::new(200., 0.));
+        g.add_edge(a, b, ());
+        g
+    }
+
+    #[test]
+    fn renders_nodes_over_background() {
+        let mut g = graph();
+        let mut meta = MetadataFrame::default();
+        meta.pan = Vec2::new(50., 50.);
+        let options = RenderOptions::new(Vec2::new(300., 100.))
+            .with_view(RenderView::Frame(meta))
+            .with_visuals(Visuals::light());
+        let image = render_image(&mut g, &options);
+        assert_eq!(image.size, [300, 100]);
+
+        let background = options.visuals().panel_fill;
+        assert_eq!(image.pixels[0], background);
+        assert_eq!(image.pixels[image.pixels.len() - 1], background);
+
+        // Node centers at (50, 50) and (250, 50) are opaque and differ from the background.
+        for x in [50, 250] {
+            let px = image.pixels[50 * 300 + x];
+            assert_ne!(px, background);
+            assert_eq!(px.a(), u8::MAX);
+        }
+    }
+
+    #[test]
+    fn labels_and_density_change_output() {
+        let mut g = graph();
+        let options = RenderOptions::new(Vec2::new(300., 100.));
+        let plain = render_image(&mut g, &options);
+        let labeled = render_image(
+            &mut g,
+            &options
+                .clone()
+                .with_style(SettingsStyle::new().with_labels_always(true)),
+        );
+        assert_ne!(plain.pixels, labeled.pixels);
+
+        let dense = render_image(&mut g, &options.with_pixels_per_point(2.));
+        assert_eq!(dense.size, [600, 200]);
+    }
+
+    #[test]
+    fn encodes_decodable_png() {
+        let mut g = graph();
+        let options = RenderOptions::new(Vec2::new(64., 32.));
+        let bytes = export_graph_to_png(&mut g, &options).unwrap();
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (64, 32));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+    }
+}
@@ -0,0 +1,353 @@
+//! SVG export of the rendered graph.
+//!
+//! The graph is drawn off-screen with [`crate::render_shapes`], so the document shows exactly
+//! what the widget would: node and edge display shapes, arrow tips, labels, group frames and
+//! colors of the selected theme. Shapes are converted one to one: circles, rects, paths and
+//! Bézier curves to their SVG counterparts, text rows to `<text>` elements and meshes to
+//! triangles.
+
+use std::fmt::Write;
+
+use egui::{
+    epaint::{ColorMode, PathStroke, TextShape},
+    Color32, FontFamily, Pos2, Rect, Shape, Stroke, StrokeKind, Vec2,
+};
+use petgraph::{stable_graph::IndexType, EdgeType};
+
+use super::escape_xml;
+use crate::{render_shapes, DisplayEdge, DisplayNode, Graph, RenderOptions};
+
+/// Renders the graph and writes it as an SVG document of the viewport size. The background is
+/// filled with the panel color of the options visuals.
+pub fn export_graph_to_string<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    options: &RenderOptions,
+) -> String
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let shapes = render_shapes(g, options);
+    shapes_to_svg(&shapes, options.size(), Some(options.visuals().panel_fill))
+}
+
+/// Writes shapes as an SVG document of the given size, e.g. the output of
+/// [`crate::render_shapes`] after custom post-processing.
+pub fn shapes_to_svg(shapes: &[Shape], size: Vec2, background: Option<Color32>) -> String {
+    let mut out = String::new();
+    write_svg(&mut out, shapes, size, background).expect("writing to a String never fails");
+    out
+}
+
+fn write_svg(
+    out: &mut String,
+    shapes: &[Shape],
+    size: Vec2,
+    background: Option<Color32>,
+) -> std::fmt::Result {
+    let (w, h) = (num(size.x), num(size.y));
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+    )?;
+    if let Some(c) = background {
+        writeln!(
+            out,
+            r#"  <rect width="100%" height="100%"{}/>"#,
+            paint("fill", c)
+        )?;
+    }
+    for s in shapes {
+        write_shape(out, s)?;
+    }
+    writeln!(out, "</svg>")
+}
+
+fn write_shape(out: &mut String, shape: &Shape) -> std::fmt::Result {
+    match shape {
+        Shape::Vec(shapes) => {
+            for s in shapes {
+                write_shape(out, s)?;
+            }
+        }
+        Shape::Circle(c) => writeln!(
+            out,
+            r#"  <circle cx="{}" cy="{}" r="{}"{}{}/>"#,
+            num(c.center.x),
+            num(c.center.y),
+            num(c.radius),
+            paint("fill", c.fill),
+            stroke(c.stroke),
+        )?,
+        Shape::Ellipse(e) => writeln!(
+            out,
+            r#"  <ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}{}/>"#,
+            num(e.center.x),
+            num(e.center.y),
+            num(e.radius.x),
+            num(e.radius.y),
+            paint("fill", e.fill),
+            stroke(e.stroke),
+        )?,
+        Shape::LineSegment { points, stroke: s } => writeln!(
+            out,
+            r#"  <line x1="{}" y1="{}" x2="{}" y2="{}"{}/>"#,
+            num(points[0].x),
+            num(points[0].y),
+            num(points[1].x),
+            num(points[1].y),
+            stroke(*s),
+        )?,
+        Shape::Path(p) => {
+            let tag = if p.closed { "polygon" } else { "polyline" };
+            let fill = if p.closed {
+                paint("fill", p.fill)
+            } else {
+                paint("fill", Color32::TRANSPARENT)
+            };
+            writeln!(
+                out,
+                r#"  <{tag} points="{}"{fill}{}/>"#,
+                points(&p.points),
+                path_stroke(&p.stroke),
+            )?;
+        }
+        Shape::Rect(r) => {
+            let radius = f32::from(r.corner_radius.nw);
+            write_rect(out, r.rect, radius, &paint("fill", r.fill))?;
+            let w = r.stroke.width;
+            let stroke_rect = match r.stroke_kind {
+                StrokeKind::Inside => r.rect.shrink(w / 2.),
+                StrokeKind::Middle => r.rect,
+                StrokeKind::Outside => r.rect.expand(w / 2.),
+            };
+            if w > 0. && r.stroke.color.a() > 0 {
+                let attrs = format!(
+                    "{}{}",
+                    paint("fill", Color32::TRANSPARENT),
+                    stroke(r.stroke)
+                );
+                write_rect(out, stroke_rect, radius, &attrs)?;
+            }
+        }
+        Shape::QuadraticBezier(b) => {
+            let [p0, p1, p2] = b.points;
+            write_curve(
+                out,
+                &format!("M{} Q{} {}", pt(p0), pt(p1), pt(p2)),
+                b.closed,
+                b.fill,
+                &b.stroke,
+            )?;
+        }
+        Shape::CubicBezier(b) => {
+            let [p0, p1, p2, p3] = b.points;
+            write_curve(
+                out,
+                &format!("M{} C{} {} {}", pt(p0), pt(p1), pt(p2), pt(p3)),
+                b.closed,
+                b.fill,
+                &b.stroke,
+            )?;
+        }
+        Shape::Text(t) => write_text(out, t)?,
+        Shape::Mesh(mesh) => {
+            for tri in mesh.indices.chunks_exact(3) {
+                let v: Vec<_> = tri.iter().map(|i| &mesh.vertices[*i as usize]).collect();
+                let pts: Vec<Pos2> = v.iter().map(|v| v.pos).collect();
+                writeln!(
+                    out,
+                    r#"  <polygon points="{}"{}/>"#,
+                    points(&pts),
+                    paint("fill", v[0].color)
+                )?;
+            }
+        }
+        Shape::Noop | Shape::Callback(_) => {}
+    }
+    Ok(())
+}
+
+fn write_rect(out: &mut String, rect: Rect, radius: f32, attrs: &str) -> std::fmt::Result {
+    write!(
+        out,
+        r#"  <rect x="{}" y="{}" width="{}" height="{}""#,
+        num(rect.min.x),
+        num(rect.min.y),
+        num(rect.width()),
+        num(rect.height())
+    )?;
+    if radius > 0. {
+        write!(out, r#" rx="{}""#, num(radius))?;
+    }
+    writeln!(out, "{attrs}/>")
+}
+
+fn write_curve(
+    out: &mut String,
+    d: &str,
+    closed: bool,
+    fill: Color32,
+    s: &PathStroke,
+) -> std::fmt::Result {
+    let (d, fill) = if closed {
+        (format!("{d} Z"), fill)
+    } else {
+        (d.to_string(), Color32::TRANSPARENT)
+    };
+    writeln!(
+        out,
+        r#"  <path d="{d}"{}{}/>"#,
+        paint("fill", fill),
+        path_stroke(s)
+    )
+}
+
+/// One `<text>` element per galley row placed on the row baseline.
+fn write_text(out: &mut String, t: &TextShape) -> std::fmt::Result {
+    let Some(section) = t.galley.job.sections.first() else {
+        return Ok(());
+    };
+    let font = &section.format.font_id;
+    let family = match font.family {
+        FontFamily::Monospace => "monospace",
+        _ => "sans-serif",
+    };
+    let color = t
+        .override_text_color
+        .unwrap_or(if section.format.color == Color32::PLACEHOLDER {
+            t.fallback_color
+        } else {
+            section.format.color
+        });
+    let color = color.gamma_multiply(t.opacity_factor);
+
+    let transform = if t.angle == 0. {
+        String::new()
+    } else {
+        format!(
+            r#" transform="rotate({} {})""#,
+            num(t.angle.to_degrees()),
+            pt(t.pos).replace(',', " ")
+        )
+    };
+
+    for row in &t.galley.rows {
+        let Some(first) = row.row.glyphs.first() else {
+            continue;
+        };
+        let text: String = row.row.glyphs.iter().map(|g| g.chr).collect();
+        let origin = t.pos + row.pos.to_vec2() + first.pos.to_vec2();
+        writeln!(
+            out,
+            r#"  <text x="{}" y="{}" font-family="{family}" font-size="{}" xml:space="preserve"{}{transform}>{}</text>"#,
+            num(origin.x),
+            num(origin.y),
+            num(font.size),
+            paint("fill", color),
+            escape_xml(&text),
+        )?;
+    }
+    Ok(())
+}
+
+/// ` name="rgb(..)"` with an opacity attribute for translucent colors, `none` when invisible.
+fn paint(name: &str, c: Color32) -> String {
+    if c.a() == 0 {
+        return format!(r#" {name}="none""#);
+    }
+    let [r, g, b, a] = c.to_srgba_unmultiplied();
+    let mut res = format!(r#" {name}="rgb({r},{g},{b})""#);
+    if a < u8::MAX {
+        let _ = write!(res, r#" {name}-opacity="{}""#, num(f32::from(a) / 255.));
+    }
+    res
+}
+
+fn stroke(s: Stroke) -> String {
+    if s.width <= 0. || s.color.a() == 0 {
+        return paint("stroke", Color32::TRANSPARENT);
+    }
+    format!(
+        r#"{} stroke-width="{}""#,
+        paint("stroke", s.color),
+        num(s.width)
+    )
+}
+
+fn path_stroke(s: &PathStroke) -> String {
+    match &s.color {
+        ColorMode::Solid(c) => stroke(Stroke::new(s.width, *c)),
+        // Gradients have no direct SVG equivalent, use the color at the origin.
+        ColorMode::UV(f) => stroke(Stroke::new(s.width, f(Rect::ZERO, Pos2::ZERO))),
+    }
+}
+
+fn points(pts: &[Pos2]) -> String {
+    pts.iter().map(|p| pt(*p)).collect::<Vec<_>>().join(" ")
+}
+
+fn pt(p: Pos2) -> String {
+    format!("{},{}", num(p.x), num(p.y))
+}
+
+/// Shortest representation with at most two decimals.
+fn num(v: f32) -> String {
+    let s = format!("{v:.2}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Visuals;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+    use crate::SettingsStyle;
+
+    #[test]
+    fn exports_nodes_edges_and_labels() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_label_and_location((), "a < b".to_string(), Pos2::new(0., 0.));
+        let b = g.add_node_with_location((), Pos2::new(100., 0.));
+        g.add_edge(a, b, ());
+        g.add_edge(a, b, ());
+        g.add_edge(b, b, ());
+
+        let options = RenderOptions::new(Vec2::new(320., 200.))
+            .with_style(SettingsStyle::new().with_labels_always(true))
+            .with_visuals(Visuals::light());
+        let svg = export_graph_to_string(&mut g, &options);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(r#"width="320" height="200""#));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert!(svg.contains(">a &lt; b</text>"));
+        // Straight edge with its arrow tip, curved parallel edge and a self loop.
+        assert!(svg.contains("<line"));
+        assert!(svg.contains("<polygon"));
+        assert!(svg.matches("<path").count() >= 2);
+    }
+
+    #[test]
+    fn formats_numbers_and_colors() {
+        assert_eq!(num(1.0), "1");
+        assert_eq!(num(-0.001), "0");
+        assert_eq!(num(2.346), "2.35");
+        assert_eq!(paint("fill", Color32::TRANSPARENT), r#" fill="none""#);
+        assert_eq!(
+            paint("fill", Color32::from_rgba_unmultiplied(255, 0, 0, 51)),
+            r#" fill="rgb(255,0,0)" fill-opacity="0.2""#
+        );
+    }
+}
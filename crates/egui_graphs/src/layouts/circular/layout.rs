@@ -0,0 +1,131 @@
+use std::f32::consts::TAU;
+
+use egui::{Pos2, Vec2};
+use petgraph::{csr::IndexType, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{Layout, LayoutState, NodeKey},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Order of the nodes along the circle, ascending from `start_angle`.
+    pub order: NodeKey,
+    /// Circle radius. None fits the circle to the node count keeping `spacing` between
+    /// neighbors.
+    pub radius: Option<f32>,
+    /// Arc length between neighbors used when `radius` is None.
+    pub spacing: f32,
+    /// Angle of the first node in radians, 0 points right.
+    pub start_angle: f32,
+    /// Place nodes clockwise on screen instead of counter-clockwise.
+    pub clockwise: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            order: NodeKey::Index,
+            radius: None,
+            spacing: 50.,
+            start_angle: 0.,
+            clockwise: false,
+        }
+    }
+}
+
+impl LayoutState for State {}
+
+/// Places nodes evenly on a circle around the origin. Applies once.
+#[derive(Debug, Default)]
+pub struct Circular {
+    state: State,
+}
+
+impl Layout<State> for Circular {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        g.clear_edge_bends();
+        layout_circular(g, &self.state);
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Circular { state }
+    }
+}
+
+fn layout_circular<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let nodes = state.order.sorted(g);
+    let n = nodes.len() as f32;
+    let radius = state.radius.unwrap_or(n * state.spacing / TAU);
+    // Screen y grows downward, so increasing angles run clockwise on screen.
+    let sign = if state.clockwise { 1. } else { -1. };
+    for (i, (idx, _)) in nodes.into_iter().enumerate() {
+        let angle = state.start_angle + sign * i as f32 / n * TAU;
+        if let Some(node) = g.node_mut(idx).filter(|n| !n.pinned()) {
+            node.set_location(Pos2::ZERO + Vec2::angled(angle) * radius);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn places_nodes_in_key_order_on_circle() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..4).map(|_| g.add_node(())).collect();
+        let st = State {
+            order: NodeKey::Custom(HashMap::from([(0, 3.), (1, 2.), (2, 1.), (3, 0.)])),
+            radius: Some(100.),
+            clockwise: true,
+            ..State::default()
+        };
+        layout_circular(&mut g, &st);
+
+        let at = |g: &Graph, i: usize| g.node(n[i]).unwrap().location();
+        // Reversed by the key, clockwise on screen starting to the right.
+        assert!((at(&g, 3) - Pos2::new(100., 0.)).length() < 1e-3);
+        assert!((at(&g, 2) - Pos2::new(0., 100.)).length() < 1e-3);
+        assert!((at(&g, 1) - Pos2::new(-100., 0.)).length() < 1e-3);
+
+        layout_circular(&mut g, &State::default());
+        let radius = 4. * 50. / TAU;
+        assert!((at(&g, 0).to_vec2().length() - radius).abs() < 1e-3);
+        assert!((at(&g, 1) - Pos2::new(0., -radius)).length() < 1e-3);
+    }
+}
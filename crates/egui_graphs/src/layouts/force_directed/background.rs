@@ -0,0 +1,612 @@
+use std::collections::HashMap;
+use std::thread::JoinHandle;
+
+use crossbeam::channel::{unbounded, Receiver, Sender};
+use egui::{Pos2, Rect};
+use petgraph::{
+    stable_graph::{DefaultIx, IndexType, NodeIndex, StableGraph},
+    Directed, EdgeType,
+};
+
+use super::algorithm::ForceAlgorithm;
+use crate::{DisplayEdge, DisplayNode, Graph};
+
+/// Copy of the visible part of a graph stepped by the worker. Nodes are numbered in the order
+/// of [`Graph::visible_node_indices`], node payloads are the clusters and edge payloads the
+/// edge weights. Node shapes are circles with the radius set to [`Graph::node_extent`], so size
+/// aware forces see the nodes as large as they are drawn.
+type Snapshot<Ty, Ix> = Graph<Option<u64>, f32, Ty, Ix>;
+
+enum Command<S, Ty: EdgeType, Ix: IndexType> {
+    Snapshot(u64, Box<Snapshot<Ty, Ix>>),
+    Move(Vec<(usize, Pos2)>),
+    State(S),
+    Running(bool),
+    /// A frame was drawn, step once with the given viewport.
+    Tick(Rect),
+    Stop,
+}
+
+struct Update<S> {
+    generation: u64,
+    /// Number of commands applied before the step.
+    seq: u64,
+    positions: Vec<Pos2>,
+    state: S,
+}
+
+/// Layouts the graph outside of [`crate::Layout::next`], see [`crate::GraphView::with_background_layout`].
+pub trait SyncLayout<N, E, Ty, Ix, Dn, De>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    /// Called once per frame instead of the layout step.
+    fn sync(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, view: Rect);
+}
+
+/// Runs a [`ForceAlgorithm`] on a worker thread so heavy simulations do not stall rendering.
+///
+/// The worker steps a snapshot of the visible nodes and edges at most once per
+/// [`BackgroundLayout::sync`] call, which applies the latest positions to the graph. Nodes
+/// moved outside of the layout, e.g. dragged, are sent back to the worker. The snapshot is
+/// rebuilt whenever [`Graph::revision`] changes, e.g. when nodes and edges are added, removed,
+/// hidden or pinned. The worker stops
+/// when the runner is dropped.
+///
+/// Where threads are not available, e.g. on wasm32 or when spawning fails, the snapshot is
+/// stepped on the calling thread during [`BackgroundLayout::sync`] instead.
+pub struct BackgroundLayout<A, Ty = Directed, Ix = DefaultIx>
+where
+    A: ForceAlgorithm,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    commands: Sender<Command<A::State, Ty, Ix>>,
+    updates: Receiver<Update<A::State>>,
+    driver: Option<Driver<A, Ty, Ix>>,
+    state: A::State,
+    running: bool,
+    /// Number of commands sent.
+    seq: u64,
+    generation: u64,
+    /// Revision of the snapshotted graph, None forces a resync.
+    revision: Option<u64>,
+    /// Index in the graph of every snapshot node.
+    nodes: Vec<NodeIndex<Ix>>,
+    /// Location of every snapshot node as last written or sent to the worker.
+    known: Vec<Pos2>,
+    /// Sequence number of the last move sent for every snapshot node. Positions computed
+    /// before the move reached the worker are stale for the node.
+    moved_at: Vec<u64>,
+}
+
+impl<A, Ty, Ix> BackgroundLayout<A, Ty, Ix>
+where
+    A: ForceAlgorithm + 'static,
+    A::State: Send + 'static,
+    Ty: EdgeType + Send + 'static,
+    Ix: IndexType + Send,
+{
+    /// Spawns the worker running the algorithm created from `state`. Falls back to stepping
+    /// on the calling thread if the worker can not be spawned.
+    pub fn new(state: A::State) -> Self {
+        let (commands, commands_rx) = unbounded();
+        let (updates_tx, updates) = unbounded();
+        let driver = spawn::<A, Ty, Ix>(&state, commands_rx, updates_tx);
+        Self::with_driver(state, commands, updates, driver)
+    }
+
+    /// Runner stepping the algorithm created from `state` on the calling thread.
+    pub fn new_inline(state: A::State) -> Self {
+        let (commands, commands_rx) = unbounded();
+        let (updates_tx, updates) = unbounded();
+        let driver = Driver::Inline {
+            worker: Box::new(Worker::new(&state)),
+            commands: commands_rx,
+            updates: updates_tx,
+        };
+        Self::with_driver(state, commands, updates, driver)
+    }
+
+    /// Whether the algorithm is stepped on a worker thread.
+    pub fn is_threaded(&self) -> bool {
+        matches!(self.driver, Some(Driver::Thread(_)))
+    }
+
+    fn with_driver(
+        state: A::State,
+        commands: Sender<Command<A::State, Ty, Ix>>,
+        updates: Receiver<Update<A::State>>,
+        driver: Driver<A, Ty, Ix>,
+    ) -> Self {
+        Self {
+            commands,
+            updates,
+            driver: Some(driver),
+            state,
+            running: true,
+            seq: 0,
+            generation: 0,
+            revision: None,
+            nodes: Vec::new(),
+            known: Vec::new(),
+            moved_at: Vec::new(),
+        }
+    }
+
+    /// State of the algorithm as of the last applied step.
+    pub fn state(&self) -> A::State {
+        self.state.clone()
+    }
+
+    /// Replaces the algorithm with one created from `state`, e.g. after tweaking parameters.
+    pub fn set_state(&mut self, state: A::State) {
+        self.state = state.clone();
+        self.send(Command::State(state));
+    }
+
+    /// Stops stepping, positions keep the last applied step.
+    pub fn pause(&mut self) {
+        self.running = false;
+        self.send(Command::Running(false));
+    }
+
+    pub fn resume(&mut self) {
+        self.running = true;
+        self.send(Command::Running(true));
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Rebuilds the snapshot on the next sync even if the graph revision did not change, e.g.
+    /// after changing node sizes by other means than [`Graph::node_mut`].
+    pub fn resync(&mut self) {
+        self.revision = None;
+    }
+
+    /// Applies the latest step of the worker to `g` and asks for the next one. Call once per
+    /// frame before drawing.
+    pub fn sync<N, E, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, view: Rect)
+    where
+        N: Clone,
+        E: Clone,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.revision == Some(g.revision()) {
+            self.send_moves(g);
+        } else {
+            self.snapshot(g);
+        }
+
+        let generation = self.generation;
+        if let Some(update) = self
+            .updates
+            .try_iter()
+            .filter(|u| u.generation == generation)
+            .last()
+        {
+            self.apply(g, update);
+        }
+
+        self.send(Command::Tick(view));
+    }
+
+    fn send(&mut self, cmd: Command<A::State, Ty, Ix>) {
+        // Fails only if the worker panicked, there is nothing left to drive then.
+        if self.commands.send(cmd).is_ok() {
+            self.seq += 1;
+        }
+        if let Some(Driver::Inline {
+            worker,
+            commands,
+            updates,
+        }) = &mut self.driver
+        {
+            worker.handle(commands.try_iter(), updates);
+        }
+    }
+
+    fn snapshot<N, E, Dn, De>(&mut self, g: &Graph<N, E, Ty, Ix, Dn, De>)
+    where
+        N: Clone,
+        E: Clone,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.nodes = g.visible_node_indices().collect();
+        let local: HashMap<NodeIndex<Ix>, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &idx)| (idx, i))
+            .collect();
+
+        let mut sg = StableGraph::<Option<u64>, f32, Ty, Ix>::with_capacity(self.nodes.len(), 0);
+        for cluster in g.node_clusters(&self.nodes) {
+            sg.add_node(cluster);
+        }
+        for (idx, _) in g.edges_iter() {
+            if let Some((start, end)) = g.visible_edge_endpoints(idx) {
+                let weight = g.edge_weight(idx).unwrap_or(1.);
+                sg.add_edge(
+                    NodeIndex::new(local[&start]),
+                    NodeIndex::new(local[&end]),
+                    weight,
+                );
+            }
+        }
+
+        let mut snapshot: Snapshot<Ty, Ix> = Graph::from(&sg);
+        snapshot.set_edge_weight_fn(|e| e.payload);
+        snapshot.set_node_cluster_fn(|n| n.payload);
+        self.known.clear();
+        for (i, &idx) in self.nodes.iter().enumerate() {
+            let (location, fixed) = g.node(idx).map_or((Pos2::ZERO, false), |n| {
+                (n.location(), n.pinned() || n.dragged())
+            });
+            if let Some(n) = snapshot.node_mut(NodeIndex::new(i)) {
+                n.set_location(location);
+                n.set_pinned(fixed);
+                if let Some(extent) = g.node_extent(idx) {
+                    n.display_mut().radius = extent;
+                }
+            }
+            self.known.push(location);
+        }
+        self.moved_at = vec![0; self.nodes.len()];
+        self.revision = Some(g.revision());
+
+        self.generation += 1;
+        self.send(Command::Snapshot(self.generation, Box::new(snapshot)));
+    }
+
+    fn send_moves<N, E, Dn, De>(&mut self, g: &Graph<N, E, Ty, Ix, Dn, De>)
+    where
+        N: Clone,
+        E: Clone,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let moves: Vec<(usize, Pos2)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &idx)| {
+                let location = g.node(idx)?.location();
+                (location != self.known[i]).then_some((i, location))
+            })
+            .collect();
+        if moves.is_empty() {
+            return;
+        }
+
+        for &(i, location) in &moves {
+            self.known[i] = location;
+        }
+        self.send(Command::Move(moves.clone()));
+        for (i, _) in moves {
+            self.moved_at[i] = self.seq;
+        }
+    }
+
+    fn apply<N, E, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, update: Update<A::State>)
+    where
+        N: Clone,
+        E: Clone,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.state = update.state;
+        g.clear_edge_bends();
+        for (i, location) in update.positions.into_iter().enumerate() {
+            if update.seq < self.moved_at[i] {
+                continue;
+            }
+            if let Some(n) = g.node_mut_moved(self.nodes[i]).filter(|n| !n.dragged()) {
+                n.set_location(location);
+                self.known[i] = location;
+            }
+        }
+    }
+}
+
+impl<A, Ty, Ix, N, E, Dn, De> SyncLayout<N, E, Ty, Ix, Dn, De> for BackgroundLayout<A, Ty, Ix>
+where
+    A: ForceAlgorithm + 'static,
+    A::State: Send + 'static,
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType + Send + 'static,
+    Ix: IndexType + Send,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    fn sync(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, view: Rect) {
+        BackgroundLayout::sync(self, g, view);
+    }
+}
+
+impl<A, Ty, Ix> Drop for BackgroundLayout<A, Ty, Ix>
+where
+    A: ForceAlgorithm,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Stop);
+        if let Some(Driver::Thread(worker)) = self.driver.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Where the algorithm of a [`BackgroundLayout`] is stepped.
+enum Driver<A, Ty, Ix>
+where
+    A: ForceAlgorithm,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    Thread(JoinHandle<()>),
+    /// Commands are handled as soon as they are sent.
+    Inline {
+        worker: Box<Worker<A, Ty, Ix>>,
+        commands: Receiver<Command<A::State, Ty, Ix>>,
+        updates: Sender<Update<A::State>>,
+    },
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn<A, Ty, Ix>(
+    state: &A::State,
+    commands: Receiver<Command<A::State, Ty, Ix>>,
+    updates: Sender<Update<A::State>>,
+) -> Driver<A, Ty, Ix>
+where
+    A: ForceAlgorithm + 'static,
+    A::State: Send + 'static,
+    Ty: EdgeType + Send + 'static,
+    Ix: IndexType + Send,
+{
+    let initial = state.clone();
+    // The closure owns the channels, keep handles to get them back if spawning fails.
+    let (fallback_commands, fallback_updates) = (commands.clone(), updates.clone());
+    let spawned = std::thread::Builder::new()
+        .name("egui_graphs layout".to_string())
+        .spawn(move || work::<A, Ty, Ix>(Worker::new(&initial), &commands, &updates));
+    match spawned {
+        Ok(handle) => Driver::Thread(handle),
+        Err(_) => Driver::Inline {
+            worker: Box::new(Worker::new(state)),
+            commands: fallback_commands,
+            updates: fallback_updates,
+        },
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn<A, Ty, Ix>(
+    state: &A::State,
+    commands: Receiver<Command<A::State, Ty, Ix>>,
+    updates: Sender<Update<A::State>>,
+) -> Driver<A, Ty, Ix>
+where
+    A: ForceAlgorithm,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    Driver::Inline {
+        worker: Box::new(Worker::new(state)),
+        commands,
+        updates,
+    }
+}
+
+/// Algorithm and snapshot stepped by a [`BackgroundLayout`].
+struct Worker<A, Ty, Ix>
+where
+    A: ForceAlgorithm,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    alg: A,
+    snapshot: Option<(u64, Snapshot<Ty, Ix>)>,
+    running: bool,
+    /// Number of commands handled.
+    seq: u64,
+}
+
+impl<A, Ty, Ix> Worker<A, Ty, Ix>
+where
+    A: ForceAlgorithm,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    fn new(state: &A::State) -> Self {
+        Self {
+            alg: A::from_state(state.clone()),
+            snapshot: None,
+            running: true,
+            seq: 0,
+        }
+    }
+
+    /// Applies the commands and steps once if a frame was drawn meanwhile. Returns false
+    /// when asked to stop or nobody listens for updates anymore.
+    fn handle(
+        &mut self,
+        commands: impl Iterator<Item = Command<A::State, Ty, Ix>>,
+        updates: &Sender<Update<A::State>>,
+    ) -> bool {
+        let mut view = None;
+        for cmd in commands {
+            self.seq += 1;
+            match cmd {
+                Command::Snapshot(generation, g) => self.snapshot = Some((generation, *g)),
+                Command::Move(moves) => {
+                    let Some((_, g)) = &mut self.snapshot else {
+                        continue;
+                    };
+                    for (i, location) in moves {
+                        if let Some(n) = g.node_mut(NodeIndex::new(i)) {
+                            n.set_location(location);
+                        }
+                    }
+                }
+                Command::State(s) => self.alg = A::from_state(s),
+                Command::Running(r) => self.running = r,
+                Command::Tick(rect) => view = Some(rect),
+                Command::Stop => return false,
+            }
+        }
+
+        let (Some(view), Some((generation, g))) = (view, &mut self.snapshot) else {
+            return true;
+        };
+        if !self.running || g.node_count() == 0 {
+            return true;
+        }
+        self.alg.step(g, view);
+        let update = Update {
+            generation: *generation,
+            seq: self.seq,
+            positions: g.nodes_iter().map(|(_, n)| n.location()).collect(),
+            state: self.alg.state(),
+        };
+        updates.send(update).is_ok()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn work<A, Ty, Ix>(
+    mut worker: Worker<A, Ty, Ix>,
+    commands: &Receiver<Command<A::State, Ty, Ix>>,
+    updates: &Sender<Update<A::State>>,
+) where
+    A: ForceAlgorithm,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    // Block until something happens, then take everything queued so the worker never falls
+    // more than one step behind the frames.
+    while let Ok(first) = commands.recv() {
+        if !worker.handle(std::iter::once(first).chain(commands.try_iter()), updates) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Vec2;
+
+    use super::*;
+    use crate::{FruchtermanReingold, FruchtermanReingoldState};
+
+    type Runner = BackgroundLayout<FruchtermanReingold>;
+
+    fn view() -> Rect {
+        Rect::from_min_size(Pos2::ZERO, Vec2::splat(500.))
+    }
+
+    /// Syncs `frames` times, the step ticked by a sync is applied by the next one.
+    fn frames(runner: &mut Runner, g: &mut Graph, frames: usize) {
+        for _ in 0..frames {
+            runner.sync(g, view());
+        }
+    }
+
+    fn pair() -> (Graph, NodeIndex, NodeIndex) {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(0., 0.));
+        let b = g.add_node_with_location((), Pos2::new(1., 0.));
+        g.add_edge(a, b, ());
+        (g, a, b)
+    }
+
+    #[test]
+    fn worker_thread_answers_ticks() {
+        let (mut g, _, b) = pair();
+        let mut runner = Runner::new(FruchtermanReingoldState::default());
+        assert!(runner.is_threaded());
+
+        runner.sync(&mut g, view());
+        // Every tick of a running layout is answered with a step.
+        let update = runner.updates.recv().unwrap();
+        runner.apply(&mut g, update);
+        assert!(g.node(b).unwrap().location().x > 1. + 1e-3);
+    }
+
+    #[test]
+    fn streams_positions_and_pauses() {
+        let (mut g, a, b) = pair();
+        let mut runner = Runner::new_inline(FruchtermanReingoldState::default());
+        frames(&mut runner, &mut g, 2);
+        assert!(g.node(b).unwrap().location().x > 1. + 1e-3);
+
+        runner.pause();
+        // Applies the step ticked before the pause.
+        frames(&mut runner, &mut g, 1);
+        let held = g.node(a).unwrap().location();
+        frames(&mut runner, &mut g, 5);
+        assert_eq!(g.node(a).unwrap().location(), held);
+
+        runner.resume();
+        frames(&mut runner, &mut g, 2);
+        assert_ne!(g.node(a).unwrap().location(), held);
+    }
+
+    #[test]
+    fn resyncs_on_mutation_and_keeps_moves() {
+        let (mut g, a, _) = pair();
+        let mut runner = Runner::new_inline(FruchtermanReingoldState::default());
+        frames(&mut runner, &mut g, 1);
+
+        // Applying steps and moving nodes keeps the snapshot.
+        let generation = runner.generation;
+        g.set_node_location(a, Pos2::new(5., 5.));
+        frames(&mut runner, &mut g, 3);
+        assert_eq!(runner.generation, generation);
+
+        // A new node joins the simulation.
+        let c = g.add_node_with_location((), Pos2::new(0.5, 0.));
+        g.add_edge(a, c, ());
+        frames(&mut runner, &mut g, 2);
+        assert_ne!(g.node(c).unwrap().location(), Pos2::new(0.5, 0.));
+
+        // A pinned node moved by the user stays where it was put.
+        g.set_node_pinned(a, true);
+        g.node_mut(a).unwrap().set_location(Pos2::new(-300., 0.));
+        frames(&mut runner, &mut g, 10);
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(-300., 0.));
+        // Its neighbor is pulled toward the new location.
+        assert!(g.node(c).unwrap().location().x < -1.);
+    }
+
+    #[test]
+    fn snapshot_nodes_are_as_large_as_drawn() {
+        let (mut g, a, b) = pair();
+        g.node_mut(a).unwrap().display_mut().radius = 40.;
+        let mut runner = Runner::new_inline(FruchtermanReingoldState::default());
+        frames(&mut runner, &mut g, 1);
+
+        let Some(Driver::Inline { worker, .. }) = &runner.driver else {
+            unreachable!("inline runner");
+        };
+        let (_, snapshot) = worker.snapshot.as_ref().unwrap();
+        let radii: Vec<_> = snapshot
+            .nodes_iter()
+            .map(|(_, n)| n.display().radius)
+            .collect();
+        assert_eq!(radii, vec![40., g.node_extent(b).unwrap()]);
+    }
+}
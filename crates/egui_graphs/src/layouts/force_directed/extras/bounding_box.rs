@@ -0,0 +1,118 @@
+use egui::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::{DisplayEdge, DisplayNode, Graph};
+use petgraph::EdgeType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundingBoxParams {
+    /// Region in canvas coordinates, None keeps nodes inside the viewport.
+    pub bounds: Option<Rect>,
+    /// Distance kept from the walls.
+    pub margin: f32,
+    /// Pull back per unit outside the region, higher makes the walls stiffer.
+    pub strength: f32,
+}
+impl Default for BoundingBoxParams {
+    fn default() -> Self {
+        Self {
+            bounds: None,
+            margin: 10.,
+            strength: 20.,
+        }
+    }
+}
+
+/// Keeps nodes inside a rectangle: nodes beyond a wall are pulled back proportionally to how
+/// far out they are, nodes inside feel nothing.
+#[derive(Debug, Default)]
+pub struct BoundingBox;
+
+impl ExtraForce for BoundingBox {
+    type Params = BoundingBoxParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        area: Rect,
+        _k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.strength == 0.0 {
+            return;
+        }
+        let bounds = params.bounds.unwrap_or(area).shrink(params.margin);
+        // A margin larger than the region collapses it to its center.
+        let bounds = if bounds.is_positive() {
+            bounds
+        } else {
+            Rect::from_center_size(bounds.center(), Vec2::ZERO)
+        };
+        for (vec_pos, &idx) in indices.iter().enumerate() {
+            let pos = g.g().node_weight(idx).unwrap().location();
+            let inside = bounds.clamp(pos);
+            disp[vec_pos] += (inside - pos) * params.strength;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn only_nodes_outside_are_pulled_back() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let inside = g.add_node_with_location((), Pos2::new(50., 50.));
+        let right = g.add_node_with_location((), Pos2::new(130., 50.));
+        let corner = g.add_node_with_location((), Pos2::new(-10., -20.));
+        let params = BoundingBoxParams {
+            bounds: Some(Rect::from_min_max(Pos2::ZERO, Pos2::new(100., 100.))),
+            margin: 10.,
+            strength: 1.,
+        };
+        let mut disp = vec![Vec2::ZERO; 3];
+
+        BoundingBox::apply(
+            &params,
+            &g,
+            &[inside, right, corner],
+            &mut disp,
+            Rect::ZERO,
+            1.,
+        );
+
+        assert_eq!(disp[0], Vec2::ZERO);
+        assert_eq!(disp[1], Vec2::new(-40., 0.));
+        assert_eq!(disp[2], Vec2::new(20., 30.));
+    }
+
+    #[test]
+    fn defaults_to_the_viewport() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(500., 0.));
+        let params = BoundingBoxParams {
+            margin: 0.,
+            strength: 1.,
+            ..BoundingBoxParams::default()
+        };
+        let mut disp = vec![Vec2::ZERO];
+        let area = Rect::from_min_max(Pos2::new(-100., -100.), Pos2::new(100., 100.));
+
+        BoundingBox::apply(&params, &g, &[a], &mut disp, area, 1.);
+
+        assert_eq!(disp[0], Vec2::new(-400., 0.));
+    }
+}
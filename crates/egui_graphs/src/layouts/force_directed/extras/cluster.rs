@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use egui::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::{DisplayEdge, DisplayNode, Graph};
+use petgraph::EdgeType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterAttractionParams {
+    /// Pull of every node toward the centroid of its cluster.
+    pub attraction: f32,
+    /// Push of every node away from the centroids of other clusters, scaled by their sizes.
+    pub repulsion: f32,
+}
+impl Default for ClusterAttractionParams {
+    fn default() -> Self {
+        Self {
+            attraction: 0.5,
+            repulsion: 1.,
+        }
+    }
+}
+
+/// Gathers nodes sharing a cluster around their centroid and keeps clusters apart. Clusters
+/// come from [`Graph::node_clusters`]: node groups by default or any assignment set with
+/// [`Graph::set_node_cluster_fn`]. Nodes in no cluster are left alone.
+///
+/// Other clusters repel like FR repulsion from all of their members placed at the centroid,
+/// `repulsion * size * k² / distance`.
+#[derive(Debug, Default)]
+pub struct ClusterAttraction;
+
+impl ExtraForce for ClusterAttraction {
+    type Params = ClusterAttractionParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        _area: Rect,
+        k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.attraction == 0.0 && params.repulsion == 0.0 {
+            return;
+        }
+        let nodes: Vec<_> = indices
+            .iter()
+            .zip(g.node_clusters(indices))
+            .map(|(&idx, cluster)| {
+                let pos = g.g().node_weight(idx).unwrap().location().to_vec2();
+                (pos, cluster)
+            })
+            .collect();
+
+        // Sum of member positions and member count of every cluster.
+        let mut sums: HashMap<u64, (Vec2, usize)> = HashMap::new();
+        for &(pos, cluster) in &nodes {
+            if let Some(c) = cluster {
+                let entry = sums.entry(c).or_insert((Vec2::ZERO, 0));
+                entry.0 += pos;
+                entry.1 += 1;
+            }
+        }
+        let centroids: Vec<(u64, Vec2, f32)> = sums
+            .into_iter()
+            .map(|(c, (sum, count))| (c, sum / count as f32, count as f32))
+            .collect();
+
+        for (vec_pos, &(pos, cluster)) in nodes.iter().enumerate() {
+            let Some(own) = cluster else {
+                continue;
+            };
+            for &(c, centroid, size) in &centroids {
+                let delta = centroid - pos;
+                if c == own {
+                    disp[vec_pos] += delta * params.attraction;
+                    continue;
+                }
+                let dist = delta.length();
+                if dist <= f32::EPSILON {
+                    continue;
+                }
+                disp[vec_pos] -= delta / dist * params.repulsion * size * k * k / dist;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn clusters_gather_and_repel_each_other() {
+        let mut g: Graph<u64> = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location(1, Pos2::new(-10., 0.));
+        let b = g.add_node_with_location(1, Pos2::new(-30., 0.));
+        let c = g.add_node_with_location(2, Pos2::new(20., 0.));
+        let lone = g.add_node_with_location(0, Pos2::new(0., 50.));
+        g.set_node_cluster_fn(|n| (n.payload > 0).then_some(n.payload));
+        let params = ClusterAttractionParams {
+            attraction: 1.,
+            repulsion: 0.,
+        };
+        let indices = [a, b, c, lone];
+        let mut disp = vec![Vec2::ZERO; 4];
+
+        ClusterAttraction::apply(&params, &g, &indices, &mut disp, Rect::ZERO, 1.);
+
+        // Cluster 1 is centered at -20, a single node is its own centroid.
+        assert_eq!(disp[0], Vec2::new(-10., 0.));
+        assert_eq!(disp[1], Vec2::new(10., 0.));
+        assert_eq!(disp[2], Vec2::ZERO);
+        assert_eq!(disp[3], Vec2::ZERO);
+
+        let params = ClusterAttractionParams {
+            attraction: 0.,
+            repulsion: 1.,
+        };
+        let mut disp = vec![Vec2::ZERO; 4];
+        ClusterAttraction::apply(&params, &g, &indices, &mut disp, Rect::ZERO, 10.);
+
+        // Two members 40 away push node c by 2 * 10² / 40, c pushes a by 10² / 30.
+        assert_eq!(disp[2], Vec2::new(5., 0.));
+        assert!((disp[0].x + 100. / 30.).abs() < 1e-4);
+        assert_eq!(disp[3], Vec2::ZERO);
+    }
+
+    #[test]
+    fn groups_are_clusters_by_default() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(0., 0.));
+        let b = g.add_node_with_location((), Pos2::new(10., 0.));
+        let c = g.add_node_with_location((), Pos2::new(100., 0.));
+        g.add_group("g".to_string(), vec![a, b]);
+        assert_eq!(g.node_cluster(a), g.node_cluster(b));
+        assert_eq!(g.node_cluster(c), None);
+        assert_eq!(
+            g.node_clusters(&[a, b, c]),
+            vec![g.node_cluster(a), g.node_cluster(a), None]
+        );
+
+        let params = ClusterAttractionParams {
+            attraction: 1.,
+            repulsion: 0.,
+        };
+        let mut disp = vec![Vec2::ZERO; 3];
+        ClusterAttraction::apply(&params, &g, &[a, b, c], &mut disp, Rect::ZERO, 1.);
+
+        assert_eq!(
+            disp,
+            vec![Vec2::new(5., 0.), Vec2::new(-5., 0.), Vec2::ZERO]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use egui::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::{DisplayEdge, DisplayNode, Graph};
+use petgraph::EdgeType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollisionParams {
+    /// Gap kept free between node shapes.
+    pub padding: f32,
+    /// Multiplier turning the overlap into a displacement split between both nodes. Like any
+    /// displacement it is scaled by the step of the algorithm, e.g. `dt * damping` in
+    /// Fruchterman-Reingold, so overlaps are resolved over several steps.
+    pub strength: f32,
+}
+impl Default for CollisionParams {
+    fn default() -> Self {
+        Self {
+            padding: 5.,
+            strength: 1.,
+        }
+    }
+}
+
+/// Pushes apart nodes whose shapes, grown by the padding, overlap. Nodes are treated as circles
+/// of [`Graph::node_extent`], so large nodes and ports are respected. Only nodes in neighboring
+/// cells of a grid sized to the largest reach are compared.
+#[derive(Debug, Default)]
+pub struct Collision;
+
+impl ExtraForce for Collision {
+    type Params = CollisionParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        _area: Rect,
+        _k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.strength == 0.0 {
+            return;
+        }
+        let nodes: Vec<_> = indices
+            .iter()
+            .map(|&idx| {
+                let n = g.node(idx).unwrap();
+                (n.location(), g.node_extent(idx).unwrap_or(0.))
+            })
+            .collect();
+        let cell = nodes.iter().map(|&(_, r)| r).fold(0., f32::max) * 2. + params.padding;
+        if cell <= 0. {
+            return;
+        }
+        let key = |i: usize| {
+            let p = nodes[i].0;
+            ((p.x / cell).floor() as i64, (p.y / cell).floor() as i64)
+        };
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for i in 0..nodes.len() {
+            grid.entry(key(i)).or_default().push(i);
+        }
+
+        for i in 0..nodes.len() {
+            let (cx, cy) = key(i);
+            for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                let Some(cell) = grid.get(&(cx + dx, cy + dy)) else {
+                    continue;
+                };
+                for &j in cell.iter().filter(|&&j| j > i) {
+                    let delta = nodes[j].0 - nodes[i].0;
+                    let dist = delta.length();
+                    // Coincident nodes get a direction of their own so they can separate.
+                    let dir = if dist > f32::EPSILON {
+                        delta / dist
+                    } else {
+                        Vec2::angled(i as f32 + j as f32)
+                    };
+                    let overlap = nodes[i].1 + nodes[j].1 + params.padding - dist;
+                    if overlap <= 0. {
+                        continue;
+                    }
+                    let push = dir * overlap * params.strength * 0.5;
+                    disp[i] -= push;
+                    disp[j] += push;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn overlapping_shapes_are_pushed_apart() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(0., 0.));
+        let b = g.add_node_with_location((), Pos2::new(30., 0.));
+        let c = g.add_node_with_location((), Pos2::new(200., 0.));
+        g.node_mut(a).unwrap().display_mut().radius = 20.;
+        let params = CollisionParams::default();
+        let indices = [a, b, c];
+        let mut disp = vec![Vec2::ZERO; 3];
+
+        Collision::apply(&params, &g, &indices, &mut disp, Rect::ZERO, 1.);
+
+        // Radii 20 and 5 plus padding 5 need 30, the large node already touches.
+        assert_eq!(disp, vec![Vec2::ZERO; 3]);
+
+        g.node_mut(b).unwrap().set_location(Pos2::new(20., 0.));
+        Collision::apply(&params, &g, &indices, &mut disp, Rect::ZERO, 1.);
+        assert_eq!(disp[0], Vec2::new(-5., 0.));
+        assert_eq!(disp[1], Vec2::new(5., 0.));
+        assert_eq!(disp[2], Vec2::ZERO);
+    }
+
+    #[test]
+    fn coincident_nodes_separate() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::ZERO);
+        let b = g.add_node_with_location((), Pos2::ZERO);
+        let mut disp = vec![Vec2::ZERO; 2];
+
+        Collision::apply(
+            &CollisionParams::default(),
+            &g,
+            &[a, b],
+            &mut disp,
+            Rect::ZERO,
+            1.,
+        );
+
+        assert!(disp[0].length() > 0.);
+        assert_eq!(disp[0], -disp[1]);
+    }
+
+    #[test]
+    fn ports_widen_nodes_across_cells() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(-1., 0.));
+        let b = g.add_node_with_location((), Pos2::new(60., 0.));
+        let params = CollisionParams::default();
+        let mut disp = vec![Vec2::ZERO; 2];
+
+        Collision::apply(&params, &g, &[a, b], &mut disp, Rect::ZERO, 1.);
+        assert_eq!(disp, vec![Vec2::ZERO; 2]);
+
+        g.node_mut(a)
+            .unwrap()
+            .add_port(crate::Port::output("out".to_string(), Vec2::new(50., 0.)));
+        Collision::apply(&params, &g, &[a, b], &mut disp, Rect::ZERO, 1.);
+        assert!(disp[0].x < 0.);
+        assert_eq!(disp[0], -disp[1]);
+    }
+}
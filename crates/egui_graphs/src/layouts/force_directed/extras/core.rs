@@ -10,6 +10,7 @@ pub trait ExtraForce: std::fmt::Debug + Default + Send + Sync + 'static {
     type Params: Clone + Default + std::fmt::Debug + Send + Sync + 'static;
 
     /// Apply the extra force: accumulate into `disp` (same convention as base helpers).
+    /// Displacements of pinned nodes are dropped when applied, they still count as sources.
     fn apply<N, E, Ty, Ix, Dn, De>(
         params: &Self::Params,
         g: &Graph<N, E, Ty, Ix, Dn, De>,
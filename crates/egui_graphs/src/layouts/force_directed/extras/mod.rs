@@ -1,8 +1,16 @@
 mod core;
 
+pub mod bounding_box;
 pub mod center_gravity;
+pub mod cluster;
+pub mod collision;
+pub mod radial;
 
+pub use bounding_box::{BoundingBox, BoundingBoxParams};
 pub use center_gravity::{CenterGravity, CenterGravityParams};
+pub use cluster::{ClusterAttraction, ClusterAttractionParams};
+pub use collision::{Collision, CollisionParams};
 #[allow(unused_imports)]
 pub use core::ExtraForce;
 pub use core::{Extra, ExtrasTuple};
+pub use radial::{RadialConstraint, RadialConstraintParams, RadialMetric};
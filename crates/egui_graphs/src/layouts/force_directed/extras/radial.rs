@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::{DisplayEdge, DisplayNode, Graph};
+use petgraph::EdgeType;
+
+/// Ring radius of every node, see [`RadialConstraintParams::metric`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum RadialMetric {
+    /// Every node on the same ring.
+    #[default]
+    Uniform,
+    /// Well connected nodes on inner rings: a node of degree `d` sits at
+    /// `radius * (1 - d / (d_max + 1))`, so isolated nodes stay on the outer ring.
+    Degree,
+    /// Rings by node index as a share of the radius, e.g. from a centrality computed by the
+    /// application: 0 is the center and 1 the outer ring. Nodes without a value get 1.
+    ///
+    /// Keys are [`petgraph::stable_graph::NodeIndex::index`] values, which the graph hands out
+    /// again to nodes added after a removal, so the application must rebuild the map after
+    /// removing nodes or new nodes inherit the rings of removed ones.
+    Custom(HashMap<usize, f32>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RadialConstraintParams {
+    /// Center of the rings in canvas coordinates, None uses the viewport center.
+    pub center: Option<Pos2>,
+    /// Radius of the outer ring.
+    pub radius: f32,
+    /// Pull per unit of distance from the ring of the node.
+    pub strength: f32,
+    pub metric: RadialMetric,
+}
+impl Default for RadialConstraintParams {
+    fn default() -> Self {
+        Self {
+            center: None,
+            radius: 200.,
+            strength: 5.,
+            metric: RadialMetric::Uniform,
+        }
+    }
+}
+
+/// Pulls nodes toward a ring around a center, from inside and outside alike. With a
+/// [`RadialMetric`] other than uniform every node gets a ring of its own.
+#[derive(Debug, Default)]
+pub struct RadialConstraint;
+
+impl ExtraForce for RadialConstraint {
+    type Params = RadialConstraintParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        area: Rect,
+        _k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.strength == 0.0 {
+            return;
+        }
+        let center = params.center.unwrap_or(area.center());
+        let radii: Vec<f32> = match &params.metric {
+            RadialMetric::Uniform => vec![params.radius; indices.len()],
+            RadialMetric::Degree => {
+                let degrees: Vec<usize> = indices
+                    .iter()
+                    .map(|&idx| g.visible_neighbors(idx, None).len())
+                    .collect();
+                let max = degrees.iter().copied().max().unwrap_or(0) as f32;
+                degrees
+                    .iter()
+                    .map(|&d| params.radius * (1. - d as f32 / (max + 1.)))
+                    .collect()
+            }
+            RadialMetric::Custom(shares) => indices
+                .iter()
+                .map(|idx| params.radius * shares.get(&idx.index()).copied().unwrap_or(1.))
+                .collect(),
+        };
+
+        for (vec_pos, &idx) in indices.iter().enumerate() {
+            let delta = g.g().node_weight(idx).unwrap().location() - center;
+            let dist = delta.length();
+            // The direction is undefined in the center, repulsion moves the node off it.
+            if dist <= f32::EPSILON {
+                continue;
+            }
+            disp[vec_pos] += delta / dist * (radii[vec_pos] - dist) * params.strength;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn nodes_are_pulled_onto_the_ring() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let inner = g.add_node_with_location((), Pos2::new(50., 0.));
+        let outer = g.add_node_with_location((), Pos2::new(0., -150.));
+        let on = g.add_node_with_location((), Pos2::new(100., 0.));
+        let params = RadialConstraintParams {
+            center: Some(Pos2::ZERO),
+            radius: 100.,
+            strength: 1.,
+            ..RadialConstraintParams::default()
+        };
+        let mut disp = vec![Vec2::ZERO; 3];
+
+        RadialConstraint::apply(&params, &g, &[inner, outer, on], &mut disp, Rect::ZERO, 1.);
+
+        assert_eq!(disp[0], Vec2::new(50., 0.));
+        assert_eq!(disp[1], Vec2::new(0., 50.));
+        assert_eq!(disp[2], Vec2::ZERO);
+    }
+
+    #[test]
+    fn degree_metric_puts_hubs_inside() {
+        // A star: the hub has degree 3, the leaves 1 and one node is isolated.
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let hub = g.add_node_with_location((), Pos2::new(100., 0.));
+        let leaves: Vec<_> = (0..3)
+            .map(|_| g.add_node_with_location((), Pos2::new(0., 100.)))
+            .collect();
+        for &leaf in &leaves {
+            g.add_edge(hub, leaf, ());
+        }
+        let lone = g.add_node_with_location((), Pos2::new(-100., 0.));
+        let params = RadialConstraintParams {
+            center: Some(Pos2::ZERO),
+            radius: 100.,
+            strength: 1.,
+            metric: RadialMetric::Degree,
+        };
+        let indices = [hub, leaves[0], lone];
+        let mut disp = vec![Vec2::ZERO; 3];
+
+        RadialConstraint::apply(&params, &g, &indices, &mut disp, Rect::ZERO, 1.);
+
+        // Rings at 100 * (1 - d / 4): 25 for the hub, 75 for leaves and 100 when isolated.
+        assert_eq!(disp[0], Vec2::new(-75., 0.));
+        assert_eq!(disp[1], Vec2::new(0., -25.));
+        assert_eq!(disp[2], Vec2::ZERO);
+    }
+
+    #[test]
+    fn custom_metric_places_nodes_by_index() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(100., 0.));
+        let b = g.add_node_with_location((), Pos2::new(0., 100.));
+        let params = RadialConstraintParams {
+            center: Some(Pos2::ZERO),
+            radius: 100.,
+            strength: 1.,
+            metric: RadialMetric::Custom(HashMap::from([(a.index(), 0.25)])),
+        };
+        let mut disp = vec![Vec2::ZERO; 2];
+
+        RadialConstraint::apply(&params, &g, &[a, b], &mut disp, Rect::ZERO, 1.);
+
+        // A goes to the ring at 25, b has no value and stays on the outer ring.
+        assert_eq!(disp[0], Vec2::new(-75., 0.));
+        assert_eq!(disp[1], Vec2::ZERO);
+    }
+}
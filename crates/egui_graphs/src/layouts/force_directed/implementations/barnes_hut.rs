@@ -0,0 +1,287 @@
+use egui::{Pos2, Rect, Vec2};
+
+const NONE: usize = usize::MAX;
+/// Cells stop splitting at this depth so coincident points end up in one leaf.
+const MAX_DEPTH: u32 = 24;
+
+#[derive(Debug, Clone)]
+struct Cell {
+    center: Pos2,
+    half: f32,
+    mass: f32,
+    /// Mass weighted sum of body positions while building, center of mass after
+    /// [`QuadTree::build_weighted`].
+    com: Vec2,
+    /// Index of the first of four consecutive children, `NONE` for leaves.
+    children: usize,
+    /// Head of the linked list of bodies stored in a leaf.
+    first_body: usize,
+}
+
+impl Cell {
+    fn new(center: Pos2, half: f32) -> Self {
+        Self {
+            center,
+            half,
+            mass: 0.,
+            com: Vec2::ZERO,
+            children: NONE,
+            first_body: NONE,
+        }
+    }
+
+    fn quadrant(&self, p: Pos2) -> usize {
+        usize::from(p.x >= self.center.x) | (usize::from(p.y >= self.center.y) << 1)
+    }
+}
+
+/// Quad-tree over node positions approximating far away groups of nodes by their center of
+/// mass, see [`QuadTree::repulsion`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct QuadTree {
+    cells: Vec<Cell>,
+    positions: Vec<Pos2>,
+    masses: Vec<f32>,
+    next_body: Vec<usize>,
+    stack: Vec<usize>,
+}
+
+impl QuadTree {
+    /// Rebuilds the tree for bodies of unit mass reusing allocations.
+    pub(crate) fn build(&mut self, positions: impl IntoIterator<Item = Pos2>) {
+        self.build_weighted(positions.into_iter().map(|p| (p, 1.)));
+    }
+
+    /// Rebuilds the tree for bodies given as position and mass reusing allocations.
+    pub(crate) fn build_weighted(&mut self, bodies: impl IntoIterator<Item = (Pos2, f32)>) {
+        self.cells.clear();
+        self.positions.clear();
+        self.masses.clear();
+        for (pos, mass) in bodies {
+            self.positions.push(pos);
+            self.masses.push(mass);
+        }
+        self.next_body.clear();
+        self.next_body.resize(self.positions.len(), NONE);
+        if self.positions.is_empty() {
+            return;
+        }
+
+        let bounds = Rect::from_points(&self.positions);
+        let half = (bounds.width().max(bounds.height()) / 2.).max(1.);
+        self.cells.push(Cell::new(bounds.center(), half));
+        for body in 0..self.positions.len() {
+            self.insert(body);
+        }
+        for cell in &mut self.cells {
+            if cell.mass > 0. {
+                cell.com /= cell.mass;
+            }
+        }
+    }
+
+    fn insert(&mut self, body: usize) {
+        let (pos, mass) = (self.positions[body], self.masses[body]);
+        let mut cell = 0;
+        let mut depth = 0;
+        loop {
+            self.cells[cell].mass += mass;
+            self.cells[cell].com += pos.to_vec2() * mass;
+
+            if self.cells[cell].children != NONE {
+                cell = self.cells[cell].children + self.cells[cell].quadrant(pos);
+                depth += 1;
+                continue;
+            }
+            if self.cells[cell].first_body == NONE || depth >= MAX_DEPTH {
+                self.next_body[body] = self.cells[cell].first_body;
+                self.cells[cell].first_body = body;
+                return;
+            }
+
+            self.split(cell);
+            cell = self.cells[cell].children + self.cells[cell].quadrant(pos);
+            depth += 1;
+        }
+    }
+
+    /// Turns a leaf into four children and moves its bodies down one level.
+    fn split(&mut self, cell: usize) {
+        let Cell {
+            center,
+            half,
+            first_body,
+            ..
+        } = self.cells[cell];
+        let q = half / 2.;
+        let children = self.cells.len();
+        for (dx, dy) in [(-q, -q), (q, -q), (-q, q), (q, q)] {
+            self.cells.push(Cell::new(center + Vec2::new(dx, dy), q));
+        }
+        self.cells[cell].children = children;
+        self.cells[cell].first_body = NONE;
+
+        let mut body = first_body;
+        while body != NONE {
+            let next = self.next_body[body];
+            let (pos, mass) = (self.positions[body], self.masses[body]);
+            let child = children + self.cells[cell].quadrant(pos);
+            self.cells[child].mass += mass;
+            self.cells[child].com += pos.to_vec2() * mass;
+            self.next_body[body] = self.cells[child].first_body;
+            self.cells[child].first_body = body;
+            body = next;
+        }
+    }
+
+    /// Accumulates the repulsive force `c_repulse * k² / d` acting on every body into `disp`.
+    pub(crate) fn repulsion(
+        &mut self,
+        disp: &mut [Vec2],
+        k: f32,
+        epsilon: f32,
+        c_repulse: f32,
+        theta: f32,
+    ) {
+        let strength = c_repulse * k * k;
+        for (body, d) in disp.iter_mut().enumerate().take(self.positions.len()) {
+            self.visit(body, theta, |delta, mass, _| {
+                let distance = delta.length().max(epsilon);
+                *d += delta / distance * (strength * mass / distance);
+            });
+        }
+    }
+
+    /// Calls `f(delta, mass, other)` for everything acting on `body`, where `delta` points from
+    /// the source to the body. `other` is the index of a single body and None for a cell
+    /// approximated by its center of mass.
+    ///
+    /// A cell of width `s` at distance `d` is approximated when `s / d < theta` unless the body
+    /// lies within it, so the body never acts on itself. `theta = 0` visits every other body
+    /// and matches the exact pairwise sum.
+    pub(crate) fn visit(
+        &mut self,
+        body: usize,
+        theta: f32,
+        mut f: impl FnMut(Vec2, f32, Option<usize>),
+    ) {
+        if self.cells.is_empty() {
+            return;
+        }
+        let pos = self.positions[body];
+        let mut stack = std::mem::take(&mut self.stack);
+        stack.clear();
+        stack.push(0);
+        while let Some(cell) = stack.pop() {
+            let c = &self.cells[cell];
+            if c.mass == 0. {
+                continue;
+            }
+
+            if c.children == NONE {
+                let mut other = c.first_body;
+                while other != NONE {
+                    if other != body {
+                        f(pos - self.positions[other], self.masses[other], Some(other));
+                    }
+                    other = self.next_body[other];
+                }
+                continue;
+            }
+
+            let delta = pos - c.com.to_pos2();
+            let distance = delta.length();
+            let contains = (pos - c.center).abs().max_elem() <= c.half;
+            if !contains && distance > 0. && 2. * c.half < theta * distance {
+                f(delta, c.mass, None);
+            } else {
+                stack.extend(c.children..c.children + 4);
+            }
+        }
+        self.stack = stack;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact(positions: &[Pos2], k: f32, epsilon: f32) -> Vec<Vec2> {
+        let mut disp = vec![Vec2::ZERO; positions.len()];
+        for i in 0..positions.len() {
+            for j in 0..positions.len() {
+                if i != j {
+                    let delta = positions[i] - positions[j];
+                    let distance = delta.length().max(epsilon);
+                    disp[i] += delta / distance * (k * k / distance);
+                }
+            }
+        }
+        disp
+    }
+
+    fn scattered(n: usize) -> Vec<Pos2> {
+        // Deterministic pseudo random points with a few duplicates.
+        let mut seed = 7u32;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+        let mut pts: Vec<_> = (0..n)
+            .map(|_| Pos2::new(next() * 1000., next() * 600.))
+            .collect();
+        pts[1] = pts[0];
+        pts
+    }
+
+    #[test]
+    fn zero_theta_matches_exact_sum() {
+        let positions = scattered(64);
+        let mut tree = QuadTree::default();
+        tree.build(positions.iter().copied());
+        let mut disp = vec![Vec2::ZERO; positions.len()];
+        tree.repulsion(&mut disp, 30., 1e-3, 1., 0.);
+
+        for (a, b) in disp.iter().zip(exact(&positions, 30., 1e-3)) {
+            assert!(
+                (*a - b).length() <= 1e-3 * b.length().max(1.),
+                "{a:?} {b:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn cells_holding_the_body_are_not_approximated() {
+        // A wide theta would approximate the root, which holds the body itself.
+        let positions = [Pos2::new(0., 0.), Pos2::new(1., 0.), Pos2::new(1000., 0.)];
+        let mut tree = QuadTree::default();
+        tree.build(positions);
+        let mut disp = vec![Vec2::ZERO; positions.len()];
+        tree.repulsion(&mut disp, 30., 1e-3, 1., 10.);
+
+        for (a, b) in disp.iter().zip(exact(&positions, 30., 1e-3)) {
+            assert!(
+                (*a - b).length() <= 1e-3 * b.length().max(1.),
+                "{a:?} {b:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn approximation_stays_close_to_exact_sum() {
+        let positions = scattered(500);
+        let mut tree = QuadTree::default();
+        tree.build(positions.iter().copied());
+        let mut disp = vec![Vec2::ZERO; positions.len()];
+        tree.repulsion(&mut disp, 30., 1e-3, 1., 0.5);
+
+        let reference = exact(&positions, 30., 1e-3);
+        let err: f32 = disp
+            .iter()
+            .zip(&reference)
+            .map(|(a, b)| (*a - *b).length())
+            .sum();
+        let total: f32 = reference.iter().map(|v| v.length()).sum();
+        assert!(err / total < 0.02, "relative error {}", err / total);
+    }
+}
@@ -0,0 +1,635 @@
+use std::collections::HashMap;
+
+use egui::{Pos2, Rect, Vec2};
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::super::barnes_hut::QuadTree;
+use super::super::fruchterman_reingold::core::prepare_constants;
+use crate::layouts::force_directed::extras::ExtrasTuple;
+use crate::layouts::layout::AnimatedState;
+use crate::layouts::LayoutState;
+use crate::{node_size, DisplayEdge, DisplayNode, EdgeWeighting, ForceAlgorithm, Graph};
+
+/// Parameters of [`ForceAtlas2`], named after their Gephi counterparts.
+///
+/// Extras from `E` are applied after the base forces exactly as for
+/// [`crate::FruchtermanReingoldWithExtras`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
+pub struct ForceAtlas2State<E: ExtrasTuple = ()> {
+    pub is_running: bool,
+    /// Repulsion strength `kr`. Larger values make the graph sparser.
+    pub scaling_ratio: f32,
+    /// Pull of every node toward the viewport center, weighted by degree.
+    pub gravity: f32,
+    /// Gravity grows with the distance instead of being constant.
+    pub strong_gravity: bool,
+    /// Logarithmic attraction which makes clusters tighter.
+    pub lin_log: bool,
+    /// Divides attraction by the node degree pushing hubs to the periphery. Gephi calls this
+    /// outbound attraction distribution.
+    pub dissuade_hubs: bool,
+    /// Takes node sizes into account so nodes do not overlap.
+    pub prevent_overlap: bool,
+    /// How much swinging is tolerated by the adaptive speed. Higher is faster but less precise.
+    pub jitter_tolerance: f32,
+    /// Approximate repulsion with a Barnes-Hut quad-tree.
+    pub barnes_hut: bool,
+    /// Barnes-Hut accuracy, see [`crate::FruchtermanReingoldState::theta`].
+    pub theta: f32,
+    /// How edge weights scale the attraction, Gephi's edge weight influence.
+    #[serde(default)]
+    pub edge_weighting: EdgeWeighting,
+    #[serde(skip)]
+    pub last_avg_displacement: Option<f32>,
+    /// Total number of simulation steps executed.
+    pub step_count: u64,
+    pub extras: E,
+    #[serde(skip)]
+    pub(crate) speed: AdaptiveSpeed,
+}
+
+impl<E: ExtrasTuple> LayoutState for ForceAtlas2State<E> {}
+
+impl<E: ExtrasTuple> Default for ForceAtlas2State<E> {
+    fn default() -> Self {
+        Self {
+            is_running: true,
+            scaling_ratio: 10.0,
+            gravity: 1.0,
+            strong_gravity: false,
+            lin_log: false,
+            dissuade_hubs: false,
+            prevent_overlap: false,
+            jitter_tolerance: 1.0,
+            barnes_hut: false,
+            theta: 1.2,
+            edge_weighting: EdgeWeighting::default(),
+            last_avg_displacement: None,
+            step_count: 0,
+            extras: E::default(),
+            speed: AdaptiveSpeed::default(),
+        }
+    }
+}
+
+impl<E: ExtrasTuple> AnimatedState for ForceAtlas2State<E> {
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+    fn set_running(&mut self, v: bool) {
+        self.is_running = v;
+    }
+    fn last_avg_displacement(&self) -> Option<f32> {
+        self.last_avg_displacement
+    }
+    fn set_last_avg_displacement(&mut self, v: Option<f32>) {
+        self.last_avg_displacement = v;
+    }
+    fn step_count(&self) -> u64 {
+        self.step_count
+    }
+    fn set_step_count(&mut self, v: u64) {
+        self.step_count = v;
+    }
+}
+
+/// Global speed of the simulation adapted every step from how much nodes swing, i.e. change
+/// the direction of their force, versus how much they move consistently.
+#[derive(Debug, Clone)]
+pub(crate) struct AdaptiveSpeed {
+    speed: f32,
+    efficiency: f32,
+    prev_forces: Vec<Vec2>,
+}
+
+impl Default for AdaptiveSpeed {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            efficiency: 1.,
+            prev_forces: Vec::new(),
+        }
+    }
+}
+
+impl AdaptiveSpeed {
+    /// Updates the speed from the new forces and returns the per node swinging.
+    fn update(&mut self, forces: &[Vec2], masses: &[f32], jitter_tolerance: f32) -> Vec<f32> {
+        if self.prev_forces.len() != forces.len() {
+            *self = Self::default();
+            self.prev_forces.resize(forces.len(), Vec2::ZERO);
+        }
+
+        let mut swinging = Vec::with_capacity(forces.len());
+        let (mut total_swinging, mut total_traction) = (0., 0.);
+        for ((f, prev), m) in forces.iter().zip(&self.prev_forces).zip(masses) {
+            let swing = m * (*prev - *f).length();
+            swinging.push(swing);
+            total_swinging += swing;
+            total_traction += m * 0.5 * (*prev + *f).length();
+        }
+        self.prev_forces.copy_from_slice(forces);
+        if total_swinging <= 0. || total_traction <= 0. {
+            return swinging;
+        }
+
+        let n = forces.len() as f32;
+        let estimated_jitter = 0.05 * n.sqrt();
+        let min_jitter = estimated_jitter.sqrt();
+        let mut jitter = jitter_tolerance
+            * min_jitter.max((estimated_jitter * total_traction / (n * n)).min(10.));
+        let min_efficiency = 0.05;
+        if total_swinging / total_traction > 2. {
+            if self.efficiency > min_efficiency {
+                self.efficiency *= 0.5;
+            }
+            jitter = jitter.max(jitter_tolerance);
+        }
+
+        let target = jitter * self.efficiency * total_traction / total_swinging;
+        if total_swinging > jitter * total_traction {
+            if self.efficiency > min_efficiency {
+                self.efficiency *= 0.7;
+            }
+        } else if self.speed < 1000. {
+            self.efficiency *= 1.3;
+        }
+
+        let max_rise = 0.5;
+        self.speed += (target - self.speed).min(max_rise * self.speed);
+        swinging
+    }
+}
+
+/// ForceAtlas2 by Jacomy et al. as known from Gephi.
+///
+/// Repulsion is weighted by node degree so hubs push harder, attraction is linear or
+/// logarithmic along edges and gravity keeps disconnected components together. Instead of a
+/// fixed step every node moves with a speed adapted to how much it oscillates, which makes the
+/// layout converge without tuning `dt` or damping.
+#[derive(Debug, Default)]
+pub struct ForceAtlas2<E: ExtrasTuple = ()> {
+    state: ForceAtlas2State<E>,
+    // Reusable force buffer
+    scratch_forces: Vec<Vec2>,
+    // Reusable Barnes-Hut tree
+    scratch_tree: QuadTree,
+}
+
+impl<E: ExtrasTuple> ForceAtlas2<E> {
+    pub fn from_state(state: ForceAtlas2State<E>) -> Self {
+        Self {
+            state,
+            scratch_forces: Vec::new(),
+            scratch_tree: QuadTree::default(),
+        }
+    }
+}
+
+impl<E: ExtrasTuple> ForceAlgorithm for ForceAtlas2<E> {
+    type State = ForceAtlas2State<E>;
+
+    fn from_state(state: Self::State) -> Self {
+        Self::from_state(state)
+    }
+
+    fn step<N, Ed, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, Ed, Ty, Ix, Dn, De>, view: Rect)
+    where
+        N: Clone,
+        Ed: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, Ed, Ty, Ix>,
+        De: DisplayEdge<N, Ed, Ty, Ix, Dn>,
+    {
+        if !self.state.is_running || g.node_count() == 0 {
+            return;
+        }
+
+        // Members of collapsed groups are represented by the group node.
+        let indices: Vec<_> = g.visible_node_indices().collect();
+        let bodies = Bodies::new(g, &indices, self.state.prevent_overlap);
+
+        self.scratch_forces.clear();
+        self.scratch_forces.resize(indices.len(), Vec2::ZERO);
+        let st = &self.state;
+        if st.barnes_hut {
+            repulsion_barnes_hut(
+                &bodies,
+                &mut self.scratch_forces,
+                &mut self.scratch_tree,
+                st,
+            );
+        } else {
+            repulsion(&bodies, &mut self.scratch_forces, st);
+        }
+        gravity(&bodies, &mut self.scratch_forces, view.center(), st);
+        attraction(g, &indices, &bodies, &mut self.scratch_forces, st);
+
+        if let Some(k) = prepare_constants(view, indices.len(), 1.0) {
+            st.extras
+                .apply_all(g, &indices, &mut self.scratch_forces, view, k);
+        }
+
+        let swinging = self.state.speed.update(
+            &self.scratch_forces,
+            &bodies.masses,
+            self.state.jitter_tolerance,
+        );
+        let avg = apply_forces(
+            g,
+            &indices,
+            &self.scratch_forces,
+            &swinging,
+            self.state.speed.speed,
+            self.state.prevent_overlap,
+        );
+        self.state.last_avg_displacement = avg;
+        self.state.set_step_count(self.state.step_count + 1);
+    }
+
+    fn state(&self) -> Self::State {
+        self.state.clone()
+    }
+}
+
+/// Per step snapshot of the visible nodes in the order of the indices.
+struct Bodies<Ix: IndexType> {
+    positions: Vec<Pos2>,
+    /// Degree plus one.
+    masses: Vec<f32>,
+    /// Node radii when overlap is prevented, zeros otherwise.
+    radii: Vec<f32>,
+    lookup: HashMap<NodeIndex<Ix>, usize>,
+}
+
+impl<Ix: IndexType> Bodies<Ix> {
+    fn new<N, E, Ty, Dn, De>(
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[NodeIndex<Ix>],
+        sizes: bool,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let mut bodies = Self {
+            positions: Vec::with_capacity(indices.len()),
+            masses: Vec::with_capacity(indices.len()),
+            radii: Vec::with_capacity(indices.len()),
+            lookup: HashMap::with_capacity(indices.len()),
+        };
+        for (vec_pos, &idx) in indices.iter().enumerate() {
+            let node = g.node(idx).unwrap();
+            bodies.positions.push(node.location());
+            bodies
+                .masses
+                .push(g.visible_neighbors(idx, None).len() as f32 + 1.);
+            bodies
+                .radii
+                .push(if sizes { node_size(node, Vec2::X) } else { 0. });
+            bodies.lookup.insert(idx, vec_pos);
+        }
+        bodies
+    }
+}
+
+/// Repulsion between two bodies with the product of their masses `mm` along `delta`, where
+/// `overlap` is the sum of their radii.
+fn repulse(delta: Vec2, mm: f32, overlap: f32, scaling_ratio: f32) -> Vec2 {
+    let distance = delta.length();
+    if distance <= 0. {
+        return Vec2::ZERO;
+    }
+    let dir = delta / distance;
+    let gap = distance - overlap;
+    if gap > 0. {
+        dir * (scaling_ratio * mm / gap)
+    } else {
+        // Overlapping nodes push each other away strongly.
+        dir * (100. * scaling_ratio * mm)
+    }
+}
+
+fn repulsion<Ix: IndexType, E: ExtrasTuple>(
+    bodies: &Bodies<Ix>,
+    forces: &mut [Vec2],
+    st: &ForceAtlas2State<E>,
+) {
+    let n = bodies.positions.len();
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let f = repulse(
+                bodies.positions[i] - bodies.positions[j],
+                bodies.masses[i] * bodies.masses[j],
+                bodies.radii[i] + bodies.radii[j],
+                st.scaling_ratio,
+            );
+            forces[i] += f;
+            forces[j] -= f;
+        }
+    }
+}
+
+fn repulsion_barnes_hut<Ix: IndexType, E: ExtrasTuple>(
+    bodies: &Bodies<Ix>,
+    forces: &mut [Vec2],
+    tree: &mut QuadTree,
+    st: &ForceAtlas2State<E>,
+) {
+    tree.build_weighted(
+        bodies
+            .positions
+            .iter()
+            .copied()
+            .zip(bodies.masses.iter().copied()),
+    );
+    for (i, f) in forces.iter_mut().enumerate() {
+        let (mass, radius) = (bodies.masses[i], bodies.radii[i]);
+        tree.visit(i, st.theta, |delta, other_mass, other| {
+            // Sizes only matter for single nodes, far away cells never overlap.
+            let overlap = other.map_or(0., |j| radius + bodies.radii[j]);
+            *f += repulse(delta, mass * other_mass, overlap, st.scaling_ratio);
+        });
+    }
+}
+
+fn gravity<Ix: IndexType, E: ExtrasTuple>(
+    bodies: &Bodies<Ix>,
+    forces: &mut [Vec2],
+    center: Pos2,
+    st: &ForceAtlas2State<E>,
+) {
+    if st.gravity == 0. {
+        return;
+    }
+    for (i, f) in forces.iter_mut().enumerate() {
+        let delta = center - bodies.positions[i];
+        let distance = delta.length();
+        if distance <= 0. {
+            continue;
+        }
+        let strength = st.gravity * bodies.masses[i];
+        *f += if st.strong_gravity {
+            delta * strength
+        } else {
+            delta / distance * strength
+        };
+    }
+}
+
+fn attraction<N, Ed, Ty, Ix, Dn, De, E>(
+    g: &Graph<N, Ed, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+    bodies: &Bodies<Ix>,
+    forces: &mut [Vec2],
+    st: &ForceAtlas2State<E>,
+) where
+    N: Clone,
+    Ed: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, Ed, Ty, Ix>,
+    De: DisplayEdge<N, Ed, Ty, Ix, Dn>,
+    E: ExtrasTuple,
+{
+    // Keeps the overall attraction comparable when it is divided by the degree.
+    let compensation = if st.dissuade_hubs {
+        bodies.masses.iter().sum::<f32>() / bodies.masses.len() as f32
+    } else {
+        1.
+    };
+    for (i, &idx) in indices.iter().enumerate() {
+        for (edge, nbr) in g.visible_neighbor_edges(idx, None) {
+            let Some(&j) = bodies.lookup.get(&nbr) else {
+                continue;
+            };
+            let delta = bodies.positions[j] - bodies.positions[i];
+            let distance = delta.length();
+            let gap = distance - bodies.radii[i] - bodies.radii[j];
+            if distance <= 0. || gap <= 0. {
+                continue;
+            }
+            let mut strength = if st.lin_log { gap.ln_1p() } else { gap };
+            if st.dissuade_hubs {
+                strength *= compensation / bodies.masses[i];
+            }
+            if st.edge_weighting != EdgeWeighting::Ignore {
+                strength *= st.edge_weighting.factor(g.edge_weight(edge).unwrap_or(1.));
+            }
+            forces[i] += delta / distance * strength;
+        }
+    }
+}
+
+fn apply_forces<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+    forces: &[Vec2],
+    swinging: &[f32],
+    speed: f32,
+    prevent_overlap: bool,
+) -> Option<f32>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut sum = 0.0f32;
+    let mut count = 0usize;
+    for (i, &idx) in indices.iter().enumerate() {
+        if g.is_node_pinned(idx) {
+            continue;
+        }
+        let force = forces[i];
+        let len = force.length();
+        let mut factor = speed / (1. + (speed * swinging[i]).sqrt());
+        if prevent_overlap && len > 0. {
+            // Slower and bounded steps so overlaps are resolved instead of jumped over.
+            factor = (0.1 * factor * len).min(10.) / len;
+        }
+        let step = force * factor;
+        let new_loc = g.node(idx).unwrap().location() + step;
+        if !new_loc.x.is_finite() || !new_loc.y.is_finite() {
+            continue;
+        }
+        g.node_mut(idx).unwrap().set_location(new_loc);
+        sum += step.length();
+        count += 1;
+    }
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+    use crate::{to_graph, CenterGravity, DefaultEdgeShape, DefaultNodeShape, Extra};
+
+    type G = Graph<
+        (),
+        (),
+        petgraph::Undirected,
+        petgraph::stable_graph::DefaultIx,
+        DefaultNodeShape,
+        DefaultEdgeShape,
+    >;
+
+    fn view() -> Rect {
+        Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1000.0, 1000.0))
+    }
+
+    /// Two cliques of five nodes joined by a single edge, placed on a grid.
+    fn two_cliques() -> G {
+        let mut sg: StableGraph<(), (), petgraph::Undirected> = StableGraph::default();
+        let nodes: Vec<_> = (0..10).map(|_| sg.add_node(())).collect();
+        for c in [0, 5] {
+            for i in c..c + 5 {
+                for j in (i + 1)..c + 5 {
+                    sg.add_edge(nodes[i], nodes[j], ());
+                }
+            }
+        }
+        sg.add_edge(nodes[0], nodes[5], ());
+        let mut g: G = to_graph(&sg);
+        for (i, idx) in nodes.iter().enumerate() {
+            // Interleave the cliques so the layout has to separate them.
+            let loc = Pos2::new((i % 4) as f32 * 30.0 + 400.0, (i / 4) as f32 * 30.0 + 400.0);
+            g.node_mut(*idx).unwrap().set_location(loc);
+        }
+        g
+    }
+
+    fn positions(g: &G) -> Vec<Pos2> {
+        g.g().node_indices().map(|i| g.g()[i].location()).collect()
+    }
+
+    fn centroid(pts: &[Pos2]) -> Pos2 {
+        let sum = pts.iter().fold(Vec2::ZERO, |acc, p| acc + p.to_vec2());
+        (sum / pts.len() as f32).to_pos2()
+    }
+
+    fn spread(pts: &[Pos2]) -> f32 {
+        let c = centroid(pts);
+        pts.iter().map(|p| (*p - c).length()).sum::<f32>() / pts.len() as f32
+    }
+
+    #[test]
+    fn separates_clusters() {
+        let mut g = two_cliques();
+        let mut alg = ForceAtlas2::<()>::from_state(ForceAtlas2State::default());
+        for _ in 0..300 {
+            alg.step(&mut g, view());
+        }
+
+        let pts = positions(&g);
+        let (a, b) = pts.split_at(5);
+        let between = (centroid(a) - centroid(b)).length();
+        assert!(between > spread(a) && between > spread(b), "{pts:?}");
+        assert!(pts.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+        assert_eq!(alg.state().step_count(), 300);
+        assert!(alg.state().last_avg_displacement().unwrap() < 1.0);
+    }
+
+    #[test]
+    fn prevent_overlap_keeps_nodes_apart() {
+        let mut g = two_cliques();
+        let st = ForceAtlas2State::<()> {
+            prevent_overlap: true,
+            scaling_ratio: 1.0,
+            lin_log: true,
+            ..ForceAtlas2State::default()
+        };
+        let mut alg = ForceAtlas2::from_state(st);
+        for _ in 0..500 {
+            alg.step(&mut g, view());
+        }
+
+        let radius = node_size(g.g().node_weights().next().unwrap(), Vec2::X);
+        let pts = positions(&g);
+        for (i, a) in pts.iter().enumerate() {
+            for b in &pts[i + 1..] {
+                assert!((*a - *b).length() >= 2. * radius * 0.9, "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn pinned_nodes_are_left_untouched() {
+        let mut g = two_cliques();
+        let indices: Vec<_> = g.visible_node_indices().collect();
+        for &idx in &indices {
+            g.set_node_pinned(idx, true);
+        }
+        let before = (positions(&g), g.revision());
+        let forces = vec![Vec2::new(3., 4.); indices.len()];
+        let swinging = vec![0.; indices.len()];
+
+        assert_eq!(
+            apply_forces(&mut g, &indices, &forces, &swinging, 1., false),
+            None
+        );
+        assert_eq!((positions(&g), g.revision()), before);
+    }
+
+    #[test]
+    fn barnes_hut_matches_exact_repulsion() {
+        let g = two_cliques();
+        let indices: Vec<_> = g.visible_node_indices().collect();
+        let bodies = Bodies::new(&g, &indices, false);
+        let st = ForceAtlas2State::<()> {
+            theta: 0.,
+            ..ForceAtlas2State::default()
+        };
+
+        let mut exact = vec![Vec2::ZERO; indices.len()];
+        repulsion(&bodies, &mut exact, &st);
+        let mut approx = vec![Vec2::ZERO; indices.len()];
+        repulsion_barnes_hut(&bodies, &mut approx, &mut QuadTree::default(), &st);
+
+        for (a, b) in exact.iter().zip(&approx) {
+            assert!(
+                (*a - *b).length() <= 1e-3 * a.length().max(1.),
+                "{a:?} {b:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn applies_extras() {
+        type Extras = (Extra<CenterGravity, true>, ());
+        let mut g = two_cliques();
+        let mut plain = g.clone();
+        let st = ForceAtlas2State::<Extras> {
+            gravity: 0.,
+            ..ForceAtlas2State::default()
+        };
+        let mut with_extra = ForceAtlas2::from_state(st);
+        let mut without = ForceAtlas2::<()>::from_state(ForceAtlas2State {
+            gravity: 0.,
+            ..ForceAtlas2State::default()
+        });
+        for _ in 0..50 {
+            with_extra.step(&mut g, view());
+            without.step(&mut plain, view());
+        }
+
+        let center = view().center();
+        let dist = |pts: &[Pos2]| (centroid(pts) - center).length();
+        assert!(dist(&positions(&g)) < dist(&positions(&plain)));
+    }
+}
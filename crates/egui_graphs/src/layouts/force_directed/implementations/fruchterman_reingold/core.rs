@@ -1,8 +1,9 @@
-use crate::{DisplayEdge, DisplayNode, ForceAlgorithm, Graph};
+use crate::{DisplayEdge, DisplayNode, EdgeWeighting, ForceAlgorithm, Graph};
 use egui::{Rect, Vec2};
 use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
 use serde::{Deserialize, Serialize};
 
+use super::super::barnes_hut::QuadTree;
 use crate::layouts::layout::AnimatedState;
 use crate::layouts::LayoutState;
 
@@ -16,6 +17,16 @@ pub struct FruchtermanReingoldState {
     pub k_scale: f32,
     pub c_attract: f32,
     pub c_repulse: f32,
+    /// Approximate repulsion with a Barnes-Hut quad-tree, O(n log n) instead of O(n²) per step.
+    #[serde(default)]
+    pub barnes_hut: bool,
+    /// Barnes-Hut accuracy: a group of nodes is treated as one body when its cell width divided
+    /// by the distance is below `theta`. Lower is more exact, `0` matches the exact version.
+    #[serde(default = "default_theta")]
+    pub theta: f32,
+    /// How edge weights scale the attraction, see [`Graph::edge_weight`].
+    #[serde(default)]
+    pub edge_weighting: EdgeWeighting,
     #[serde(skip)]
     pub last_avg_displacement: Option<f32>,
     /// Total number of simulation steps executed.
@@ -24,6 +35,10 @@ pub struct FruchtermanReingoldState {
 
 impl LayoutState for FruchtermanReingoldState {}
 
+fn default_theta() -> f32 {
+    0.8
+}
+
 impl Default for FruchtermanReingoldState {
     fn default() -> Self {
         FruchtermanReingoldState {
@@ -35,6 +50,9 @@ impl Default for FruchtermanReingoldState {
             k_scale: 1.0,
             c_attract: 1.0,
             c_repulse: 1.0,
+            barnes_hut: false,
+            theta: default_theta(),
+            edge_weighting: EdgeWeighting::default(),
             last_avg_displacement: None,
             step_count: 0,
         }
@@ -63,10 +81,26 @@ impl FruchtermanReingoldState {
             k_scale,
             c_attract,
             c_repulse,
+            barnes_hut: false,
+            theta: default_theta(),
+            edge_weighting: EdgeWeighting::default(),
             last_avg_displacement: None,
             step_count: 0,
         }
     }
+
+    /// Switches repulsion to the Barnes-Hut approximation with the given `theta`.
+    pub fn with_barnes_hut(mut self, theta: f32) -> Self {
+        self.barnes_hut = true;
+        self.theta = theta;
+        self
+    }
+
+    /// Scales the attraction along edges by their weights as given by `weighting`.
+    pub fn with_edge_weighting(mut self, weighting: EdgeWeighting) -> Self {
+        self.edge_weighting = weighting;
+        self
+    }
 }
 
 // Step counting is provided via AnimatedState default methods and field in this state.
@@ -97,6 +131,8 @@ pub struct FruchtermanReingold {
     state: FruchtermanReingoldState,
     // Reusable displacement buffer to avoid per-frame allocations
     scratch_disp: Vec<Vec2>,
+    // Reusable Barnes-Hut tree
+    scratch_tree: QuadTree,
 }
 
 impl FruchtermanReingold {
@@ -104,6 +140,7 @@ impl FruchtermanReingold {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 }
@@ -115,6 +152,7 @@ impl ForceAlgorithm for FruchtermanReingold {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 
@@ -132,12 +170,13 @@ impl ForceAlgorithm for FruchtermanReingold {
         }
 
         let params = &self.state;
+        // Members of collapsed groups are represented by the group node.
+        let indices: Vec<_> = g.visible_node_indices().collect();
         // Always compute k from the viewport area for stability and simplicity.
-        let Some(k) = prepare_constants(view, g.node_count(), params.k_scale) else {
+        let Some(k) = prepare_constants(view, indices.len(), params.k_scale) else {
             return;
         };
 
-        let indices: Vec<_> = g.g().node_indices().collect();
         // Ensure scratch buffer is sized and zeroed
         if self.scratch_disp.len() == indices.len() {
             self.scratch_disp.fill(Vec2::ZERO);
@@ -145,13 +184,13 @@ impl ForceAlgorithm for FruchtermanReingold {
             self.scratch_disp.resize(indices.len(), Vec2::ZERO);
         }
 
-        compute_repulsion(
+        apply_repulsion(
             g,
             &indices,
             &mut self.scratch_disp,
+            &mut self.scratch_tree,
             k,
-            params.epsilon,
-            params.c_repulse,
+            params,
         );
         compute_attraction(
             g,
@@ -160,6 +199,7 @@ impl ForceAlgorithm for FruchtermanReingold {
             k,
             params.epsilon,
             params.c_attract,
+            params.edge_weighting,
         );
         let avg = apply_displacements(
             g,
@@ -221,6 +261,64 @@ pub(crate) fn compute_repulsion<N, E, Ty, Ix, Dn, De>(
     }
 }
 
+/// Exact or Barnes-Hut repulsion depending on the state.
+pub(crate) fn apply_repulsion<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+    disp: &mut [Vec2],
+    tree: &mut QuadTree,
+    k: f32,
+    params: &FruchtermanReingoldState,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    if params.barnes_hut {
+        compute_repulsion_barnes_hut(
+            g,
+            indices,
+            disp,
+            tree,
+            k,
+            params.epsilon,
+            params.c_repulse,
+            params.theta,
+        );
+    } else {
+        compute_repulsion(g, indices, disp, k, params.epsilon, params.c_repulse);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn compute_repulsion_barnes_hut<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+    disp: &mut [Vec2],
+    tree: &mut QuadTree,
+    k: f32,
+    epsilon: f32,
+    c_repulse: f32,
+    theta: f32,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    tree.build(
+        indices
+            .iter()
+            .map(|&idx| g.g().node_weight(idx).unwrap().location()),
+    );
+    tree.repulsion(disp, k, epsilon, c_repulse, theta);
+}
+
 pub(crate) fn compute_attraction<N, E, Ty, Ix, Dn, De>(
     g: &Graph<N, E, Ty, Ix, Dn, De>,
     indices: &[NodeIndex<Ix>],
@@ -228,6 +326,7 @@ pub(crate) fn compute_attraction<N, E, Ty, Ix, Dn, De>(
     k: f32,
     epsilon: f32,
     c_attract: f32,
+    weighting: EdgeWeighting,
 ) where
     N: Clone,
     E: Clone,
@@ -238,10 +337,13 @@ pub(crate) fn compute_attraction<N, E, Ty, Ix, Dn, De>(
 {
     for (vec_pos, &idx) in indices.iter().enumerate() {
         let loc = g.g().node_weight(idx).unwrap().location();
-        for nbr in g.g().neighbors_undirected(idx) {
+        for (edge, nbr) in g.visible_neighbor_edges(idx, None) {
             let delta = g.g().node_weight(nbr).unwrap().location() - loc;
             let distance = delta.length().max(epsilon);
-            let force = c_attract * (distance * distance) / k;
+            let mut force = c_attract * (distance * distance) / k;
+            if weighting != EdgeWeighting::Ignore {
+                force *= weighting.factor(g.edge_weight(edge).unwrap_or(1.));
+            }
             disp[vec_pos] += (delta / distance) * force;
         }
     }
@@ -269,6 +371,9 @@ where
     let mut sum = 0.0f32;
     let mut count = 0usize;
     for (vec_pos, &idx) in indices.iter().enumerate() {
+        if g.is_node_pinned(idx) {
+            continue;
+        }
         let mut step = disp[vec_pos] * dt * damping;
         let len = step.length();
         if len > max_step {
@@ -279,10 +384,7 @@ where
         if !new_loc.x.is_finite() || !new_loc.y.is_finite() {
             continue;
         }
-        g.g_mut()
-            .node_weight_mut(idx)
-            .unwrap()
-            .set_location(new_loc);
+        g.node_mut(idx).unwrap().set_location(new_loc);
         sum += len.min(max_step);
         count += 1;
     }
@@ -362,6 +464,18 @@ mod tests {
         assert!((b.x - a.x).abs() > 1.0, "Nodes should move apart");
     }
 
+    #[test]
+    fn pinned_nodes_are_not_displaced() {
+        let mut g = make_graph(2);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        g.set_node_pinned(indices[0], true);
+        let disp = vec![Vec2::new(5.0, 0.0); 2];
+        let avg = apply_displacements(&mut g, &indices, &disp, 1.0, 1.0, 10.0);
+        assert_eq!(g.node(indices[0]).unwrap().location(), Pos2::new(0.0, 0.0));
+        assert_eq!(g.node(indices[1]).unwrap().location(), Pos2::new(15.0, 0.0));
+        assert_eq!(avg, Some(5.0));
+    }
+
     #[test]
     fn attraction_decreases_distance_when_far() {
         let mut g = make_graph(2);
@@ -382,7 +496,15 @@ mod tests {
         let mut disp = vec![Vec2::ZERO; indices.len()];
         let start_dist = 1200.0;
         compute_repulsion(&g, &indices, &mut disp, k, params.epsilon, params.c_repulse);
-        compute_attraction(&g, &indices, &mut disp, k, params.epsilon, params.c_attract);
+        compute_attraction(
+            &g,
+            &indices,
+            &mut disp,
+            k,
+            params.epsilon,
+            params.c_attract,
+            params.edge_weighting,
+        );
         apply_displacements(
             &mut g,
             &indices,
@@ -399,4 +521,79 @@ mod tests {
             "Distance should shrink due to attraction"
         );
     }
+
+    #[test]
+    fn edge_weights_scale_attraction() {
+        let mut g = make_graph(3);
+        let idxs: Vec<_> = g.g().node_indices().collect();
+        for (idx, x) in idxs.iter().zip([0.0, 100.0, -100.0]) {
+            g.node_mut(*idx).unwrap().set_location(Pos2::new(x, 0.0));
+        }
+        let heavy = g.add_edge(idxs[0], idxs[1], ());
+        g.add_edge(idxs[0], idxs[2], ());
+        g.edge_mut(heavy).unwrap().set_weight(Some(4.0));
+
+        let pull = |g: &Graph, weighting| {
+            let mut disp = vec![Vec2::ZERO; 3];
+            compute_attraction(g, &idxs, &mut disp, 10.0, 1e-3, 1.0, weighting);
+            (disp[1].length(), disp[2].length())
+        };
+        let (a, b) = pull(&g, EdgeWeighting::Ignore);
+        assert_eq!(a, b);
+        let (a, b) = pull(&g, EdgeWeighting::Strength);
+        assert_eq!(a, 4.0 * b);
+        let (a, b) = pull(&g, EdgeWeighting::Length);
+        assert_eq!(a, b / 4.0);
+
+        // A weight function takes precedence over the weight stored on the edge.
+        g.set_edge_weight_fn(|_| 1.0);
+        let (a, b) = pull(&g, EdgeWeighting::Strength);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn barnes_hut_steps_stay_close_to_exact() {
+        let mut exact = make_graph(300);
+        let idxs: Vec<_> = exact.g().node_indices().collect();
+        for (i, idx) in idxs.iter().enumerate() {
+            let loc = Pos2::new(
+                (i % 20) as f32 * 40.0,
+                (i / 20) as f32 * 30.0 + i as f32 % 7.0,
+            );
+            exact
+                .g_mut()
+                .node_weight_mut(*idx)
+                .unwrap()
+                .set_location(loc);
+            if i > 0 {
+                exact.add_edge(idxs[i - 1], *idx, ());
+            }
+        }
+        let mut approx = exact.clone();
+
+        let rect = empty_ui_rect();
+        let mut alg_exact = FruchtermanReingold::from_state(FruchtermanReingoldState::default());
+        let mut alg_approx = FruchtermanReingold::from_state(
+            FruchtermanReingoldState::default().with_barnes_hut(0.5),
+        );
+        for _ in 0..10 {
+            alg_exact.step(&mut exact, rect);
+            alg_approx.step(&mut approx, rect);
+        }
+
+        let devs: Vec<f32> = idxs
+            .iter()
+            .map(|idx| {
+                let a = exact.g().node_weight(*idx).unwrap().location();
+                let b = approx.g().node_weight(*idx).unwrap().location();
+                (a - b).length()
+            })
+            .collect();
+        let mean_dev = devs.iter().sum::<f32>() / devs.len() as f32;
+        let max_dev = devs.iter().copied().fold(0.0f32, f32::max);
+        // Each step moves a node by up to max_step, so 10 steps move it up to 100.
+        assert!(mean_dev < 0.5, "mean deviation {mean_dev}");
+        assert!(max_dev < 10.0, "max deviation {max_dev}");
+        assert_eq!(alg_approx.state().step_count, 10);
+    }
 }
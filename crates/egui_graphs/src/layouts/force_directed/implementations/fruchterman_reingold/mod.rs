@@ -1,4 +1,4 @@
-mod core;
+pub(crate) mod core;
 
 pub mod with_extras;
 
@@ -1,13 +1,14 @@
 use crate::{
-    layouts::force_directed::extras::ExtrasTuple, CenterGravity, DisplayEdge, DisplayNode, Extra,
-    ForceAlgorithm, Graph,
+    layouts::force_directed::extras::ExtrasTuple, BoundingBox, CenterGravity, ClusterAttraction,
+    Collision, DisplayEdge, DisplayNode, Extra, ForceAlgorithm, Graph, RadialConstraint,
 };
 use egui::{Rect, Vec2};
 use petgraph::EdgeType;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use super::super::barnes_hut::QuadTree;
 use super::core::{
-    apply_displacements, compute_attraction, compute_repulsion, prepare_constants,
+    apply_displacements, apply_repulsion, compute_attraction, prepare_constants,
     FruchtermanReingoldState,
 };
 use crate::layouts::layout::AnimatedState;
@@ -47,6 +48,8 @@ pub struct FruchtermanReingoldWithExtras<E: ExtrasTuple> {
     state: FruchtermanReingoldWithExtrasState<E>,
     // Reusable displacement buffer
     scratch_disp: Vec<Vec2>,
+    // Reusable Barnes-Hut tree
+    scratch_tree: QuadTree,
 }
 
 impl<E: ExtrasTuple> FruchtermanReingoldWithExtras<E> {
@@ -54,6 +57,7 @@ impl<E: ExtrasTuple> FruchtermanReingoldWithExtras<E> {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 }
@@ -65,6 +69,7 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 
@@ -82,24 +87,25 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
         }
         let base = &self.state.base;
         let area_rect = view;
-        let Some(k) = prepare_constants(view, g.node_count(), base.k_scale) else {
+        // Members of collapsed groups are represented by the group node.
+        let indices: Vec<_> = g.visible_node_indices().collect();
+        let Some(k) = prepare_constants(view, indices.len(), base.k_scale) else {
             return;
         };
 
-        let indices: Vec<_> = g.g().node_indices().collect();
         if self.scratch_disp.len() == indices.len() {
             self.scratch_disp.fill(Vec2::ZERO);
         } else {
             self.scratch_disp.resize(indices.len(), Vec2::ZERO);
         }
 
-        compute_repulsion(
+        apply_repulsion(
             g,
             &indices,
             &mut self.scratch_disp,
+            &mut self.scratch_tree,
             k,
-            base.epsilon,
-            base.c_repulse,
+            base,
         );
         compute_attraction(
             g,
@@ -108,6 +114,7 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
             k,
             base.epsilon,
             base.c_attract,
+            base.edge_weighting,
         );
 
         self.state
@@ -138,3 +145,65 @@ pub type FruchtermanReingoldWithCenterGravity =
     FruchtermanReingoldWithExtras<(Extra<CenterGravity, true>, ())>;
 pub type FruchtermanReingoldWithCenterGravityState =
     FruchtermanReingoldWithExtrasState<(Extra<CenterGravity, true>, ())>;
+
+/// Every built-in extra with only center gravity enabled by default. Layout specs of
+/// `io::json` and the demo use this set.
+pub type BuiltinExtras = (
+    Extra<CenterGravity, true>,
+    (
+        Extra<Collision, false>,
+        (
+            Extra<BoundingBox, false>,
+            (
+                Extra<RadialConstraint, false>,
+                (Extra<ClusterAttraction, false>, ()),
+            ),
+        ),
+    ),
+);
+pub type FruchtermanReingoldWithBuiltinExtras = FruchtermanReingoldWithExtras<BuiltinExtras>;
+pub type FruchtermanReingoldWithBuiltinExtrasState =
+    FruchtermanReingoldWithExtrasState<BuiltinExtras>;
+
+/// Named access to the members of [`BuiltinExtras`].
+impl FruchtermanReingoldWithBuiltinExtrasState {
+    pub fn center_gravity(&self) -> &Extra<CenterGravity, true> {
+        &self.extras.0
+    }
+
+    pub fn center_gravity_mut(&mut self) -> &mut Extra<CenterGravity, true> {
+        &mut self.extras.0
+    }
+
+    pub fn collision(&self) -> &Extra<Collision, false> {
+        &self.extras.1 .0
+    }
+
+    pub fn collision_mut(&mut self) -> &mut Extra<Collision, false> {
+        &mut self.extras.1 .0
+    }
+
+    pub fn bounding_box(&self) -> &Extra<BoundingBox, false> {
+        &self.extras.1 .1 .0
+    }
+
+    pub fn bounding_box_mut(&mut self) -> &mut Extra<BoundingBox, false> {
+        &mut self.extras.1 .1 .0
+    }
+
+    pub fn radial_constraint(&self) -> &Extra<RadialConstraint, false> {
+        &self.extras.1 .1 .1 .0
+    }
+
+    pub fn radial_constraint_mut(&mut self) -> &mut Extra<RadialConstraint, false> {
+        &mut self.extras.1 .1 .1 .0
+    }
+
+    pub fn cluster_attraction(&self) -> &Extra<ClusterAttraction, false> {
+        &self.extras.1 .1 .1 .1 .0
+    }
+
+    pub fn cluster_attraction_mut(&mut self) -> &mut Extra<ClusterAttraction, false> {
+        &mut self.extras.1 .1 .1 .1 .0
+    }
+}
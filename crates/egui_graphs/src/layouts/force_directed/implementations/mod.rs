@@ -1 +1,3 @@
+mod barnes_hut;
+pub mod force_atlas2;
 pub mod fruchterman_reingold;
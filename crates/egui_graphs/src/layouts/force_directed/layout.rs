@@ -29,6 +29,7 @@ impl<A: ForceAlgorithm> Layout<A::State> for ForceDirected<A> {
             return;
         }
 
+        g.clear_edge_bends();
         self.alg.step(g, ui.ctx().content_rect());
     }
 
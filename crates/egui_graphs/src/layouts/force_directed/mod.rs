@@ -1,14 +1,26 @@
 mod algorithm;
+#[cfg(feature = "background")]
+mod background;
 mod implementations;
 mod layout;
+mod weighting;
 
 pub mod extras;
 
 pub use algorithm::ForceAlgorithm;
-pub use extras::{CenterGravity, CenterGravityParams, Extra};
+#[cfg(feature = "background")]
+pub use background::{BackgroundLayout, SyncLayout};
+pub use extras::{
+    BoundingBox, BoundingBoxParams, CenterGravity, CenterGravityParams, ClusterAttraction,
+    ClusterAttractionParams, Collision, CollisionParams, Extra, RadialConstraint,
+    RadialConstraintParams, RadialMetric,
+};
+pub use implementations::force_atlas2::{ForceAtlas2, ForceAtlas2State};
 pub use implementations::fruchterman_reingold::with_extras::{
+    BuiltinExtras, FruchtermanReingoldWithBuiltinExtras, FruchtermanReingoldWithBuiltinExtrasState,
     FruchtermanReingoldWithCenterGravity, FruchtermanReingoldWithCenterGravityState,
     FruchtermanReingoldWithExtras, FruchtermanReingoldWithExtrasState,
 };
 pub use implementations::fruchterman_reingold::{FruchtermanReingold, FruchtermanReingoldState};
 pub use layout::ForceDirected;
+pub use weighting::EdgeWeighting;
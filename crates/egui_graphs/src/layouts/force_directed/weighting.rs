@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// How force-directed algorithms use [`crate::Graph::edge_weight`] for the attraction along
+/// an edge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeWeighting {
+    /// Every edge pulls alike.
+    #[default]
+    Ignore,
+    /// Attraction is multiplied by the weight, strongly connected nodes cluster together.
+    Strength,
+    /// Attraction is divided by the weight so heavy edges settle longer, matching how
+    /// [`crate::LayoutStress`] reads weights as lengths.
+    Length,
+}
+
+impl EdgeWeighting {
+    /// Factor of the attraction along an edge of the given weight. Non-positive and
+    /// non-finite weights count as 1.
+    pub fn factor(self, weight: f32) -> f32 {
+        let w = if weight.is_finite() && weight > 0. {
+            weight
+        } else {
+            1.
+        };
+        match self {
+            EdgeWeighting::Ignore => 1.,
+            EdgeWeighting::Strength => w,
+            EdgeWeighting::Length => 1. / w,
+        }
+    }
+}
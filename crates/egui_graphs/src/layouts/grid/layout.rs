@@ -0,0 +1,142 @@
+use egui::Pos2;
+use petgraph::{csr::IndexType, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{Layout, LayoutState, NodeKey},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+/// Direction the grid is filled in.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// Left to right, wrapping to the next row after `lines` nodes.
+    #[default]
+    Rows,
+    /// Top to bottom, wrapping to the next column after `lines` nodes.
+    Columns,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Order the cells are filled in.
+    pub order: NodeKey,
+    /// Fill direction.
+    pub orientation: Orientation,
+    /// Nodes per row for [`Orientation::Rows`] or per column for [`Orientation::Columns`].
+    /// None makes the grid roughly square.
+    pub lines: Option<usize>,
+    /// Horizontal distance between cells.
+    pub col_dist: f32,
+    /// Vertical distance between cells.
+    pub row_dist: f32,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            order: NodeKey::Index,
+            orientation: Orientation::Rows,
+            lines: None,
+            col_dist: 50.,
+            row_dist: 50.,
+        }
+    }
+}
+
+impl LayoutState for State {}
+
+/// Places nodes on a regular grid starting at the origin. Applies once.
+#[derive(Debug, Default)]
+pub struct Grid {
+    state: State,
+}
+
+impl Layout<State> for Grid {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        g.clear_edge_bends();
+        layout_grid(g, &self.state);
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Grid { state }
+    }
+}
+
+fn layout_grid<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let nodes = state.order.sorted(g);
+    let lines = state
+        .lines
+        .unwrap_or_else(|| (nodes.len() as f32).sqrt().ceil() as usize)
+        .max(1);
+    for (i, (idx, _)) in nodes.into_iter().enumerate() {
+        let (major, minor) = (i / lines, i % lines);
+        let (col, row) = match state.orientation {
+            Orientation::Rows => (minor, major),
+            Orientation::Columns => (major, minor),
+        };
+        if let Some(node) = g.node_mut(idx).filter(|n| !n.pinned()) {
+            node.set_location(Pos2::new(
+                col as f32 * state.col_dist,
+                row as f32 * state.row_dist,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn fills_rows_or_columns() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..5).map(|_| g.add_node(())).collect();
+        let at = |g: &Graph, i: usize| g.node(n[i]).unwrap().location();
+
+        layout_grid(&mut g, &State::default());
+        // Five nodes need three per row.
+        assert_eq!(at(&g, 2), Pos2::new(100., 0.));
+        assert_eq!(at(&g, 4), Pos2::new(50., 50.));
+
+        let st = State {
+            orientation: Orientation::Columns,
+            lines: Some(2),
+            row_dist: 30.,
+            ..State::default()
+        };
+        layout_grid(&mut g, &st);
+        assert_eq!(at(&g, 1), Pos2::new(0., 30.));
+        assert_eq!(at(&g, 4), Pos2::new(100., 0.));
+    }
+}
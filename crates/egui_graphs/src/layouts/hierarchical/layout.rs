@@ -1,6 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use egui::Pos2;
+use egui::{Pos2, Vec2};
 use petgraph::{
     csr::IndexType,
     stable_graph::NodeIndex,
@@ -9,6 +9,11 @@ use petgraph::{
 };
 use serde::{Deserialize, Serialize};
 
+use super::{
+    spacing::Spacing,
+    sugiyama::{self, CrossingHeuristic, Layering},
+    tidy,
+};
 use crate::{
     layouts::{Layout, LayoutState},
     DisplayEdge, DisplayNode, Graph,
@@ -22,20 +27,93 @@ pub enum Orientation {
     TopDown,
     /// Levels grow to the right. Rows are horizontal steps.
     LeftRight,
+    /// Levels grow upward, roots at the bottom. Rows are vertical steps.
+    BottomUp,
+    /// Levels grow to the left. Rows are horizontal steps.
+    RightLeft,
+}
+
+impl Orientation {
+    /// Canvas position of a node `row` along the level axis and `col` across it.
+    fn place(self, row: f32, col: f32) -> Pos2 {
+        match self {
+            Orientation::TopDown => Pos2::new(col, row),
+            Orientation::LeftRight => Pos2::new(row, col),
+            Orientation::BottomUp => Pos2::new(col, -row),
+            Orientation::RightLeft => Pos2::new(-row, col),
+        }
+    }
+
+    /// Splits a node size into the extent along the level axis and across it.
+    fn split(self, size: Vec2) -> (f32, f32) {
+        match self {
+            Orientation::TopDown | Orientation::BottomUp => (size.y, size.x),
+            Orientation::LeftRight | Orientation::RightLeft => (size.x, size.y),
+        }
+    }
+}
+
+/// Algorithm placing the nodes on levels.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Depth-first spanning forest, every node one level below the parent it was reached from.
+    /// Cheap and stable for trees.
+    #[default]
+    Dfs,
+    /// Compact tidy tree of Reingold and Tilford over the same spanning forest as `Dfs`:
+    /// subtrees are packed by their contours and parents are always centered above their
+    /// children.
+    Tidy,
+    /// Layered drawing for general directed graphs: cycles are broken, long edges are routed
+    /// through bend points and the order within levels minimizes crossings.
+    Sugiyama,
+}
+
+fn default_crossing_sweeps() -> usize {
+    24
+}
+
+fn default_node_margin() -> f32 {
+    10.
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
     pub triggered: bool,
-    /// Distance between levels (rows). Interpreted as Y step for TopDown and X step for LeftRight.
+    /// Distance between levels (rows). Interpreted as Y step for TopDown/BottomUp and X step
+    /// for LeftRight/RightLeft.
     pub row_dist: f32,
-    /// Distance between siblings/columns. Interpreted as X step for TopDown and Y step for LeftRight.
+    /// Distance between siblings/columns. Interpreted as X step for TopDown/BottomUp and Y step
+    /// for LeftRight/RightLeft.
     pub col_dist: f32,
-    /// Center a parent above/beside the span of its children.
+    /// Center a parent above/beside the span of its children in the `Dfs` mode instead of
+    /// aligning it with the first child.
     pub center_parent: bool,
     /// Layout orientation.
     pub orientation: Orientation,
+    /// Placement algorithm. All modes reset edge bends, only Sugiyama sets them.
+    #[serde(default)]
+    pub mode: Mode,
+    /// Layer assignment of the Sugiyama mode.
+    #[serde(default)]
+    pub layering: Layering,
+    /// Ordering heuristic of the Sugiyama mode.
+    #[serde(default)]
+    pub crossing_heuristic: CrossingHeuristic,
+    /// Upper bound of crossing minimization sweeps in the Sugiyama mode, each sweep goes over
+    /// all levels once in alternating directions.
+    #[serde(default = "default_crossing_sweeps")]
+    pub crossing_sweeps: usize,
+    /// Space levels and siblings by the actual node sizes from
+    /// [`DisplayNode::bounding_rect`] plus `node_margin`, `row_dist` and `col_dist` become
+    /// minimum distances between centers. Sizes are known once nodes have been drawn, so a
+    /// layout applied on the first frame may need a reset to pick them up.
+    #[serde(default)]
+    pub fit_node_sizes: bool,
+    /// Free space kept between neighboring nodes when `fit_node_sizes` is on.
+    #[serde(default = "default_node_margin")]
+    pub node_margin: f32,
 }
 
 impl Default for State {
@@ -46,10 +124,65 @@ impl Default for State {
             col_dist: 50.0,
             center_parent: false,
             orientation: Orientation::TopDown,
+            mode: Mode::Dfs,
+            layering: Layering::default(),
+            crossing_heuristic: CrossingHeuristic::default(),
+            crossing_sweeps: default_crossing_sweeps(),
+            fit_node_sizes: false,
+            node_margin: default_node_margin(),
+        }
+    }
+}
+
+impl State {
+    fn spacing(&self) -> Spacing {
+        Spacing {
+            row_dist: self.row_dist,
+            col_dist: self.col_dist,
+            margin: if self.fit_node_sizes {
+                self.node_margin
+            } else {
+                0.
+            },
         }
     }
 }
 
+/// Extent of a node along the levels and across them, zero unless sizes are fitted.
+fn extent<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    idx: NodeIndex<Ix>,
+    state: &State,
+) -> (f32, f32)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    if !state.fit_node_sizes {
+        return (0., 0.);
+    }
+    let size = g
+        .node(idx)
+        .map_or(Vec2::ZERO, |n| n.display().bounding_rect().size());
+    state.orientation.split(size)
+}
+
+/// Thickness of every level as the largest extent along the levels of its nodes.
+fn level_thickness(rows: impl Iterator<Item = (usize, f32)>) -> Vec<f32> {
+    let mut thickness = Vec::new();
+    for (row, along) in rows {
+        if thickness.len() <= row {
+            thickness.resize(row + 1, 0.);
+        }
+        thickness[row] = f32::max(thickness[row], along);
+    }
+    thickness
+}
+
 impl LayoutState for State {}
 
 #[derive(Debug, Default)]
@@ -71,30 +204,12 @@ impl Layout<State> for Hierarchical {
             return;
         }
 
-        let mut visited = HashSet::new();
-
-        // Place forests starting from all roots (no incoming edges), packing them left-to-right
-        // without overlap by advancing the next starting column by the width of each subtree.
-        let mut next_col: usize = 0;
-        let roots: Vec<NodeIndex<Ix>> = g.g().externals(Incoming).collect();
-        for root in &roots {
-            if visited.contains(root) {
-                continue;
-            }
-
-            let curr_max_col = layout_tree(g, &mut visited, root, &self.state, 0, next_col);
-            next_col = curr_max_col + 1;
-        }
-
-        // Fallback: if the graph has cycles or no formal roots, lay out any remaining components.
-        let all_nodes: Vec<NodeIndex<Ix>> = g.g().node_indices().collect();
-        for n in &all_nodes {
-            if visited.contains(n) {
-                continue;
-            }
+        g.clear_edge_bends();
 
-            let curr_max_col = layout_tree(g, &mut visited, n, &self.state, 0, next_col);
-            next_col = curr_max_col + 1;
+        match self.state.mode {
+            Mode::Dfs => layout_dfs(g, &self.state),
+            Mode::Tidy => layout_tidy(g, &self.state),
+            Mode::Sugiyama => layout_sugiyama(g, &self.state),
         }
 
         self.state.triggered = true;
@@ -109,14 +224,241 @@ impl Layout<State> for Hierarchical {
     }
 }
 
+fn layout_dfs<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut visited = HashSet::new();
+
+    // Place forests starting from all roots, packing them left-to-right without overlap by
+    // advancing the next starting column by the width of each subtree.
+    let mut placed = Vec::new();
+    let mut next_col: usize = 0;
+    for root in &traversal_starts(g) {
+        if visited.contains(root) {
+            continue;
+        }
+
+        let (curr_max_col, _) = layout_tree(g, &mut visited, root, state, 0, next_col, &mut placed);
+        next_col = curr_max_col + 1;
+    }
+
+    // Rows and columns of the grid are as wide as their widest nodes, parents centered
+    // between columns are interpolated.
+    let extents: Vec<(f32, f32)> = placed
+        .iter()
+        .map(|&(idx, _, _)| extent(g, idx, state))
+        .collect();
+    let spacing = state.spacing();
+    let rows = spacing.level_positions(&level_thickness(
+        placed
+            .iter()
+            .zip(&extents)
+            .map(|(&(_, row, _), &(along, _))| (row, along)),
+    ));
+    let mut widths = vec![0.; next_col];
+    for (&(_, _, col), &(_, across)) in placed.iter().zip(&extents) {
+        for c in [col.floor() as usize, col.ceil() as usize] {
+            widths[c] = f32::max(widths[c], across);
+        }
+    }
+    let cols = spacing.col_positions(&widths);
+
+    for (idx, row, col) in placed {
+        let (lo, hi) = (col.floor() as usize, col.ceil() as usize);
+        let x = cols[lo] + (cols[hi] - cols[lo]) * col.fract();
+        if let Some(n) = g.node_mut(idx).filter(|n| !n.pinned()) {
+            n.set_location(state.orientation.place(rows[row], x));
+        }
+    }
+}
+
+fn layout_tidy<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut visited = HashSet::new();
+    let mut trees = Vec::new();
+    for root in traversal_starts(g) {
+        if visited.contains(&root) {
+            continue;
+        }
+
+        let (nodes, rows, tree) = spanning_tree(g, &mut visited, root);
+        let extents: Vec<(f32, f32)> = nodes.iter().map(|&idx| extent(g, idx, state)).collect();
+        trees.push((nodes, rows, tree, extents));
+    }
+
+    let spacing = state.spacing();
+    let levels = spacing.level_positions(&level_thickness(trees.iter().flat_map(
+        |(_, rows, _, extents)| {
+            rows.iter()
+                .zip(extents)
+                .map(|(&row, &(along, _))| (row, along))
+        },
+    )));
+
+    // Trees are packed left-to-right with their bounding boxes `col_dist` apart.
+    let mut next_left = 0.;
+    for (nodes, rows, tree, extents) in trees {
+        let cols = tidy::positions(&tree, &|a, b| spacing.col_gap(extents[a].1, extents[b].1));
+        let (left, right) = cols.iter().zip(&extents).fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(l, r), (&col, &(_, across))| (l.min(col - across / 2.), r.max(col + across / 2.)),
+        );
+        let shift = next_left - left;
+        for ((idx, row), col) in nodes.into_iter().zip(rows).zip(cols) {
+            let pos = state.orientation.place(levels[row], shift + col);
+            if let Some(n) = g.node_mut(idx).filter(|n| !n.pinned()) {
+                n.set_location(pos);
+            }
+        }
+        next_left = shift + right + spacing.col_gap(0., 0.);
+    }
+}
+
+/// Spanning tree grown like in [`layout_tree`]: children are claimed depth first in neighbor
+/// order, so a node reachable from several parents belongs to the first subtree reaching it.
+/// Returns the nodes with the root first, their rows and the tree over their positions.
+fn spanning_tree<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    visited: &mut HashSet<NodeIndex<Ix>>,
+    root: NodeIndex<Ix>,
+) -> (Vec<NodeIndex<Ix>>, Vec<usize>, tidy::Tree)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    visited.insert(root);
+    let mut nodes = vec![root];
+    let mut rows = vec![0];
+    let mut children = vec![Vec::new()];
+    let mut stack = vec![(0, g.visible_neighbors(root, Some(Outgoing)), 0)];
+    while let Some((v, candidates, next)) = stack.last_mut() {
+        let Some(&c) = candidates.get(*next) else {
+            stack.pop();
+            continue;
+        };
+        *next += 1;
+        if !visited.insert(c) {
+            continue;
+        }
+        let (v, local) = (*v, nodes.len());
+        nodes.push(c);
+        rows.push(rows[v] + 1);
+        children.push(Vec::new());
+        children[v].push(local);
+        stack.push((local, g.visible_neighbors(c, Some(Outgoing)), 0));
+    }
+    (nodes, rows, tidy::Tree { children })
+}
+
+/// Nodes trees are grown from: roots without incoming edges first, then every other node as a
+/// fallback for cycles and components without formal roots. Callers skip nodes already visited
+/// by an earlier tree.
+///
+/// Hidden nodes take no slot; members of collapsed groups are skipped as the group node takes
+/// over their edges.
+pub(crate) fn traversal_starts<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+) -> Vec<NodeIndex<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let (mut roots, rest): (Vec<_>, Vec<_>) = g
+        .visible_node_indices()
+        .partition(|idx| g.visible_neighbors(*idx, Some(Incoming)).is_empty());
+    roots.extend(rest);
+    roots
+}
+
+fn layout_sugiyama<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let nodes: Vec<NodeIndex<Ix>> = g.visible_node_indices().collect();
+    let local: HashMap<NodeIndex<Ix>, usize> =
+        nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let (edge_ids, edges): (Vec<_>, Vec<_>) = g
+        .edges_iter()
+        .filter_map(|(idx, _)| {
+            let (a, b) = g.visible_edge_endpoints(idx)?;
+            Some((idx, (local[&a], local[&b])))
+        })
+        .unzip();
+
+    let drawing = sugiyama::draw(
+        nodes.len(),
+        &edges,
+        &sugiyama::Params {
+            spacing: state.spacing(),
+            extents: nodes.iter().map(|&idx| extent(g, idx, state)).collect(),
+            layering: state.layering,
+            heuristic: state.crossing_heuristic,
+            sweeps: state.crossing_sweeps,
+        },
+    );
+
+    let orient = |p: Pos2| state.orientation.place(p.y, p.x);
+    // Pinned nodes stay where they are, so bends routed to their drawn place would be off.
+    let mut off_drawing = vec![false; nodes.len()];
+    for (i, (idx, p)) in nodes.into_iter().zip(drawing.nodes).enumerate() {
+        let Some(n) = g.node_mut(idx) else {
+            continue;
+        };
+        if n.pinned() {
+            off_drawing[i] = n.location() != orient(p);
+        } else {
+            n.set_location(orient(p));
+        }
+    }
+    for ((idx, (a, b)), bends) in edge_ids.into_iter().zip(edges).zip(drawing.bends) {
+        let bends = if off_drawing[a] || off_drawing[b] {
+            Vec::new()
+        } else {
+            bends.into_iter().map(orient).collect()
+        };
+        if let Some(e) = g.edge_mut(idx) {
+            e.set_bends(bends);
+        }
+    }
+}
+
+/// Collects the row and (possibly fractional) column of every node of the subtree into
+/// `placed`, returns the last column used and the column of the root.
 fn layout_tree<N, E, Ty, Ix, Dn, De>(
-    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
     visited: &mut HashSet<NodeIndex<Ix>>,
     root_idx: &NodeIndex<Ix>,
     state: &State,
     start_row: usize,
     start_col: usize,
-) -> usize
+    placed: &mut Vec<(NodeIndex<Ix>, usize, f32)>,
+) -> (usize, f32)
 where
     N: Clone,
     E: Clone,
@@ -130,18 +472,31 @@ where
         visited.insert(*root_idx);
     }
 
-    let children: Vec<NodeIndex<Ix>> = g.g().neighbors_directed(*root_idx, Outgoing).collect();
+    let children: Vec<NodeIndex<Ix>> = g.visible_neighbors(*root_idx, Some(Outgoing));
 
     // Traverse children to compute the horizontal span of this subtree.
     let mut max_col = start_col;
     let mut child_col = start_col;
+    let mut placed_children: Option<(f32, f32)> = None;
     for neighbour_idx in children.iter() {
         if visited.contains(neighbour_idx) {
             continue;
         }
         visited.insert(*neighbour_idx);
 
-        let child_max_col = layout_tree(g, visited, neighbour_idx, state, start_row + 1, child_col);
+        let (child_max_col, child_place_col) = layout_tree(
+            g,
+            visited,
+            neighbour_idx,
+            state,
+            start_row + 1,
+            child_col,
+            placed,
+        );
+        placed_children = Some(match placed_children {
+            None => (child_place_col, child_place_col),
+            Some((first, _)) => (first, child_place_col),
+        });
         if child_max_col > max_col {
             max_col = child_max_col;
         }
@@ -149,22 +504,185 @@ where
     }
 
     // Column where the current node will be placed.
-    let place_col = start_col;
-
-    // Compute actual coordinates based on orientation.
-    let (x, y) = match state.orientation {
-        Orientation::TopDown => (
-            (place_col as f32) * state.col_dist,
-            (start_row as f32) * state.row_dist,
-        ),
-        Orientation::LeftRight => (
-            (start_row as f32) * state.row_dist,
-            (place_col as f32) * state.col_dist,
-        ),
+    let place_col = match placed_children {
+        Some((first, last)) if state.center_parent => f32::midpoint(first, last),
+        _ => start_col as f32,
     };
 
-    let node = &mut g.g_mut()[*root_idx];
-    node.set_location(Pos2::new(x, y));
+    placed.push((*root_idx, start_row, place_col));
+
+    (max_col, place_col)
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    fn at(g: &Graph, idx: NodeIndex) -> Pos2 {
+        g.node(idx).unwrap().location()
+    }
+
+    /// Root 0 with children 1 and 2, node 1 with children 3 and 4.
+    fn tree() -> (Graph, Vec<NodeIndex>) {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..5).map(|_| g.add_node(())).collect();
+        for (a, b) in [(0, 1), (0, 2), (1, 3), (1, 4)] {
+            g.add_edge(n[a], n[b], ());
+        }
+        (g, n)
+    }
+
+    #[test]
+    fn dfs_centers_parents_on_request() {
+        let (mut g, n) = tree();
+        let mut st = State::default();
+        layout_dfs(&mut g, &st);
+        // Aligned with the first child whichever it is.
+        assert_eq!(at(&g, n[0]).x, at(&g, n[1]).x.min(at(&g, n[2]).x));
+
+        st.center_parent = true;
+        st.orientation = Orientation::BottomUp;
+        layout_dfs(&mut g, &st);
+        let (x0, x1) = (at(&g, n[0]).x, at(&g, n[1]).x);
+        assert_eq!(x1, f32::midpoint(at(&g, n[3]).x, at(&g, n[4]).x));
+        let x2 = at(&g, n[2]).x;
+        assert_eq!(x0, f32::midpoint(x1, x2));
+        assert_eq!(at(&g, n[3]).y, -100.);
+    }
+
+    fn apply(g: &mut Graph, st: &State) {
+        match st.mode {
+            Mode::Dfs => layout_dfs(g, st),
+            Mode::Tidy => layout_tidy(g, st),
+            Mode::Sugiyama => layout_sugiyama(g, st),
+        }
+    }
+
+    #[test]
+    fn fits_node_sizes_in_every_mode() {
+        let (mut g, n) = tree();
+        // Node 1 becomes a 100x100 circle.
+        g.node_mut(n[1]).unwrap().display_mut().radius = 50.;
+        for mode in [Mode::Dfs, Mode::Tidy, Mode::Sugiyama] {
+            let mut st = State {
+                mode,
+                ..State::default()
+            };
+            apply(&mut g, &st);
+            assert_eq!(at(&g, n[1]).y, 50.);
+            assert!((at(&g, n[1]).x - at(&g, n[2]).x).abs() < 100.);
 
-    max_col
+            st.fit_node_sizes = true;
+            apply(&mut g, &st);
+            // Half of the big node plus half of a 10x10 default node and the margin.
+            assert_eq!(at(&g, n[1]).y, 65., "{mode:?}");
+            assert_eq!(at(&g, n[3]).y, 130., "{mode:?}");
+            assert!(
+                (at(&g, n[1]).x - at(&g, n[2]).x).abs() >= 65. - 1e-3,
+                "{mode:?}"
+            );
+        }
+    }
+
+    /// Runs `layout` for one frame.
+    fn run<S: LayoutState>(g: &mut Graph, layout: &mut impl Layout<S>) {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| layout.next(g, ui));
+        });
+    }
+
+    #[test]
+    fn other_layouts_drop_sugiyama_bends() {
+        let (mut g, n) = tree();
+        // Spans two layers.
+        g.add_edge(n[0], n[3], ());
+        let bent = |g: &Graph| {
+            g.edges_iter()
+                .filter(|(_, e)| !e.bends().is_empty())
+                .count()
+        };
+
+        let mut sugiyama = Hierarchical::from_state(State {
+            mode: Mode::Sugiyama,
+            ..State::default()
+        });
+        run(&mut g, &mut sugiyama);
+        assert_eq!(bent(&g), 1);
+
+        run(&mut g, &mut crate::LayoutCircular::default());
+        assert_eq!(bent(&g), 0);
+    }
+
+    #[test]
+    fn edges_of_moved_pinned_nodes_are_not_bent() {
+        let (mut g, n) = tree();
+        let long = g.add_edge(n[0], n[3], ());
+        g.set_node_pinned(n[3], true);
+        g.node_mut(n[3])
+            .unwrap()
+            .set_location(Pos2::new(500., 500.));
+        let mut sugiyama = Hierarchical::from_state(State {
+            mode: Mode::Sugiyama,
+            ..State::default()
+        });
+        run(&mut g, &mut sugiyama);
+
+        assert_eq!(at(&g, n[3]), Pos2::new(500., 500.));
+        assert!(g.edges_iter().all(|(_, e)| e.bends().is_empty()));
+
+        // Unpinned again, the node follows the drawing and the edge is routed.
+        g.set_node_pinned(n[3], false);
+        sugiyama = Hierarchical::from_state(State {
+            mode: Mode::Sugiyama,
+            ..State::default()
+        });
+        run(&mut g, &mut sugiyama);
+        assert!(!g.edge(long).unwrap().bends().is_empty());
+    }
+
+    #[test]
+    fn undoing_a_move_restores_sugiyama_bends() {
+        let (mut g, n) = tree();
+        let long = g.add_edge(n[0], n[3], ());
+        let mut sugiyama = Hierarchical::from_state(State {
+            mode: Mode::Sugiyama,
+            ..State::default()
+        });
+        run(&mut g, &mut sugiyama);
+        let bends = g.edge(long).unwrap().bends().to_vec();
+        assert!(!bends.is_empty());
+
+        g.set_history_enabled(true);
+        g.set_node_location(n[3], Pos2::new(500., 500.));
+        assert!(g.edge(long).unwrap().bends().is_empty());
+
+        assert!(g.undo());
+        assert_eq!(g.edge(long).unwrap().bends(), bends.as_slice());
+        assert!(g.redo());
+        assert!(g.edge(long).unwrap().bends().is_empty());
+    }
+
+    #[test]
+    fn tidy_packs_and_orients() {
+        let (mut g, n) = tree();
+        let st = State {
+            mode: Mode::Tidy,
+            orientation: Orientation::RightLeft,
+            ..State::default()
+        };
+        layout_tidy(&mut g, &st);
+
+        // Levels grow to the left, siblings are one column apart along y.
+        assert_eq!(at(&g, n[0]).x, 0.);
+        assert_eq!(at(&g, n[3]).x, -100.);
+        assert_eq!((at(&g, n[3]).y - at(&g, n[4]).y).abs(), 50.);
+        assert_eq!((at(&g, n[1]).y - at(&g, n[2]).y).abs(), 50.);
+        assert_eq!(
+            at(&g, n[0]).y,
+            f32::midpoint(at(&g, n[1]).y, at(&g, n[2]).y)
+        );
+    }
 }
@@ -1,3 +1,9 @@
 mod layout;
+mod radial;
+mod spacing;
+mod sugiyama;
+mod tidy;
 
-pub use layout::{Hierarchical, Orientation, State};
+pub use layout::{Hierarchical, Mode, Orientation, State};
+pub use radial::{Radial, State as RadialState};
+pub use sugiyama::{CrossingHeuristic, Layering};
@@ -0,0 +1,257 @@
+use std::collections::{HashSet, VecDeque};
+use std::f32::consts::TAU;
+
+use egui::{Pos2, Vec2};
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, Direction::Outgoing, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use super::layout::traversal_starts;
+use crate::{
+    layouts::{Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Index of the node in the center. None or a node that is not visible falls back to the
+    /// first selected node, then to the first root of the graph.
+    pub root: Option<usize>,
+    /// Distance between consecutive levels (circles).
+    pub level_dist: f32,
+    /// Angle where the share of the first child of the root starts, in radians.
+    pub start_angle: f32,
+    /// Only follow outgoing edges like the hierarchical layout. By default edges are followed
+    /// in both directions so predecessors of the root are placed as well.
+    pub outgoing_only: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            root: None,
+            level_dist: 80.,
+            start_angle: 0.,
+            outgoing_only: false,
+        }
+    }
+}
+
+impl LayoutState for State {}
+
+/// Radial tree: the root in the center and BFS levels on concentric circles. Every subtree
+/// gets an angular share proportional to its size. Components not reachable from the root
+/// are laid out as own trees to the right. Applies once.
+#[derive(Debug, Default)]
+pub struct Radial {
+    state: State,
+}
+
+impl Layout<State> for Radial {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        g.clear_edge_bends();
+        layout_radial(g, &self.state);
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Radial { state }
+    }
+}
+
+fn layout_radial<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let chosen = state
+        .root
+        .map(NodeIndex::new)
+        .into_iter()
+        .chain(g.selected_nodes().iter().copied())
+        .find(|idx| g.is_node_visible(*idx));
+
+    let mut visited = HashSet::new();
+    let mut right = None;
+    for root in chosen.into_iter().chain(traversal_starts(g)) {
+        if visited.contains(&root) {
+            continue;
+        }
+
+        let tree = Tree::bfs(g, &mut visited, root, state.outgoing_only);
+        let radius = tree.depth as f32 * state.level_dist;
+        let center = match right {
+            None => Pos2::ZERO,
+            Some(x) => Pos2::new(x + state.level_dist + radius, 0.),
+        };
+        right = Some(center.x + radius);
+
+        for (idx, angle, depth) in tree.place(state.start_angle) {
+            if let Some(node) = g.node_mut(idx).filter(|n| !n.pinned()) {
+                node.set_location(center + Vec2::angled(angle) * depth as f32 * state.level_dist);
+            }
+        }
+    }
+}
+
+/// BFS spanning tree, nodes are stored in visiting order so parents precede children.
+struct Tree<Ix: IndexType> {
+    nodes: Vec<NodeIndex<Ix>>,
+    parent: Vec<usize>,
+    level: Vec<usize>,
+    depth: usize,
+}
+
+impl<Ix: IndexType> Tree<Ix> {
+    fn bfs<N, E, Ty, Dn, De>(
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        visited: &mut HashSet<NodeIndex<Ix>>,
+        root: NodeIndex<Ix>,
+        outgoing_only: bool,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let mut tree = Self {
+            nodes: vec![root],
+            parent: vec![usize::MAX],
+            level: vec![0],
+            depth: 0,
+        };
+        visited.insert(root);
+
+        let dir = outgoing_only.then_some(Outgoing);
+        let mut queue = VecDeque::from([0]);
+        while let Some(i) = queue.pop_front() {
+            for child in g.visible_neighbors(tree.nodes[i], dir) {
+                if !visited.insert(child) {
+                    continue;
+                }
+                let level = tree.level[i] + 1;
+                tree.depth = tree.depth.max(level);
+                queue.push_back(tree.nodes.len());
+                tree.nodes.push(child);
+                tree.parent.push(i);
+                tree.level.push(level);
+            }
+        }
+        tree
+    }
+
+    /// Angle and level of every node. Each node splits its wedge among its children by
+    /// subtree size and sits in the middle of its own wedge.
+    fn place(&self, start_angle: f32) -> Vec<(NodeIndex<Ix>, f32, usize)> {
+        let n = self.nodes.len();
+        let mut size = vec![1usize; n];
+        for i in (1..n).rev() {
+            size[self.parent[i]] += size[i];
+        }
+
+        // Start of the wedge still free for the next child of every node.
+        let mut next_start = vec![0f32; n];
+        let mut wedge = vec![(start_angle, TAU); n];
+        next_start[0] = start_angle;
+        for i in 1..n {
+            let p = self.parent[i];
+            let (_, parent_span) = wedge[p];
+            let span = parent_span * size[i] as f32 / (size[p] - 1) as f32;
+            wedge[i] = (next_start[p], span);
+            next_start[i] = next_start[p];
+            next_start[p] += span;
+        }
+
+        (0..n)
+            .map(|i| {
+                let (start, span) = wedge[i];
+                (self.nodes[i], start + span / 2., self.level[i])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    fn at(g: &Graph, idx: NodeIndex) -> Pos2 {
+        g.node(idx).unwrap().location()
+    }
+
+    #[test]
+    fn levels_on_circles_with_shares_by_subtree_size() {
+        // Root 0 with a chain 1 -> 2 -> 3 and leaves 4 and 5.
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..6).map(|_| g.add_node(())).collect();
+        g.add_edge(n[0], n[1], ());
+        g.add_edge(n[1], n[2], ());
+        g.add_edge(n[2], n[3], ());
+        g.add_edge(n[0], n[4], ());
+        g.add_edge(n[0], n[5], ());
+
+        layout_radial(&mut g, &State::default());
+
+        assert_eq!(at(&g, n[0]), Pos2::ZERO);
+        for (i, level) in [(1, 1.), (2, 2.), (3, 3.), (4, 1.)] {
+            assert!((at(&g, n[i]).to_vec2().length() - 80. * level).abs() < 1e-3);
+        }
+        // The chain holds three of five nodes below the root, each leaf a fifth of the circle,
+        // so the leaves are a fifth apart whatever the order of the children.
+        let angle = |i: usize| at(&g, n[i]).to_vec2().angle();
+        assert!((angle(1) - angle(3)).abs() < 1e-3);
+        let apart = (angle(4) - angle(5)).rem_euclid(TAU);
+        assert!((apart.min(TAU - apart) - TAU / 5.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn root_from_state_or_selection() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..4).map(|_| g.add_node(())).collect();
+        g.add_edge(n[0], n[1], ());
+        g.add_edge(n[1], n[2], ());
+
+        g.set_selected_nodes(vec![n[1]]);
+        layout_radial(&mut g, &State::default());
+        assert_eq!(at(&g, n[1]), Pos2::ZERO);
+        assert!((at(&g, n[0]).to_vec2().length() - 80.).abs() < 1e-3);
+
+        let st = State {
+            root: Some(2),
+            outgoing_only: true,
+            ..State::default()
+        };
+        layout_radial(&mut g, &st);
+        assert_eq!(at(&g, n[2]), Pos2::ZERO);
+        // Nothing is reachable from 2 following edge directions, the rest of the component
+        // and the isolated node 3 become own trees to the right.
+        assert_eq!(at(&g, n[0]), Pos2::new(80. + 80., 0.));
+        assert!(at(&g, n[3]).x > at(&g, n[1]).x);
+    }
+}
@@ -0,0 +1,60 @@
+/// Distances between level and sibling centers. Node extents are measured along the level
+/// axis for rows and across it for columns; with zero extents and margin the fixed distances
+/// apply unchanged.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Spacing {
+    pub row_dist: f32,
+    pub col_dist: f32,
+    pub margin: f32,
+}
+
+impl Spacing {
+    /// Distance between the centers of neighbors on a level with extents `a` and `b`.
+    pub fn col_gap(&self, a: f32, b: f32) -> f32 {
+        self.col_dist.max(f32::midpoint(a, b) + self.margin)
+    }
+
+    /// Distance between the centers of consecutive levels as thick as their thickest nodes.
+    pub fn row_gap(&self, a: f32, b: f32) -> f32 {
+        self.row_dist.max(f32::midpoint(a, b) + self.margin)
+    }
+
+    /// Positions of levels with the given thickness, the first level at 0.
+    pub fn level_positions(&self, thickness: &[f32]) -> Vec<f32> {
+        accumulate(thickness, |a, b| self.row_gap(a, b))
+    }
+
+    /// Positions of grid columns with the given widths, the first column at 0.
+    pub fn col_positions(&self, widths: &[f32]) -> Vec<f32> {
+        accumulate(widths, |a, b| self.col_gap(a, b))
+    }
+}
+
+fn accumulate(extents: &[f32], gap: impl Fn(f32, f32) -> f32) -> Vec<f32> {
+    let mut res = Vec::with_capacity(extents.len());
+    let mut pos = 0.;
+    for (i, &e) in extents.iter().enumerate() {
+        if i > 0 {
+            pos += gap(extents[i - 1], e);
+        }
+        res.push(pos);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extents_grow_fixed_distances() {
+        let s = Spacing {
+            row_dist: 50.,
+            col_dist: 40.,
+            margin: 10.,
+        };
+        assert_eq!(s.col_gap(10., 20.), 40.);
+        assert_eq!(s.col_gap(60., 80.), 80.);
+        assert_eq!(s.level_positions(&[0., 100., 20.]), [0., 60., 130.]);
+    }
+}
@@ -0,0 +1,935 @@
+//! Sugiyama style layered drawing on plain node and edge indices.
+//!
+//! The pipeline runs per weakly connected component:
+//! 1. cycle removal by reversing DFS back edges,
+//! 2. layer assignment by longest path, optionally refined by network simplex,
+//! 3. dummy nodes for edges spanning several layers,
+//! 4. crossing minimization by median or barycenter sweeps with adjacent transpositions,
+//! 5. coordinate assignment pulling nodes toward their neighbors while keeping the order.
+//!
+//! Components are packed side by side. Coordinates are top-down: layers grow along y.
+
+use egui::Pos2;
+use serde::{Deserialize, Serialize};
+
+use super::spacing::Spacing;
+
+/// Layer assignment of the Sugiyama mode.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Layering {
+    /// Every node one layer below its lowest predecessor. Fast, but sources end up in the top
+    /// layer even when their edges become long.
+    LongestPath,
+    /// Minimizes the total edge length, shortening edges of sources and sinks. Every pivot is
+    /// linear in the size of the component, the number of pivots is capped.
+    #[default]
+    NetworkSimplex,
+}
+
+/// Ordering heuristic of the crossing minimization sweeps.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum CrossingHeuristic {
+    /// Average position of the neighbors in the fixed layer.
+    Barycenter,
+    /// Weighted median position of the neighbors in the fixed layer.
+    #[default]
+    Median,
+}
+
+pub(crate) struct Params {
+    pub spacing: Spacing,
+    /// Extent of every input node along the levels and across them, empty for points.
+    pub extents: Vec<(f32, f32)>,
+    pub layering: Layering,
+    pub heuristic: CrossingHeuristic,
+    pub sweeps: usize,
+}
+
+pub(crate) struct Drawing {
+    /// Node centers indexed like the input nodes.
+    pub nodes: Vec<Pos2>,
+    /// Bend points from source to target indexed like the input edges, empty for edges
+    /// between adjacent layers and self loops.
+    pub bends: Vec<Vec<Pos2>>,
+}
+
+const NONE: usize = usize::MAX;
+const NETWORK_SIMPLEX_MAX_ITER: usize = 500;
+const COORDINATE_PASSES: usize = 8;
+
+/// Lays out `n` nodes connected by `edges` given as `(source, target)`.
+pub(crate) fn draw(n: usize, edges: &[(usize, usize)], params: &Params) -> Drawing {
+    let mut drawing = Drawing {
+        nodes: vec![Pos2::ZERO; n],
+        bends: vec![Vec::new(); edges.len()],
+    };
+
+    let extent = |v: usize| params.extents.get(v).copied().unwrap_or_default();
+    let components: Vec<Component> = components(n, edges)
+        .into_iter()
+        .map(|(nodes, edge_ids)| {
+            let mut component = Component::new(&nodes, &edge_ids, edges);
+            component.assign_layers(params.layering);
+            component.insert_dummies();
+            component.order(params.heuristic, params.sweeps);
+            let widths: Vec<f32> = (0..component.rank.len())
+                .map(|v| component.input.get(v).map_or(0., |&i| extent(i).1))
+                .collect();
+            component.assign_coordinates(&widths, &params.spacing);
+            component
+        })
+        .collect();
+
+    // Levels are shared by all components so equal ranks line up.
+    let mut thickness = Vec::new();
+    for component in &components {
+        for (v, &input) in component.input.iter().enumerate() {
+            let r = component.rank[v];
+            if thickness.len() <= r {
+                thickness.resize(r + 1, 0.);
+            }
+            thickness[r] = f32::max(thickness[r], extent(input).0);
+        }
+    }
+    let rows = params.spacing.level_positions(&thickness);
+
+    let mut x_offset = 0.;
+    for component in &components {
+        let width = |v: usize| component.input.get(v).map_or(0., |&i| extent(i).1);
+        x_offset =
+            component.write(&mut drawing, x_offset, &rows, width) + params.spacing.col_gap(0., 0.);
+    }
+    drawing
+}
+
+/// Weakly connected components as node lists and edge ids, self loops are left out.
+fn components(n: usize, edges: &[(usize, usize)]) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn find(parent: &mut [usize], mut v: usize) -> usize {
+        while parent[v] != v {
+            parent[v] = parent[parent[v]];
+            v = parent[v];
+        }
+        v
+    }
+    for &(a, b) in edges {
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        if ra != rb {
+            parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+
+    let mut slot = vec![NONE; n];
+    let mut res: Vec<(Vec<usize>, Vec<usize>)> = Vec::new();
+    for v in 0..n {
+        let root = find(&mut parent, v);
+        if slot[root] == NONE {
+            slot[root] = res.len();
+            res.push((Vec::new(), Vec::new()));
+        }
+        res[slot[root]].0.push(v);
+    }
+    for (id, &(a, b)) in edges.iter().enumerate() {
+        if a != b {
+            let root = find(&mut parent, a);
+            res[slot[root]].1.push(id);
+        }
+    }
+    res
+}
+
+/// Input edge drawn through a chain of layered nodes.
+struct Chain {
+    edge: usize,
+    reversed: bool,
+    /// Dummy nodes from the upper to the lower end.
+    dummies: Vec<usize>,
+}
+
+/// Layered graph of one component. Local nodes `0..real` are the input nodes, the rest are
+/// dummies.
+struct Component {
+    /// Input index of each real node.
+    input: Vec<usize>,
+    /// Acyclic edges between local nodes, upper end first.
+    dag: Vec<(usize, usize)>,
+    chains: Vec<Chain>,
+    rank: Vec<usize>,
+    /// Neighbors in the layer above and below.
+    up: Vec<Vec<usize>>,
+    down: Vec<Vec<usize>>,
+    layers: Vec<Vec<usize>>,
+    /// Index of every node in its layer.
+    pos: Vec<usize>,
+    x: Vec<f32>,
+}
+
+impl Component {
+    fn new(nodes: &[usize], edge_ids: &[usize], edges: &[(usize, usize)]) -> Self {
+        let local: std::collections::HashMap<usize, usize> =
+            nodes.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let m = nodes.len();
+
+        let mut out: Vec<Vec<(usize, usize)>> = vec![Vec::new(); m];
+        let mut indegree = vec![0; m];
+        let local_edges: Vec<(usize, usize)> = edge_ids
+            .iter()
+            .map(|&id| (local[&edges[id].0], local[&edges[id].1]))
+            .collect();
+        for (i, &(a, b)) in local_edges.iter().enumerate() {
+            out[a].push((b, i));
+            indegree[b] += 1;
+        }
+
+        // DFS from sources first, every edge to a node on the stack closes a cycle.
+        let mut reversed = vec![false; local_edges.len()];
+        let mut mark = vec![0u8; m];
+        let starts = (0..m)
+            .filter(|&v| indegree[v] == 0)
+            .chain((0..m).filter(|&v| indegree[v] != 0));
+        for s in starts {
+            if mark[s] != 0 {
+                continue;
+            }
+            let mut stack = vec![(s, 0usize)];
+            mark[s] = 1;
+            while let Some((v, next)) = stack.last_mut() {
+                let v = *v;
+                if let Some(&(w, e)) = out[v].get(*next) {
+                    *next += 1;
+                    match mark[w] {
+                        0 => {
+                            mark[w] = 1;
+                            stack.push((w, 0));
+                        }
+                        1 => reversed[e] = true,
+                        _ => {}
+                    }
+                } else {
+                    mark[v] = 2;
+                    stack.pop();
+                }
+            }
+        }
+
+        let dag = local_edges
+            .iter()
+            .zip(&reversed)
+            .map(|(&(a, b), &r)| if r { (b, a) } else { (a, b) })
+            .collect();
+        let chains = edge_ids
+            .iter()
+            .zip(reversed)
+            .map(|(&edge, reversed)| Chain {
+                edge,
+                reversed,
+                dummies: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            input: nodes.to_vec(),
+            dag,
+            chains,
+            rank: Vec::new(),
+            up: Vec::new(),
+            down: Vec::new(),
+            layers: Vec::new(),
+            pos: Vec::new(),
+            x: Vec::new(),
+        }
+    }
+
+    fn real(&self) -> usize {
+        self.input.len()
+    }
+
+    fn assign_layers(&mut self, layering: Layering) {
+        let m = self.real();
+        let mut rank = longest_path(m, &self.dag);
+        if layering == Layering::NetworkSimplex {
+            network_simplex(&mut rank, &self.dag);
+        }
+        let min = rank.iter().copied().min().unwrap_or(0);
+        self.rank = rank.into_iter().map(|r| (r - min) as usize).collect();
+    }
+
+    fn insert_dummies(&mut self) {
+        let m = self.real();
+        self.up = vec![Vec::new(); m];
+        self.down = vec![Vec::new(); m];
+        for (chain, &(a, b)) in self.chains.iter_mut().zip(&self.dag) {
+            let mut prev = a;
+            for r in self.rank[a] + 1..self.rank[b] {
+                let d = self.rank.len();
+                self.rank.push(r);
+                self.up.push(Vec::new());
+                self.down.push(Vec::new());
+                self.down[prev].push(d);
+                self.up[d].push(prev);
+                chain.dummies.push(d);
+                prev = d;
+            }
+            self.down[prev].push(b);
+            self.up[b].push(prev);
+        }
+    }
+
+    /// Initial order by DFS from the top layer so connected nodes start close together, then
+    /// alternating sweeps keeping the order with the fewest crossings.
+    fn order(&mut self, heuristic: CrossingHeuristic, sweeps: usize) {
+        let total = self.rank.len();
+        let depth = self.rank.iter().copied().max().map_or(0, |r| r + 1);
+        self.layers = vec![Vec::new(); depth];
+        let mut seen = vec![false; total];
+        let mut roots: Vec<usize> = (0..total).collect();
+        roots.sort_by_key(|&v| self.rank[v]);
+        for root in roots {
+            if seen[root] {
+                continue;
+            }
+            let mut stack = vec![root];
+            while let Some(v) = stack.pop() {
+                if seen[v] {
+                    continue;
+                }
+                seen[v] = true;
+                self.layers[self.rank[v]].push(v);
+                stack.extend(self.down[v].iter().rev().copied());
+            }
+        }
+        self.pos = vec![0; total];
+        self.update_positions();
+
+        let mut best = self.layers.clone();
+        let mut best_crossings = self.crossings();
+        for sweep in 0..sweeps {
+            if best_crossings == 0 {
+                break;
+            }
+            if sweep % 2 == 0 {
+                for r in 1..depth {
+                    self.reorder(r, true, heuristic);
+                }
+            } else {
+                for r in (0..depth.saturating_sub(1)).rev() {
+                    self.reorder(r, false, heuristic);
+                }
+            }
+            self.transpose();
+
+            let crossings = self.crossings();
+            if crossings < best_crossings {
+                best_crossings = crossings;
+                best.clone_from(&self.layers);
+            }
+        }
+        self.layers = best;
+        self.update_positions();
+    }
+
+    fn update_positions(&mut self) {
+        for layer in &self.layers {
+            for (i, &v) in layer.iter().enumerate() {
+                self.pos[v] = i;
+            }
+        }
+    }
+
+    /// Sorts layer `r` by the positions of the neighbors in the layer above or below.
+    fn reorder(&mut self, r: usize, from_above: bool, heuristic: CrossingHeuristic) {
+        let mut keyed: Vec<(f32, usize)> = self.layers[r]
+            .iter()
+            .map(|&v| {
+                let nbrs = if from_above {
+                    &self.up[v]
+                } else {
+                    &self.down[v]
+                };
+                let mut p: Vec<f32> = nbrs.iter().map(|&w| self.pos[w] as f32).collect();
+                p.sort_by(f32::total_cmp);
+                let key = match (p.len(), heuristic) {
+                    // Nodes without neighbors keep their place.
+                    (0, _) => self.pos[v] as f32,
+                    (len, CrossingHeuristic::Barycenter) => p.iter().sum::<f32>() / len as f32,
+                    (len, CrossingHeuristic::Median) => weighted_median(&p, len),
+                };
+                (key, v)
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self.layers[r] = keyed.into_iter().map(|(_, v)| v).collect();
+        for (i, &v) in self.layers[r].iter().enumerate() {
+            self.pos[v] = i;
+        }
+    }
+
+    /// Swaps adjacent nodes while that reduces crossings.
+    fn transpose(&mut self) {
+        for _ in 0..8 {
+            let mut improved = false;
+            for r in 0..self.layers.len() {
+                for i in 1..self.layers[r].len() {
+                    let (v, w) = (self.layers[r][i - 1], self.layers[r][i]);
+                    if self.pair_crossings(w, v) < self.pair_crossings(v, w) {
+                        self.layers[r].swap(i - 1, i);
+                        self.pos[v] = i;
+                        self.pos[w] = i - 1;
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+    }
+
+    /// Crossings among edges of `v` and `w` when `v` is left of `w`.
+    fn pair_crossings(&self, v: usize, w: usize) -> usize {
+        let mut count = 0;
+        for nbrs in [&self.up, &self.down] {
+            for &a in &nbrs[v] {
+                for &b in &nbrs[w] {
+                    if self.pos[a] > self.pos[b] {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    fn crossings(&self) -> usize {
+        let mut total = 0;
+        for r in 1..self.layers.len() {
+            let mut ends: Vec<(usize, usize)> = self.layers[r - 1]
+                .iter()
+                .flat_map(|&u| self.down[u].iter().map(move |&v| (u, v)))
+                .map(|(u, v)| (self.pos[u], self.pos[v]))
+                .collect();
+            ends.sort_unstable();
+            total += count_inversions(ends.into_iter().map(|(_, p)| p), self.layers[r].len());
+        }
+        total
+    }
+
+    /// Starts from evenly spaced layers and repeatedly moves every node toward its neighbors in
+    /// the previous layer of the pass while keeping the order and the gap given by `spacing`
+    /// and `widths` between neighbors. Edges between dummies weigh the most so long edges run
+    /// straight.
+    fn assign_coordinates(&mut self, widths: &[f32], spacing: &Spacing) {
+        let total = self.rank.len();
+        self.x = vec![0.; total];
+        // Offset of every node from the first one in its layer when packed tightly.
+        let offsets: Vec<Vec<f32>> = self
+            .layers
+            .iter()
+            .map(|layer| {
+                let mut offset = 0.;
+                layer
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| {
+                        if i > 0 {
+                            offset += spacing.col_gap(widths[layer[i - 1]], widths[v]);
+                        }
+                        offset
+                    })
+                    .collect()
+            })
+            .collect();
+        let span = |o: &Vec<f32>| o.last().copied().unwrap_or(0.);
+        let widest = offsets.iter().map(span).fold(0., f32::max);
+        for (layer, o) in self.layers.iter().zip(&offsets) {
+            let shift = (widest - span(o)) / 2.;
+            for (&v, &off) in layer.iter().zip(o) {
+                self.x[v] = shift + off;
+            }
+        }
+
+        let real = self.real();
+        let weight = |a: usize, b: usize| match (a >= real, b >= real) {
+            (true, true) => 8.,
+            (false, false) => 1.,
+            _ => 2.,
+        };
+        let depth = self.layers.len();
+        for pass in 0..COORDINATE_PASSES {
+            let down = pass % 2 == 0;
+            let order: Vec<usize> = if down {
+                (1..depth).collect()
+            } else {
+                (0..depth.saturating_sub(1)).rev().collect()
+            };
+            for r in order {
+                let (desired, weights): (Vec<f32>, Vec<f32>) = self.layers[r]
+                    .iter()
+                    .map(|&v| {
+                        let nbrs = if down { &self.up[v] } else { &self.down[v] };
+                        let w: f32 = nbrs.iter().map(|&u| weight(u, v)).sum();
+                        if w == 0. {
+                            (self.x[v], 1.)
+                        } else {
+                            let sum: f32 = nbrs.iter().map(|&u| weight(u, v) * self.x[u]).sum();
+                            (sum / w, w)
+                        }
+                    })
+                    .unzip();
+                let placed = place_ordered(&desired, &weights, &offsets[r]);
+                for (&v, x) in self.layers[r].iter().zip(placed) {
+                    self.x[v] = x;
+                }
+            }
+        }
+    }
+
+    /// Writes real nodes and bends shifted so the left border is at `x_offset` and ranks are
+    /// at `rows`, returns the right border.
+    fn write(
+        &self,
+        drawing: &mut Drawing,
+        x_offset: f32,
+        rows: &[f32],
+        width: impl Fn(usize) -> f32,
+    ) -> f32 {
+        let (min, max) = (0..self.x.len()).fold((f32::INFINITY, f32::NEG_INFINITY), |(l, r), v| {
+            let half = width(v) / 2.;
+            (l.min(self.x[v] - half), r.max(self.x[v] + half))
+        });
+        let at = |v: usize| Pos2::new(self.x[v] - min + x_offset, rows[self.rank[v]]);
+
+        for (v, &input) in self.input.iter().enumerate() {
+            drawing.nodes[input] = at(v);
+        }
+        for chain in &self.chains {
+            let mut bends: Vec<Pos2> = chain.dummies.iter().map(|&d| at(d)).collect();
+            if chain.reversed {
+                bends.reverse();
+            }
+            drawing.bends[chain.edge] = bends;
+        }
+        max - min + x_offset
+    }
+}
+
+/// Gansner et al. weighted median of sorted positions, interpolated toward the denser side.
+fn weighted_median(p: &[f32], len: usize) -> f32 {
+    let mid = len / 2;
+    if len % 2 == 1 {
+        return p[mid];
+    }
+    if len == 2 {
+        return f32::midpoint(p[0], p[1]);
+    }
+    let left = p[mid - 1] - p[0];
+    let right = p[len - 1] - p[mid];
+    if left + right == 0. {
+        f32::midpoint(p[mid - 1], p[mid])
+    } else {
+        (p[mid - 1] * right + p[mid] * left) / (left + right)
+    }
+}
+
+/// Number of pairs out of order in `seq` of values below `bound`, O(n log bound).
+fn count_inversions(seq: impl Iterator<Item = usize>, bound: usize) -> usize {
+    let mut tree = vec![0usize; bound + 1];
+    let mut inversions = 0;
+    for (seen, p) in seq.enumerate() {
+        // Values not greater than p seen so far.
+        let mut i = p + 1;
+        let mut not_greater = 0;
+        while i > 0 {
+            not_greater += tree[i];
+            i &= i - 1;
+        }
+        inversions += seen - not_greater;
+        let mut i = p + 1;
+        while i <= bound {
+            tree[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+    inversions
+}
+
+/// Positions closest to `desired` in the weighted least squares sense keeping the order and
+/// neighbors at least as far apart as in the tightly packed `offsets`, by pool adjacent
+/// violators on `desired[i] - offsets[i]`.
+fn place_ordered(desired: &[f32], weights: &[f32], offsets: &[f32]) -> Vec<f32> {
+    // Blocks of (weighted mean, weight, count).
+    let mut blocks: Vec<(f32, f32, usize)> = Vec::with_capacity(desired.len());
+    for ((&d, &w), &o) in desired.iter().zip(weights).zip(offsets) {
+        blocks.push((d - o, w, 1));
+        while blocks.len() >= 2 {
+            let (m2, w2, c2) = blocks[blocks.len() - 1];
+            let (m1, w1, c1) = blocks[blocks.len() - 2];
+            if m1 <= m2 {
+                break;
+            }
+            blocks.pop();
+            let last = blocks.len() - 1;
+            blocks[last] = ((m1 * w1 + m2 * w2) / (w1 + w2), w1 + w2, c1 + c2);
+        }
+    }
+
+    let mut res = Vec::with_capacity(desired.len());
+    for (mean, _, count) in blocks {
+        for _ in 0..count {
+            res.push(mean + offsets[res.len()]);
+        }
+    }
+    res
+}
+
+/// Ranks with every node one below its lowest predecessor, sources at 0.
+fn longest_path(m: usize, dag: &[(usize, usize)]) -> Vec<i64> {
+    let mut indegree = vec![0; m];
+    let mut out = vec![Vec::new(); m];
+    for &(a, b) in dag {
+        out[a].push(b);
+        indegree[b] += 1;
+    }
+    let mut rank = vec![0i64; m];
+    let mut queue: Vec<usize> = (0..m).filter(|&v| indegree[v] == 0).collect();
+    while let Some(v) = queue.pop() {
+        for &w in &out[v] {
+            rank[w] = rank[w].max(rank[v] + 1);
+            indegree[w] -= 1;
+            if indegree[w] == 0 {
+                queue.push(w);
+            }
+        }
+    }
+    rank
+}
+
+/// Network simplex of Gansner et al. minimizing the total edge length of a connected DAG with
+/// feasible `rank`.
+fn network_simplex(rank: &mut [i64], dag: &[(usize, usize)]) {
+    let m = rank.len();
+    if m < 2 || dag.is_empty() {
+        return;
+    }
+    let mut adj: Vec<Vec<usize>> = vec![Vec::new(); m];
+    for (e, &(a, b)) in dag.iter().enumerate() {
+        adj[a].push(e);
+        adj[b].push(e);
+    }
+    let slack = |rank: &[i64], e: usize| rank[dag[e].1] - rank[dag[e].0] - 1;
+
+    let mut tree = feasible_tree(rank, dag, &adj);
+    for _ in 0..NETWORK_SIMPLEX_MAX_ITER {
+        let rooted = RootedTree::new(m, dag, &adj, &tree);
+        let Some((leave, tail_is_subtree, child)) = (0..dag.len())
+            .filter(|&e| tree[e])
+            .map(|e| (e, rooted.cut_value(e, dag)))
+            .filter(|&(_, cut)| cut < 0)
+            .min_by_key(|&(_, cut)| cut)
+            .map(|(e, _)| {
+                let child = rooted.child_of(e, dag);
+                (e, child == dag[e].0, child)
+            })
+        else {
+            break;
+        };
+
+        let in_tail = |v: usize| rooted.in_subtree(child, v) == tail_is_subtree;
+        let Some(enter) = (0..dag.len())
+            .filter(|&f| !tree[f] && !in_tail(dag[f].0) && in_tail(dag[f].1))
+            .min_by_key(|&f| slack(rank, f))
+        else {
+            break;
+        };
+
+        let delta = slack(rank, enter);
+        for (v, r) in rank.iter_mut().enumerate() {
+            if in_tail(v) {
+                *r -= delta;
+            }
+        }
+        tree[leave] = false;
+        tree[enter] = true;
+    }
+}
+
+/// Spanning tree of tight edges, shifting ranks of the partial tree until it spans.
+fn feasible_tree(rank: &mut [i64], dag: &[(usize, usize)], adj: &[Vec<usize>]) -> Vec<bool> {
+    let m = rank.len();
+    loop {
+        let mut tree = vec![false; dag.len()];
+        let mut in_tree = vec![false; m];
+        in_tree[0] = true;
+        let mut size = 1;
+        let mut stack = vec![0];
+        while let Some(v) = stack.pop() {
+            for &e in &adj[v] {
+                let (a, b) = dag[e];
+                let w = if a == v { b } else { a };
+                if !in_tree[w] && rank[b] - rank[a] == 1 {
+                    in_tree[w] = true;
+                    tree[e] = true;
+                    size += 1;
+                    stack.push(w);
+                }
+            }
+        }
+        if size == m {
+            return tree;
+        }
+
+        let Some((e, slack)) = (0..dag.len())
+            .filter(|&e| in_tree[dag[e].0] != in_tree[dag[e].1])
+            .map(|e| (e, rank[dag[e].1] - rank[dag[e].0] - 1))
+            .min_by_key(|&(_, slack)| slack)
+        else {
+            // Not connected, cannot happen for a component.
+            return tree;
+        };
+        let delta = if in_tree[dag[e].0] { slack } else { -slack };
+        for (v, r) in rank.iter_mut().enumerate() {
+            if in_tree[v] {
+                *r += delta;
+            }
+        }
+    }
+}
+
+/// Spanning tree rooted at node 0 with postorder numbers for constant time subtree tests.
+struct RootedTree {
+    parent_edge: Vec<usize>,
+    low: Vec<usize>,
+    lim: Vec<usize>,
+    /// Out degree minus in degree summed over the subtree of every node.
+    balance: Vec<i64>,
+}
+
+impl RootedTree {
+    fn new(m: usize, dag: &[(usize, usize)], adj: &[Vec<usize>], tree: &[bool]) -> Self {
+        let mut res = Self {
+            parent_edge: vec![NONE; m],
+            low: vec![0; m],
+            lim: vec![0; m],
+            balance: vec![0; m],
+        };
+        for &(a, b) in dag {
+            res.balance[a] += 1;
+            res.balance[b] -= 1;
+        }
+        let mut visited = vec![false; m];
+        let mut counter = 0;
+        let mut stack = vec![(0usize, 0usize)];
+        visited[0] = true;
+        res.low[0] = counter;
+        while let Some((v, next)) = stack.last_mut() {
+            let v = *v;
+            if let Some(&e) = adj[v].get(*next) {
+                *next += 1;
+                let (a, b) = dag[e];
+                let w = if a == v { b } else { a };
+                if tree[e] && !visited[w] {
+                    visited[w] = true;
+                    res.parent_edge[w] = e;
+                    res.low[w] = counter;
+                    stack.push((w, 0));
+                }
+            } else {
+                res.lim[v] = counter;
+                counter += 1;
+                stack.pop();
+                // Children are finished before their parent.
+                if let Some(&e) = res.parent_edge.get(v).filter(|&&e| e != NONE) {
+                    let (a, b) = dag[e];
+                    let parent = if a == v { b } else { a };
+                    res.balance[parent] += res.balance[v];
+                }
+            }
+        }
+        res
+    }
+
+    /// Endpoint of the tree edge `e` farther from the root.
+    fn child_of(&self, e: usize, dag: &[(usize, usize)]) -> usize {
+        let (a, b) = dag[e];
+        if self.parent_edge[a] == e {
+            a
+        } else {
+            b
+        }
+    }
+
+    fn in_subtree(&self, root: usize, v: usize) -> bool {
+        self.low[root] <= self.lim[v] && self.lim[v] <= self.lim[root]
+    }
+
+    /// Edges from the tail component of `e` to its head component minus the reverse.
+    ///
+    /// Edges within a subtree cancel out in its balance, which leaves the edges leaving the
+    /// subtree minus the ones entering it.
+    fn cut_value(&self, e: usize, dag: &[(usize, usize)]) -> i64 {
+        let child = self.child_of(e, dag);
+        if child == dag[e].0 {
+            self.balance[child]
+        } else {
+            -self.balance[child]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(layering: Layering) -> Params {
+        Params {
+            spacing: Spacing {
+                row_dist: 50.,
+                col_dist: 40.,
+                margin: 0.,
+            },
+            extents: Vec::new(),
+            layering,
+            heuristic: CrossingHeuristic::Median,
+            sweeps: 24,
+        }
+    }
+
+    fn layer(d: &Drawing, v: usize) -> i32 {
+        (d.nodes[v].y / 50.).round() as i32
+    }
+
+    #[test]
+    fn long_edges_get_bends_on_intermediate_layers() {
+        // 0 -> 1 -> 2 -> 3 and a shortcut 0 -> 3.
+        let edges = [(0, 1), (1, 2), (2, 3), (0, 3)];
+        let d = draw(4, &edges, &params(Layering::LongestPath));
+
+        assert_eq!(
+            (0..4).map(|v| layer(&d, v)).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert!(d.bends[..3].iter().all(Vec::is_empty));
+        let ys: Vec<f32> = d.bends[3].iter().map(|p| p.y).collect();
+        assert_eq!(ys, [50., 100.]);
+    }
+
+    #[test]
+    fn cycles_are_drawn_with_reversed_edges() {
+        let edges = [(0, 1), (1, 2), (2, 0)];
+        let d = draw(3, &edges, &params(Layering::LongestPath));
+
+        let mut layers: Vec<_> = (0..3).map(|v| layer(&d, v)).collect();
+        layers.sort_unstable();
+        assert_eq!(layers, [0, 1, 2]);
+        // The back edge spans two layers and runs upward through its bend.
+        assert_eq!(d.bends[2].len(), 1);
+        assert_eq!(d.bends[2][0].y, 50.);
+        assert!(d.nodes[2].y > d.nodes[0].y);
+    }
+
+    #[test]
+    fn network_simplex_shortens_edges() {
+        // A chain and a source that longest path puts in the top layer.
+        let edges = [(0, 1), (1, 2), (2, 3), (4, 3)];
+        let total =
+            |d: &Drawing| -> i32 { edges.iter().map(|&(a, b)| layer(d, b) - layer(d, a)).sum() };
+
+        let longest = draw(5, &edges, &params(Layering::LongestPath));
+        let simplex = draw(5, &edges, &params(Layering::NetworkSimplex));
+        assert_eq!(total(&longest), 6);
+        assert_eq!(total(&simplex), 4);
+        assert!(simplex.bends.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn cut_values_count_crossing_edges() {
+        let dag = [(0, 1), (1, 2), (2, 3), (4, 3), (0, 4), (1, 3), (4, 5)];
+        let mut rank = longest_path(6, &dag);
+        let mut adj = vec![Vec::new(); 6];
+        for (e, &(a, b)) in dag.iter().enumerate() {
+            adj[a].push(e);
+            adj[b].push(e);
+        }
+        let tree = feasible_tree(&mut rank, &dag, &adj);
+        let rooted = RootedTree::new(6, &dag, &adj, &tree);
+
+        for e in (0..dag.len()).filter(|&e| tree[e]) {
+            let child = rooted.child_of(e, &dag);
+            let tail_is_subtree = child == dag[e].0;
+            let in_tail = |v: usize| rooted.in_subtree(child, v) == tail_is_subtree;
+            let expected: i64 = dag
+                .iter()
+                .map(|&(a, b)| match (in_tail(a), in_tail(b)) {
+                    (true, false) => 1,
+                    (false, true) => -1,
+                    _ => 0,
+                })
+                .sum();
+            assert_eq!(rooted.cut_value(e, &dag), expected, "edge {e}");
+        }
+    }
+
+    #[test]
+    fn sweeps_remove_avoidable_crossings() {
+        // Two parents whose children are interleaved by the initial DFS order.
+        let edges = [(0, 2), (0, 3), (1, 4), (1, 5), (0, 4), (5, 6), (3, 7)];
+        let mut c = Component::new(
+            &(0..8).collect::<Vec<_>>(),
+            &(0..7).collect::<Vec<_>>(),
+            &edges,
+        );
+        c.assign_layers(Layering::LongestPath);
+        c.insert_dummies();
+        c.order(CrossingHeuristic::Median, 24);
+        assert_eq!(c.crossings(), 0);
+    }
+
+    #[test]
+    fn coordinates_keep_order_and_separation() {
+        let edges = [(0, 1), (0, 2), (0, 3), (1, 4), (3, 4), (5, 4)];
+        let d = draw(6, &edges, &params(Layering::NetworkSimplex));
+        let mut by_layer: std::collections::BTreeMap<i32, Vec<f32>> = Default::default();
+        for v in 0..6 {
+            by_layer.entry(layer(&d, v)).or_default().push(d.nodes[v].x);
+        }
+        for xs in by_layer.values_mut() {
+            xs.sort_by(f32::total_cmp);
+            assert!(xs.windows(2).all(|w| w[1] - w[0] >= 40. - 1e-3), "{xs:?}");
+        }
+        // The parent is centered above its three children.
+        assert!((d.nodes[0].x - d.nodes[2].x).abs() < 1e-3);
+    }
+
+    #[test]
+    fn components_are_packed_side_by_side() {
+        let edges = [(0, 1), (2, 3), (4, 4)];
+        let d = draw(5, &edges, &params(Layering::LongestPath));
+        assert!(d.nodes[2].x >= d.nodes[0].x + 40.);
+        assert!(d.nodes[4].x >= d.nodes[2].x + 40.);
+        assert!(d.bends[2].is_empty());
+    }
+
+    #[test]
+    fn extents_widen_levels_and_gaps() {
+        // Node 1 is wide and tall, its sibling 2 and the next level make room for it.
+        let edges = [(0, 1), (0, 2), (1, 3)];
+        let mut p = params(Layering::LongestPath);
+        p.spacing.margin = 10.;
+        p.extents = vec![(0., 0.), (100., 200.), (0., 0.), (0., 0.)];
+        let d = draw(4, &edges, &p);
+        assert!((d.nodes[1].x - d.nodes[2].x).abs() >= 110. - 1e-3);
+        assert_eq!(d.nodes[1].y - d.nodes[0].y, 60.);
+        assert_eq!(d.nodes[3].y - d.nodes[1].y, 60.);
+    }
+
+    #[test]
+    fn helpers() {
+        assert_eq!(count_inversions([2, 0, 1].into_iter(), 3), 2);
+        assert_eq!(
+            place_ordered(&[0., 0., 0.], &[1., 1., 1.], &[0., 10., 20.]),
+            [-10., 0., 10.]
+        );
+        // Leans toward the denser left side.
+        assert!((weighted_median(&[0., 1., 2., 10.], 4) - 10. / 9.).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,275 @@
+//! Tidy tree drawing of Reingold and Tilford as generalized by Walker, in the linear time
+//! formulation of Buchheim, Jünger and Leipert.
+//!
+//! Subtrees are packed as close as their contours allow, parents are centered above their
+//! children and smaller subtrees between larger ones are spaced evenly.
+
+const NONE: usize = usize::MAX;
+
+/// Tree over `0..n` with node 0 as root and children in drawing order.
+pub(crate) struct Tree {
+    pub children: Vec<Vec<usize>>,
+}
+
+/// Horizontal positions with at least `sep(left, right)` between the centers of neighbors on
+/// a level, the leftmost node is at 0.
+pub(crate) fn positions(tree: &Tree, sep: &dyn Fn(usize, usize) -> f32) -> Vec<f32> {
+    let n = tree.children.len();
+    let mut w = Walker::new(tree, sep);
+    for v in postorder(tree) {
+        w.first_walk(v);
+    }
+    w.prelim[0] = w.mid[0];
+
+    let mut x = vec![0.; n];
+    let mut stack = vec![(0, 0.)];
+    while let Some((v, m)) = stack.pop() {
+        x[v] = w.prelim[v] + m;
+        for &c in &tree.children[v] {
+            stack.push((c, m + w.modifier[v]));
+        }
+    }
+
+    let min = x.iter().copied().fold(f32::INFINITY, f32::min);
+    for v in &mut x {
+        *v -= min;
+    }
+    x
+}
+
+fn postorder(tree: &Tree) -> Vec<usize> {
+    let mut order = Vec::with_capacity(tree.children.len());
+    let mut stack = vec![(0, false)];
+    while let Some((v, expanded)) = stack.pop() {
+        if expanded {
+            order.push(v);
+            continue;
+        }
+        stack.push((v, true));
+        for &c in tree.children[v].iter().rev() {
+            stack.push((c, false));
+        }
+    }
+    order
+}
+
+struct Walker<'a> {
+    tree: &'a Tree,
+    sep: &'a dyn Fn(usize, usize) -> f32,
+    parent: Vec<usize>,
+    /// 1-based position among the siblings.
+    number: Vec<usize>,
+    prelim: Vec<f32>,
+    /// Preliminary position of a node relative to the midpoint of its children.
+    mid: Vec<f32>,
+    modifier: Vec<f32>,
+    shift: Vec<f32>,
+    change: Vec<f32>,
+    thread: Vec<usize>,
+    ancestor: Vec<usize>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(tree: &'a Tree, sep: &'a dyn Fn(usize, usize) -> f32) -> Self {
+        let n = tree.children.len();
+        let mut parent = vec![NONE; n];
+        let mut number = vec![1; n];
+        for (v, children) in tree.children.iter().enumerate() {
+            for (i, &c) in children.iter().enumerate() {
+                parent[c] = v;
+                number[c] = i + 1;
+            }
+        }
+        Self {
+            tree,
+            sep,
+            parent,
+            number,
+            prelim: vec![0.; n],
+            mid: vec![0.; n],
+            modifier: vec![0.; n],
+            shift: vec![0.; n],
+            change: vec![0.; n],
+            thread: vec![NONE; n],
+            ancestor: (0..n).collect(),
+        }
+    }
+
+    fn left_sibling(&self, v: usize) -> Option<usize> {
+        let p = self.parent[v];
+        (p != NONE && self.number[v] > 1).then(|| self.tree.children[p][self.number[v] - 2])
+    }
+
+    fn next_left(&self, v: usize) -> usize {
+        self.tree.children[v]
+            .first()
+            .copied()
+            .unwrap_or(self.thread[v])
+    }
+
+    fn next_right(&self, v: usize) -> usize {
+        self.tree.children[v]
+            .last()
+            .copied()
+            .unwrap_or(self.thread[v])
+    }
+
+    /// Runs once all children of `v` are done: places every child next to its left sibling
+    /// and separates it from the contour of the subtrees already placed.
+    fn first_walk(&mut self, v: usize) {
+        let children = &self.tree.children[v];
+        let (Some(&first), Some(&last)) = (children.first(), children.last()) else {
+            return;
+        };
+
+        let mut default_ancestor = first;
+        for &w in children {
+            match self.left_sibling(w) {
+                Some(l) => {
+                    self.prelim[w] = self.prelim[l] + (self.sep)(l, w);
+                    if !self.tree.children[w].is_empty() {
+                        self.modifier[w] = self.prelim[w] - self.mid[w];
+                    }
+                }
+                None => self.prelim[w] = self.mid[w],
+            }
+            default_ancestor = self.apportion(w, default_ancestor);
+        }
+        self.execute_shifts(v);
+        self.mid[v] = f32::midpoint(self.prelim[first], self.prelim[last]);
+    }
+
+    fn apportion(&mut self, v: usize, mut default_ancestor: usize) -> usize {
+        let Some(w) = self.left_sibling(v) else {
+            return default_ancestor;
+        };
+
+        let (mut vir, mut vor) = (v, v);
+        let mut vil = w;
+        let mut vol = self.tree.children[self.parent[v]][0];
+        let (mut sir, mut sor) = (self.modifier[vir], self.modifier[vor]);
+        let (mut sil, mut sol) = (self.modifier[vil], self.modifier[vol]);
+        while self.next_right(vil) != NONE && self.next_left(vir) != NONE {
+            vil = self.next_right(vil);
+            vir = self.next_left(vir);
+            vol = self.next_left(vol);
+            vor = self.next_right(vor);
+            self.ancestor[vor] = v;
+            let shift = (self.prelim[vil] + sil) - (self.prelim[vir] + sir) + (self.sep)(vil, vir);
+            if shift > 0. {
+                let a = self.left_ancestor(vil, v, default_ancestor);
+                self.move_subtree(a, v, shift);
+                sir += shift;
+                sor += shift;
+            }
+            sil += self.modifier[vil];
+            sir += self.modifier[vir];
+            sol += self.modifier[vol];
+            sor += self.modifier[vor];
+        }
+        if self.next_right(vil) != NONE && self.next_right(vor) == NONE {
+            self.thread[vor] = self.next_right(vil);
+            self.modifier[vor] += sil - sor;
+        }
+        if self.next_left(vir) != NONE && self.next_left(vol) == NONE {
+            self.thread[vol] = self.next_left(vir);
+            self.modifier[vol] += sir - sol;
+            default_ancestor = v;
+        }
+        default_ancestor
+    }
+
+    fn left_ancestor(&self, vil: usize, v: usize, default_ancestor: usize) -> usize {
+        let a = self.ancestor[vil];
+        if self.parent[a] == self.parent[v] {
+            a
+        } else {
+            default_ancestor
+        }
+    }
+
+    fn move_subtree(&mut self, wl: usize, wr: usize, shift: f32) {
+        let subtrees = (self.number[wr] - self.number[wl]) as f32;
+        self.change[wr] -= shift / subtrees;
+        self.shift[wr] += shift;
+        self.change[wl] += shift / subtrees;
+        self.prelim[wr] += shift;
+        self.modifier[wr] += shift;
+    }
+
+    fn execute_shifts(&mut self, v: usize) {
+        let (mut shift, mut change) = (0., 0.);
+        for &w in self.tree.children[v].iter().rev() {
+            self.prelim[w] += shift;
+            self.modifier[w] += shift;
+            change += self.change[w];
+            shift += self.shift[w] + change;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(edges: &[(usize, usize)], n: usize) -> Tree {
+        let mut children = vec![Vec::new(); n];
+        for &(p, c) in edges {
+            children[p].push(c);
+        }
+        Tree { children }
+    }
+
+    #[test]
+    fn parents_are_centered_over_children() {
+        let t = tree(&[(0, 1), (0, 2), (0, 3), (1, 4), (1, 5)], 6);
+        let x = positions(&t, &|_, _| 1.);
+        assert_eq!(x[4], 0.);
+        assert_eq!(x[5], 1.);
+        assert_eq!(x[1], 0.5);
+        assert_eq!(x[0], x[2]);
+        assert_eq!(x[0], f32::midpoint(x[1], x[3]));
+        assert_eq!(x[3] - x[2], 1.);
+    }
+
+    #[test]
+    fn separation_is_per_pair() {
+        let t = tree(&[(0, 1), (0, 2), (1, 3), (2, 4)], 5);
+        let width = [1., 1., 1., 5., 1.];
+        let x = positions(&t, &|a, b| f32::midpoint(width[a], width[b]));
+        // The wide node pushes its cousin and with it both parents apart.
+        assert_eq!(x[4] - x[3], 3.);
+        assert_eq!(x[2] - x[1], 3.);
+    }
+
+    #[test]
+    fn subtrees_pack_by_contour() {
+        // Two deep chains whose contours only meet at the bottom, and a leaf between them
+        // spaced evenly.
+        let t = tree(
+            &[
+                (0, 1),
+                (0, 2),
+                (0, 3),
+                (1, 4),
+                (4, 5),
+                (4, 6),
+                (3, 7),
+                (7, 8),
+                (7, 9),
+            ],
+            10,
+        );
+        let x = positions(&t, &|_, _| 1.);
+        // Same depth nodes keep at least the sibling distance.
+        let levels: [&[usize]; 3] = [&[1, 2, 3], &[4, 7], &[5, 6, 8, 9]];
+        for level in levels {
+            let mut xs: Vec<f32> = level.iter().map(|&v| x[v]).collect();
+            xs.sort_by(f32::total_cmp);
+            assert!(xs.windows(2).all(|w| w[1] - w[0] >= 1. - 1e-5), "{xs:?}");
+        }
+        // The two bottom pairs touch at the sibling distance.
+        assert!((x[8] - x[6] - 1.).abs() < 1e-5);
+        assert!((x[2] - f32::midpoint(x[1], x[3])).abs() < 1e-5);
+    }
+}
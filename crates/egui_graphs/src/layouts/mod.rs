@@ -1,6 +1,15 @@
+pub mod circular;
 pub mod force_directed;
+pub mod grid;
 pub mod hierarchical;
 pub mod random;
+pub mod shell;
+pub mod stress;
 
 mod layout;
+mod node_key;
+mod transition;
 pub use layout::{AnimatedState, Layout, LayoutState};
+pub use node_key::NodeKey;
+pub use transition::Easing;
+pub(crate) use transition::Transition;
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{DisplayEdge, DisplayNode, Graph};
+
+/// Value nodes are ordered or grouped by in the one-shot layouts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum NodeKey {
+    /// Node index.
+    #[default]
+    Index,
+    /// Number of visible neighbors.
+    Degree,
+    /// Values by node index, e.g. a centrality computed by the application. Nodes without a
+    /// value get 0.
+    Custom(HashMap<usize, f32>),
+}
+
+impl NodeKey {
+    /// Visible nodes with their key sorted ascending, ties keep the index order.
+    pub(crate) fn sorted<N, E, Ty, Ix, Dn, De>(
+        &self,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+    ) -> Vec<(NodeIndex<Ix>, f32)>
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let mut res: Vec<(NodeIndex<Ix>, f32)> = g
+            .visible_node_indices()
+            .map(|idx| {
+                let key = match self {
+                    NodeKey::Index => idx.index() as f32,
+                    NodeKey::Degree => g.visible_neighbors(idx, None).len() as f32,
+                    NodeKey::Custom(values) => values.get(&idx.index()).copied().unwrap_or(0.),
+                };
+                (idx, key)
+            })
+            .collect();
+        res.sort_by(|a, b| a.1.total_cmp(&b.1));
+        res
+    }
+}
@@ -36,8 +36,13 @@ impl Layout<State> for Random {
             return;
         }
 
+        g.clear_edge_bends();
         let mut rng = rand::rng();
-        for node in g.g_mut().node_weights_mut() {
+        let indices: Vec<_> = g.g().node_indices().collect();
+        for idx in indices {
+            let Some(node) = g.node_mut(idx).filter(|n| !n.pinned()) else {
+                continue;
+            };
             node.set_location(Pos2::new(
                 rng.random_range(0. ..SPAWN_SIZE),
                 rng.random_range(0. ..SPAWN_SIZE),
@@ -0,0 +1,162 @@
+use std::f32::consts::TAU;
+
+use egui::{Pos2, Vec2};
+use petgraph::{csr::IndexType, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{Layout, LayoutState, NodeKey},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Metric assigning nodes to shells, the highest values go to the innermost shell.
+    pub metric: NodeKey,
+    /// Number of shells holding equally many nodes. None puts every distinct metric value on
+    /// its own shell.
+    pub shells: Option<usize>,
+    /// Radial distance between consecutive shells.
+    pub shell_dist: f32,
+    /// Minimal arc length between neighbors, crowded shells grow beyond `shell_dist`.
+    pub spacing: f32,
+    /// Angle of the first node of every shell in radians, 0 points right.
+    pub start_angle: f32,
+    /// Place nodes clockwise on screen instead of counter-clockwise.
+    pub clockwise: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            metric: NodeKey::Degree,
+            shells: None,
+            shell_dist: 80.,
+            spacing: 40.,
+            start_angle: 0.,
+            clockwise: false,
+        }
+    }
+}
+
+impl LayoutState for State {}
+
+/// Places nodes on concentric circles around the origin grouped by a metric. A single node
+/// in the innermost shell takes the center. Applies once.
+#[derive(Debug, Default)]
+pub struct Shell {
+    state: State,
+}
+
+impl Layout<State> for Shell {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        g.clear_edge_bends();
+        layout_shell(g, &self.state);
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Shell { state }
+    }
+}
+
+fn layout_shell<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut nodes = state.metric.sorted(g);
+    nodes.reverse();
+
+    let shells: Vec<&[_]> = match state.shells {
+        Some(count) => nodes
+            .chunks(nodes.len().div_ceil(count.max(1)).max(1))
+            .collect(),
+        None => nodes.chunk_by(|a, b| a.1 == b.1).collect(),
+    };
+
+    let sign = if state.clockwise { 1. } else { -1. };
+    let mut radius: f32 = 0.;
+    for (k, shell) in shells.into_iter().enumerate() {
+        let n = shell.len() as f32;
+        let fit = n * state.spacing / TAU;
+        radius = if k == 0 && shell.len() == 1 {
+            0.
+        } else {
+            (radius + state.shell_dist).max(fit)
+        };
+        // Ties were reversed together with the keys, restore the index order inside a shell.
+        for (i, &(idx, _)) in shell.iter().rev().enumerate() {
+            let angle = state.start_angle + sign * i as f32 / n * TAU;
+            if let Some(node) = g.node_mut(idx).filter(|n| !n.pinned()) {
+                node.set_location(Pos2::ZERO + Vec2::angled(angle) * radius);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    fn radius(g: &Graph, idx: petgraph::stable_graph::NodeIndex) -> f32 {
+        g.node(idx).unwrap().location().to_vec2().length()
+    }
+
+    #[test]
+    fn hubs_go_inside() {
+        // A star with two leaves sharing an extra edge.
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..5).map(|_| g.add_node(())).collect();
+        for &leaf in &n[1..] {
+            g.add_edge(n[0], leaf, ());
+        }
+        g.add_edge(n[1], n[2], ());
+
+        layout_shell(&mut g, &State::default());
+        assert_eq!(radius(&g, n[0]), 0.);
+        assert!((radius(&g, n[1]) - 80.).abs() < 1e-3);
+        assert!((radius(&g, n[2]) - 80.).abs() < 1e-3);
+        assert!((radius(&g, n[3]) - 160.).abs() < 1e-3);
+        assert!((radius(&g, n[4]) - 160.).abs() < 1e-3);
+
+        let st = State {
+            metric: NodeKey::Index,
+            shells: Some(2),
+            spacing: 200.,
+            ..State::default()
+        };
+        layout_shell(&mut g, &st);
+        // Three nodes in the inner shell need more room than `shell_dist`.
+        let inner = 3. * 200. / TAU;
+        for &i in &n[2..] {
+            assert!((radius(&g, i) - inner).abs() < 1e-3);
+        }
+        assert!((radius(&g, n[0]) - (inner + 80.)).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,336 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use egui::{Pos2, Vec2};
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Upper bound of majorization iterations, each moves every node once.
+    pub iterations: usize,
+    /// Stops once an iteration lowers the stress by less than this fraction.
+    pub tolerance: f32,
+    /// Ideal length of an edge of weight 1. Ideal distances of other pairs are shortest path
+    /// lengths in these units.
+    pub edge_length: f32,
+    /// Scale edge lengths by [`Graph::edge_weight`]. Non-positive weights count as 1.
+    pub use_edge_weights: bool,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            iterations: 300,
+            tolerance: 1e-4,
+            edge_length: 60.,
+            use_edge_weights: true,
+        }
+    }
+}
+
+impl LayoutState for State {}
+
+/// Stress majorization: places nodes so their distances match shortest path distances, the
+/// energy of Kamada-Kawai minimized by the solver of Gansner, Koren and North.
+///
+/// Starts from the current locations and keeps their centroid, or the pinned nodes in place
+/// when there are any. Suited for small and medium graphs as it needs all pairs distances.
+/// Applies once.
+#[derive(Debug, Default)]
+pub struct Stress {
+    state: State,
+}
+
+impl Layout<State> for Stress {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        g.clear_edge_bends();
+        layout_stress(g, &self.state);
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Stress { state }
+    }
+}
+
+fn layout_stress<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let nodes: Vec<NodeIndex<Ix>> = g.visible_node_indices().collect();
+    if nodes.len() < 2 {
+        return;
+    }
+    let local: HashMap<NodeIndex<Ix>, usize> =
+        nodes.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+
+    // Hidden and filtered edges do not pull, direction is ignored.
+    let mut adj: Vec<Vec<(usize, f32)>> = vec![Vec::new(); nodes.len()];
+    for (idx, _) in g.edges_iter() {
+        let Some((a, b)) = g.visible_edge_endpoints(idx) else {
+            continue;
+        };
+        let (a, b) = (local[&a], local[&b]);
+        if a == b {
+            continue;
+        }
+        let w = if state.use_edge_weights {
+            g.edge_weight(idx)
+                .filter(|w| w.is_finite() && *w > 0.)
+                .unwrap_or(1.)
+        } else {
+            1.
+        };
+        adj[a].push((b, w * state.edge_length));
+        adj[b].push((a, w * state.edge_length));
+    }
+
+    let mut pos: Vec<Vec2> = nodes
+        .iter()
+        .map(|&n| g.node(n).map_or(Vec2::ZERO, |n| n.location().to_vec2()))
+        .collect();
+    let fixed: Vec<bool> = nodes.iter().map(|&n| g.is_node_pinned(n)).collect();
+    let centroid = pos.iter().fold(Vec2::ZERO, |acc, p| acc + *p) / pos.len() as f32;
+
+    let dist = distances(&adj, state.edge_length);
+    separate_coincident(&mut pos, &fixed, state.edge_length);
+    majorize(&mut pos, &dist, &fixed, state.iterations, state.tolerance);
+
+    let shift = if fixed.contains(&true) {
+        Vec2::ZERO
+    } else {
+        centroid - pos.iter().fold(Vec2::ZERO, |acc, p| acc + *p) / pos.len() as f32
+    };
+    for (n, p) in nodes.into_iter().zip(pos) {
+        if let Some(node) = g.node_mut(n).filter(|n| !n.pinned()) {
+            node.set_location(Pos2::ZERO + p + shift);
+        }
+    }
+}
+
+/// All pairs shortest path lengths as a row-major matrix. Pairs in different components are
+/// placed one `gap` farther apart than the farthest connected pair.
+fn distances(adj: &[Vec<(usize, f32)>], gap: f32) -> Vec<f32> {
+    let n = adj.len();
+    let mut dist = vec![f32::INFINITY; n * n];
+    let mut heap = BinaryHeap::new();
+    for s in 0..n {
+        let row = &mut dist[s * n..(s + 1) * n];
+        row[s] = 0.;
+        // Bits of non-negative floats order like their values.
+        heap.push(Reverse((0f32.to_bits(), s)));
+        while let Some(Reverse((d, v))) = heap.pop() {
+            let d = f32::from_bits(d);
+            if d > row[v] {
+                continue;
+            }
+            for &(w, len) in &adj[v] {
+                let nd = d + len;
+                if nd < row[w] {
+                    row[w] = nd;
+                    heap.push(Reverse((nd.to_bits(), w)));
+                }
+            }
+        }
+    }
+
+    let max = dist
+        .iter()
+        .copied()
+        .filter(|d| d.is_finite())
+        .fold(0., f32::max);
+    for d in &mut dist {
+        if d.is_infinite() {
+            *d = max + gap;
+        }
+    }
+    dist
+}
+
+/// Moves nodes sharing a location apart along a spiral, the majorization cannot split them.
+/// Fixed nodes stay, the others move away from them.
+fn separate_coincident(pos: &mut [Vec2], fixed: &[bool], step: f32) {
+    let mut seen: HashSet<_> = pos
+        .iter()
+        .zip(fixed)
+        .filter(|(_, &f)| f)
+        .map(|(p, _)| (p.x.to_bits(), p.y.to_bits()))
+        .collect();
+    let mut k = 0;
+    for (p, _) in pos.iter_mut().zip(fixed).filter(|(_, &f)| !f) {
+        while !seen.insert((p.x.to_bits(), p.y.to_bits())) {
+            k += 1;
+            let angle = k as f32 * 2.399_963; // golden angle
+            *p += Vec2::angled(angle) * step * 0.1 * (k as f32).sqrt();
+        }
+    }
+}
+
+/// Localized stress majorization with weights `d^-2`, nodes not `fixed` are updated in place.
+fn majorize(pos: &mut [Vec2], dist: &[f32], fixed: &[bool], iterations: usize, tolerance: f32) {
+    let n = pos.len();
+    let mut prev = stress(pos, dist);
+    for _ in 0..iterations {
+        for i in (0..n).filter(|&i| !fixed[i]) {
+            let mut sum = Vec2::ZERO;
+            let mut weights = 0.;
+            for j in (0..n).filter(|&j| j != i) {
+                let d = dist[i * n + j];
+                if d <= 0. {
+                    continue;
+                }
+                let w = 1. / (d * d);
+                let delta = pos[i] - pos[j];
+                let len = delta.length();
+                let target = if len > f32::EPSILON {
+                    pos[j] + delta * (d / len)
+                } else {
+                    pos[j]
+                };
+                sum += target * w;
+                weights += w;
+            }
+            if weights > 0. {
+                pos[i] = sum / weights;
+            }
+        }
+
+        let curr = stress(pos, dist);
+        if prev - curr < tolerance * prev {
+            break;
+        }
+        prev = curr;
+    }
+}
+
+fn stress(pos: &[Vec2], dist: &[f32]) -> f32 {
+    let n = pos.len();
+    let mut total = 0.;
+    for i in 0..n {
+        for j in i + 1..n {
+            let d = dist[i * n + j];
+            if d > 0. {
+                let diff = (pos[i] - pos[j]).length() - d;
+                total += diff * diff / (d * d);
+            }
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    fn span(g: &Graph<(), f32>, a: NodeIndex, b: NodeIndex) -> f32 {
+        (g.node(a).unwrap().location() - g.node(b).unwrap().location()).length()
+    }
+
+    #[test]
+    fn distances_follow_shortest_paths() {
+        let mut g: Graph<(), f32> = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..4)
+            .map(|i| g.add_node_with_location((), Pos2::new(i as f32 * 7., (i * i) as f32)))
+            .collect();
+        g.add_edge(n[0], n[1], 1.);
+        g.add_edge(n[1], n[2], 1.);
+        g.add_edge(n[2], n[3], 1.);
+
+        layout_stress(&mut g, &State::default());
+
+        // A path is drawn straight with unit edges.
+        for (a, b, d) in [(0, 1, 60.), (1, 2, 60.), (0, 2, 120.), (0, 3, 180.)] {
+            assert!((span(&g, n[a], n[b]) - d).abs() < 1., "{a}-{b}");
+        }
+    }
+
+    #[test]
+    fn edge_weights_scale_lengths() {
+        let mut g: Graph<(), f32> = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(0., 0.));
+        let b = g.add_node_with_location((), Pos2::new(10., 0.));
+        let c = g.add_node_with_location((), Pos2::new(10., 10.));
+        g.add_edge(a, b, 3.);
+        g.add_edge(b, c, 1.);
+        g.set_edge_weight_fn(|props| props.payload);
+
+        layout_stress(&mut g, &State::default());
+        assert!((span(&g, a, b) - 180.).abs() < 1.);
+        assert!((span(&g, b, c) - 60.).abs() < 1.);
+
+        let st = State {
+            use_edge_weights: false,
+            ..State::default()
+        };
+        layout_stress(&mut g, &st);
+        assert!((span(&g, a, b) - 60.).abs() < 1.);
+    }
+
+    #[test]
+    fn pinned_nodes_stay() {
+        let mut g: Graph<(), f32> = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..3)
+            .map(|i| g.add_node_with_location((), Pos2::new(i as f32 * 5., 0.)))
+            .collect();
+        g.add_edge(n[0], n[1], 1.);
+        g.add_edge(n[1], n[2], 1.);
+        g.set_node_pinned(n[0], true);
+
+        layout_stress(&mut g, &State::default());
+
+        assert_eq!(g.node(n[0]).unwrap().location(), Pos2::ZERO);
+        assert!((span(&g, n[0], n[1]) - 60.).abs() < 1.);
+        assert!((span(&g, n[0], n[2]) - 120.).abs() < 1.);
+    }
+
+    #[test]
+    fn coincident_and_disconnected_nodes_are_separated() {
+        let mut g: Graph<(), f32> = Graph::from(&StableGraph::default());
+        let n: Vec<_> = (0..4)
+            .map(|_| g.add_node_with_location((), Pos2::new(5., 5.)))
+            .collect();
+        g.add_edge(n[0], n[1], 1.);
+        g.add_edge(n[2], n[3], 1.);
+
+        layout_stress(&mut g, &State::default());
+
+        assert!((span(&g, n[0], n[1]) - 60.).abs() < 1.);
+        assert!(span(&g, n[0], n[2]) > 30.);
+        let centroid = n.iter().fold(Vec2::ZERO, |acc, &i| {
+            acc + g.node(i).unwrap().location().to_vec2()
+        }) / 4.;
+        assert!((centroid - Vec2::splat(5.)).length() < 1e-3);
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use egui::Pos2;
+use petgraph::{stable_graph::IndexType, stable_graph::NodeIndex, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{DisplayEdge, DisplayNode, Graph};
+
+/// Easing curve of animated layout transitions, see [`crate::SettingsAnimation`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slow and speeds up (cubic).
+    EaseIn,
+    /// Starts fast and slows down toward the target (cubic).
+    EaseOut,
+    /// Slow at both ends (cubic).
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    /// Eased progress for linear progress `t` in `0..=1`.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1. - (1. - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (-2. * t + 2.).powi(3) / 2.
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Tween {
+    from: Pos2,
+    to: Pos2,
+    /// Location written to the node in the last frame, anything else means the node was moved
+    /// outside of the layout, e.g. dragged.
+    shown: Pos2,
+}
+
+/// Tweens of a running layout transition, kept between frames of a `GraphView`.
+///
+/// Nodes hold their shown locations while a transition runs. Before every layout step the
+/// targets are restored so layouts always work on their own results, afterwards the shown
+/// locations are written back. Nodes are keyed by index so the state can live in egui memory.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Transition {
+    /// Whether the last layout step moved any node, None before the first step.
+    moved: Option<bool>,
+    /// Time the running transition started at, in seconds.
+    start: f64,
+    tweens: HashMap<usize, Tween>,
+}
+
+impl Transition {
+    /// Puts tweened nodes at their targets and returns the locations of all nodes before the
+    /// layout step. Tweens of nodes moved since the last frame or removed are dropped.
+    pub fn restore<N, E, Ty, Ix, Dn, De>(
+        &mut self,
+        g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    ) -> Vec<(NodeIndex<Ix>, Pos2)>
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.tweens
+            .retain(|&idx, tween| match g.node_mut(NodeIndex::new(idx)) {
+                Some(n) if n.location() == tween.shown => {
+                    n.set_location(tween.to);
+                    true
+                }
+                _ => false,
+            });
+        g.nodes_iter().map(|(idx, n)| (idx, n.location())).collect()
+    }
+
+    /// Starts or updates the transition after a layout step and moves nodes to their shown
+    /// locations at time `now`.
+    ///
+    /// A step moving nodes after a step which did not starts a new transition from where the
+    /// nodes are shown. Steps during a transition only retarget its tweens, so continuous
+    /// layouts moving nodes every frame ease in once and then follow without delay.
+    pub fn step<N, E, Ty, Ix, Dn, De>(
+        &mut self,
+        g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+        before: &[(NodeIndex<Ix>, Pos2)],
+        now: f64,
+        duration: f32,
+        easing: Easing,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let moved: Vec<(NodeIndex<Ix>, Pos2, Pos2)> = before
+            .iter()
+            .filter_map(|&(idx, from)| {
+                let to = g.node(idx)?.location();
+                (to != from).then_some((idx, from, to))
+            })
+            .collect();
+
+        if self.moved == Some(false) && !moved.is_empty() {
+            for tween in self.tweens.values_mut() {
+                tween.from = tween.shown;
+            }
+            for &(idx, from, to) in &moved {
+                let from = self.tweens.get(&idx.index()).map_or(from, |t| t.shown);
+                self.tweens.insert(
+                    idx.index(),
+                    Tween {
+                        from,
+                        to,
+                        shown: from,
+                    },
+                );
+            }
+            self.start = now;
+        } else {
+            for &(idx, _, to) in &moved {
+                if let Some(tween) = self.tweens.get_mut(&idx.index()) {
+                    tween.to = to;
+                }
+            }
+        }
+        self.moved = Some(!moved.is_empty());
+
+        let t = if duration > 0. {
+            ((now - self.start) as f32 / duration).min(1.)
+        } else {
+            1.
+        };
+        if t >= 1. {
+            self.tweens.clear();
+            return;
+        }
+        let e = easing.apply(t);
+        for (&idx, tween) in &mut self.tweens {
+            tween.shown = tween.from.lerp(tween.to, e);
+            if let Some(n) = g.node_mut(NodeIndex::new(idx)) {
+                n.set_location(tween.shown);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+
+    #[test]
+    fn jumps_are_tweened_from_where_nodes_are_shown() {
+        let mut g: Graph = Graph::from(&StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::ZERO);
+        let at = |g: &Graph| g.node(a).unwrap().location();
+        let mut tr = Transition::default();
+        let mut frame = |g: &mut Graph, target: Option<Pos2>, now: f64| {
+            let before = tr.restore(g);
+            if let Some(p) = target {
+                g.node_mut(a).unwrap().set_location(p);
+            }
+            tr.step(g, &before, now, 1., Easing::Linear);
+        };
+
+        // The first step is never animated, it has nothing to transition from.
+        frame(&mut g, Some(Pos2::new(10., 0.)), 0.);
+        assert_eq!(at(&g), Pos2::new(10., 0.));
+        frame(&mut g, None, 0.1);
+
+        // A one-shot layout jumps to 110 and the node travels there.
+        frame(&mut g, Some(Pos2::new(110., 0.)), 1.);
+        assert_eq!(at(&g), Pos2::new(10., 0.));
+        frame(&mut g, None, 1.25);
+        assert_eq!(at(&g), Pos2::new(35., 0.));
+        // Re-run mid-way: a new transition starts where the node is shown.
+        frame(&mut g, Some(Pos2::new(210., 0.)), 1.5);
+        assert_eq!(at(&g), Pos2::new(35., 0.));
+        // Moves right after retarget it keeping the timing.
+        frame(&mut g, Some(Pos2::new(215., 0.)), 2.);
+        assert_eq!(at(&g), Pos2::new(125., 0.));
+        frame(&mut g, None, 2.5);
+        assert_eq!(at(&g), Pos2::new(215., 0.));
+
+        // Dragged mid-transition: the node stays where the user put it.
+        frame(&mut g, None, 2.6);
+        frame(&mut g, Some(Pos2::new(0., 0.)), 3.);
+        g.node_mut(a).unwrap().set_location(Pos2::new(-5., -5.));
+        frame(&mut g, None, 3.5);
+        assert_eq!(at(&g), Pos2::new(-5., -5.));
+    }
+
+    #[test]
+    fn easing_curves_hit_the_ends() {
+        for e in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(e.apply(0.), 0.);
+            assert_eq!(e.apply(1.), 1.);
+        }
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    }
+}
@@ -2,18 +2,25 @@ mod draw;
 mod elements;
 mod graph;
 mod graph_view;
+mod groups;
 mod helpers;
+mod history;
 mod layouts;
 mod metadata;
 mod settings;
+mod spatial_index;
 
-pub use draw::{DefaultEdgeShape, DefaultNodeShape, DisplayEdge, DisplayNode, DrawContext};
-pub use elements::{Edge, EdgeProps, Node, NodeProps};
-pub use graph::Graph;
+pub use draw::{
+    render_image, render_shapes, DefaultEdgeShape, DefaultNodeShape, DisplayEdge, DisplayNode,
+    DrawContext, RenderOptions, RenderView,
+};
+pub use elements::{Edge, EdgeProps, Node, NodeProps, Port, PortKind};
+pub use graph::{EdgeFilter, EdgeWeightFn, Graph, NodeClusterFn, NodeFilter};
 pub use graph_view::{
     get_layout_state, get_metrics, reset, reset_layout, set_layout_state, DefaultGraphView,
     GraphView,
 };
+pub use groups::{Group, GroupId};
 #[allow(deprecated)]
 pub use helpers::{
     add_edge, add_edge_custom, add_node, add_node_custom, default_edge_transform,
@@ -21,20 +28,43 @@ pub use helpers::{
     generate_simple_ungraph, node_size, to_graph, to_graph_custom,
 };
 
+pub use layouts::circular::{Circular as LayoutCircular, State as LayoutStateCircular};
 pub use layouts::force_directed::{
-    CenterGravity, CenterGravityParams, Extra, ForceAlgorithm,
-    ForceDirected as LayoutForceDirected, FruchtermanReingold, FruchtermanReingoldState,
-    FruchtermanReingoldWithCenterGravity, FruchtermanReingoldWithCenterGravityState,
-    FruchtermanReingoldWithExtras, FruchtermanReingoldWithExtrasState,
+    BoundingBox, BoundingBoxParams, BuiltinExtras, CenterGravity, CenterGravityParams,
+    ClusterAttraction, ClusterAttractionParams, Collision, CollisionParams, EdgeWeighting, Extra,
+    ForceAlgorithm, ForceAtlas2, ForceAtlas2State, ForceDirected as LayoutForceDirected,
+    FruchtermanReingold, FruchtermanReingoldState, FruchtermanReingoldWithBuiltinExtras,
+    FruchtermanReingoldWithBuiltinExtrasState, FruchtermanReingoldWithCenterGravity,
+    FruchtermanReingoldWithCenterGravityState, FruchtermanReingoldWithExtras,
+    FruchtermanReingoldWithExtrasState, RadialConstraint, RadialConstraintParams, RadialMetric,
+};
+pub use layouts::grid::{
+    Grid as LayoutGrid, Orientation as LayoutGridOrientation, State as LayoutStateGrid,
 };
 pub use layouts::hierarchical::{
-    Hierarchical as LayoutHierarchical, Orientation as LayoutHierarchicalOrientation,
-    State as LayoutStateHierarchical,
+    CrossingHeuristic as LayoutHierarchicalCrossingHeuristic, Hierarchical as LayoutHierarchical,
+    Layering as LayoutHierarchicalLayering, Mode as LayoutHierarchicalMode,
+    Orientation as LayoutHierarchicalOrientation, Radial as LayoutRadial,
+    RadialState as LayoutStateRadial, State as LayoutStateHierarchical,
 };
 pub use layouts::random::{Random as LayoutRandom, State as LayoutStateRandom};
-pub use layouts::{Layout, LayoutState};
+pub use layouts::shell::{Shell as LayoutShell, State as LayoutStateShell};
+pub use layouts::stress::{State as LayoutStateStress, Stress as LayoutStress};
+pub use layouts::{Easing as LayoutEasing, Layout, LayoutState, NodeKey as LayoutNodeKey};
 pub use metadata::{reset_metadata, MetadataFrame};
-pub use settings::{SettingsInteraction, SettingsNavigation, SettingsStyle};
+pub use settings::{SettingsAnimation, SettingsInteraction, SettingsNavigation, SettingsStyle};
+
+#[cfg(feature = "background")]
+pub use layouts::force_directed::{BackgroundLayout, SyncLayout};
 
 #[cfg(feature = "events")]
 pub mod events;
+
+#[cfg(any(
+    feature = "json",
+    feature = "graphml",
+    feature = "dot",
+    feature = "svg",
+    feature = "png"
+))]
+pub mod io;
@@ -1,3 +1,5 @@
+use crate::LayoutEasing;
+
 /// Represents graph interaction settings.
 ///
 /// Master-child semantics:
@@ -16,6 +18,7 @@ pub struct SettingsInteraction {
     pub(crate) edge_clicking_enabled: bool,
     pub(crate) edge_selection_enabled: bool,
     pub(crate) edge_selection_multi_enabled: bool,
+    pub(crate) pin_dragged_nodes: bool,
 }
 
 impl Default for SettingsInteraction {
@@ -29,6 +32,7 @@ impl Default for SettingsInteraction {
             edge_clicking_enabled: false,
             edge_selection_enabled: false,
             edge_selection_multi_enabled: false,
+            pin_dragged_nodes: false,
         }
     }
 }
@@ -115,6 +119,15 @@ impl SettingsInteraction {
         self.edge_selection_multi_enabled = enabled;
         self
     }
+
+    /// Pins a node once the user drags it so layouts keep it where it was dropped. The pin
+    /// is part of the drag and undone with it.
+    ///
+    /// Default: `false`
+    pub fn with_pin_dragged_nodes(mut self, enabled: bool) -> Self {
+        self.pin_dragged_nodes = enabled;
+        self
+    }
 }
 
 /// Represents graph navigation settings.
@@ -272,3 +285,46 @@ pub type NodeStrokeHook = std::sync::Arc<
 /// Type alias for the edge stroke hook closure to keep type signatures concise.
 pub type EdgeStrokeHook =
     std::sync::Arc<dyn Fn(bool, usize, egui::Stroke, &egui::Style) -> egui::Stroke + Send + Sync>;
+
+/// `SettingsAnimation` stores settings of animated layout transitions.
+///
+/// When a layout moves nodes after they stood still, e.g. after switching layouts or re-running
+/// a one-shot layout, nodes tween from their previous to their new locations instead of
+/// jumping. Continuous layouts moving nodes every frame are not slowed down.
+#[derive(Debug, Clone)]
+pub struct SettingsAnimation {
+    pub(crate) transition_duration: f32,
+    pub(crate) transition_easing: LayoutEasing,
+}
+
+impl Default for SettingsAnimation {
+    fn default() -> Self {
+        Self {
+            transition_duration: 0.,
+            transition_easing: LayoutEasing::default(),
+        }
+    }
+}
+
+impl SettingsAnimation {
+    /// Creates new [`SettingsAnimation`] with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Duration of layout transitions in seconds, 0 moves nodes instantly.
+    ///
+    /// Default: `0.0`
+    pub fn with_transition_duration(mut self, seconds: f32) -> Self {
+        self.transition_duration = seconds;
+        self
+    }
+
+    /// Easing curve of layout transitions.
+    ///
+    /// Default: [`LayoutEasing::EaseInOut`]
+    pub fn with_transition_easing(mut self, easing: LayoutEasing) -> Self {
+        self.transition_easing = easing;
+        self
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::{Pos2, Rect};
+use petgraph::stable_graph::{EdgeIndex, IndexType, NodeIndex};
+
+/// Default size of a grid cell in canvas units.
+pub(crate) const DEFAULT_CELL_SIZE: f32 = 50.0;
+
+/// Edges whose bounding rect spans more cells than this are kept in a separate list and
+/// checked on every query instead of being spread over the grid.
+const MAX_EDGE_CELLS: i64 = 256;
+
+/// How many rings of cells the nearest-node search walks before falling back to a linear scan.
+const MAX_NEAREST_RINGS: i32 = 64;
+
+type Cell = (i32, i32);
+
+#[derive(Debug, Clone, Copy)]
+struct NodeEntry {
+    pos: Pos2,
+    extent: f32,
+    cell: Cell,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EdgeEntry {
+    rect: Rect,
+    min: Cell,
+    max: Cell,
+    wide: bool,
+}
+
+/// Uniform grid over canvas coordinates used to speed up node and edge hit-testing.
+///
+/// Nodes are bucketed by their location, edges by the bounding rect of their geometry.
+/// The index is maintained incrementally by [`crate::Graph`] mutators. Once per frame
+/// [`crate::GraphView`] reindexes the elements layouts changed through the graph accessors.
+#[derive(Debug, Clone)]
+pub(crate) struct SpatialIndex<Ix: IndexType> {
+    cell_size: f32,
+    /// Largest node extent seen since the last rebuild. Used to widen point queries.
+    max_extent: f32,
+
+    nodes: HashMap<NodeIndex<Ix>, NodeEntry>,
+    node_cells: HashMap<Cell, Vec<NodeIndex<Ix>>>,
+
+    edges: HashMap<EdgeIndex<Ix>, EdgeEntry>,
+    edge_cells: HashMap<Cell, Vec<EdgeIndex<Ix>>>,
+    wide_edges: HashSet<EdgeIndex<Ix>>,
+}
+
+impl<Ix: IndexType> Default for SpatialIndex<Ix> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl<Ix: IndexType> SpatialIndex<Ix> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: sanitize_cell_size(cell_size),
+            max_extent: 0.,
+            nodes: HashMap::new(),
+            node_cells: HashMap::new(),
+            edges: HashMap::new(),
+            edge_cells: HashMap::new(),
+            wide_edges: HashSet::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Drops all entries and sets a new cell size.
+    pub fn reset(&mut self, cell_size: f32) {
+        *self = Self::new(cell_size);
+    }
+
+    pub fn nodes_len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edges_len(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Returns true if the node is not indexed or was indexed with different geometry.
+    pub fn node_changed(&self, idx: NodeIndex<Ix>, pos: Pos2, extent: f32) -> bool {
+        match self.nodes.get(&idx) {
+            Some(e) => e.pos != pos || e.extent != extent,
+            None => true,
+        }
+    }
+
+    /// Inserts or moves a node.
+    pub fn upsert_node(&mut self, idx: NodeIndex<Ix>, pos: Pos2, extent: f32) {
+        let cell = self.cell_of(pos);
+        if let Some(entry) = self.nodes.get_mut(&idx) {
+            let prev_cell = entry.cell;
+            entry.pos = pos;
+            entry.extent = extent;
+            entry.cell = cell;
+            if prev_cell != cell {
+                remove_from_bucket(&mut self.node_cells, prev_cell, idx);
+                self.node_cells.entry(cell).or_default().push(idx);
+            }
+        } else {
+            self.nodes.insert(idx, NodeEntry { pos, extent, cell });
+            self.node_cells.entry(cell).or_default().push(idx);
+        }
+
+        if extent.is_finite() && extent > self.max_extent {
+            self.max_extent = extent;
+        }
+    }
+
+    pub fn remove_node(&mut self, idx: NodeIndex<Ix>) {
+        if let Some(entry) = self.nodes.remove(&idx) {
+            remove_from_bucket(&mut self.node_cells, entry.cell, idx);
+        }
+    }
+
+    /// Inserts or replaces an edge covering `rect` (canvas coordinates).
+    pub fn upsert_edge(&mut self, idx: EdgeIndex<Ix>, rect: Rect) {
+        if self.edges.get(&idx).is_some_and(|e| e.rect == rect) {
+            return;
+        }
+        self.remove_edge(idx);
+
+        let min = self.cell_of(rect.min);
+        let max = self.cell_of(rect.max);
+        let cells =
+            (i64::from(max.0) - i64::from(min.0) + 1) * (i64::from(max.1) - i64::from(min.1) + 1);
+        let wide = !(1..=MAX_EDGE_CELLS).contains(&cells);
+
+        if wide {
+            self.wide_edges.insert(idx);
+        } else {
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    self.edge_cells.entry((x, y)).or_default().push(idx);
+                }
+            }
+        }
+
+        self.edges.insert(
+            idx,
+            EdgeEntry {
+                rect,
+                min,
+                max,
+                wide,
+            },
+        );
+    }
+
+    pub fn remove_edge(&mut self, idx: EdgeIndex<Ix>) {
+        let Some(entry) = self.edges.remove(&idx) else {
+            return;
+        };
+
+        if entry.wide {
+            self.wide_edges.remove(&idx);
+            return;
+        }
+
+        for x in entry.min.0..=entry.max.0 {
+            for y in entry.min.1..=entry.max.1 {
+                remove_from_bucket(&mut self.edge_cells, (x, y), idx);
+            }
+        }
+    }
+
+    /// Nodes which may contain `pos`: every node whose center lies within the largest known
+    /// node extent plus `slack` from `pos`.
+    pub fn node_candidates(&self, pos: Pos2, slack: f32) -> Vec<NodeIndex<Ix>> {
+        let reach = self.max_extent + slack.max(0.);
+        let rect = Rect::from_center_size(pos, egui::Vec2::splat(reach * 2.));
+        self.nodes_in_rect(rect)
+    }
+
+    /// Edges whose indexed bounding rect contains `pos`.
+    pub fn edge_candidates(&self, pos: Pos2) -> Vec<EdgeIndex<Ix>> {
+        let mut res: Vec<EdgeIndex<Ix>> = self
+            .edge_cells
+            .get(&self.cell_of(pos))
+            .map(|bucket| {
+                bucket
+                    .iter()
+                    .copied()
+                    .filter(|idx| self.edges[idx].rect.contains(pos))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        res.extend(
+            self.wide_edges
+                .iter()
+                .copied()
+                .filter(|idx| self.edges[idx].rect.contains(pos)),
+        );
+
+        res
+    }
+
+    /// Nodes whose indexed location lies inside `rect`.
+    pub fn nodes_in_rect(&self, rect: Rect) -> Vec<NodeIndex<Ix>> {
+        if !is_valid_rect(rect) {
+            return Vec::new();
+        }
+
+        let min = self.cell_of(rect.min);
+        let max = self.cell_of(rect.max);
+        let cells =
+            (i64::from(max.0) - i64::from(min.0) + 1) * (i64::from(max.1) - i64::from(min.1) + 1);
+
+        // When the rect covers more cells than there are nodes, scanning the nodes is cheaper.
+        if cells > self.nodes.len() as i64 {
+            return self
+                .nodes
+                .iter()
+                .filter(|(_, e)| rect.contains(e.pos))
+                .map(|(idx, _)| *idx)
+                .collect();
+        }
+
+        let mut res = Vec::new();
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                let Some(bucket) = self.node_cells.get(&(x, y)) else {
+                    continue;
+                };
+                res.extend(
+                    bucket
+                        .iter()
+                        .copied()
+                        .filter(|idx| rect.contains(self.nodes[idx].pos)),
+                );
+            }
+        }
+        res
+    }
+
+    /// Node whose indexed location is the closest to `pos`.
+    pub fn nearest_node(&self, pos: Pos2) -> Option<NodeIndex<Ix>> {
+        if self.nodes.is_empty() || !pos.x.is_finite() || !pos.y.is_finite() {
+            return None;
+        }
+
+        let center = self.cell_of(pos);
+        let mut best: Option<(f32, NodeIndex<Ix>)> = None;
+        for ring in 0..=MAX_NEAREST_RINGS {
+            for cell in ring_cells(center, ring) {
+                let Some(bucket) = self.node_cells.get(&cell) else {
+                    continue;
+                };
+                for idx in bucket {
+                    let d = self.nodes[idx].pos.distance_sq(pos);
+                    if best.is_none_or(|(best_d, best_idx)| {
+                        d < best_d || (d == best_d && *idx < best_idx)
+                    }) {
+                        best = Some((d, *idx));
+                    }
+                }
+            }
+
+            // Any node in the next ring is at least `ring * cell_size` away.
+            if let Some((best_d, _)) = best {
+                let guaranteed = ring as f32 * self.cell_size;
+                if best_d <= guaranteed * guaranteed {
+                    return best.map(|(_, idx)| idx);
+                }
+            }
+        }
+
+        // Sparse index: nodes are far away from the query point, fall back to a linear scan.
+        self.nodes
+            .iter()
+            .map(|(idx, e)| (e.pos.distance_sq(pos), *idx))
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+            .map(|(_, idx)| idx)
+    }
+
+    fn cell_of(&self, pos: Pos2) -> Cell {
+        (
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+fn sanitize_cell_size(cell_size: f32) -> f32 {
+    if cell_size.is_finite() && cell_size > 0. {
+        cell_size
+    } else {
+        DEFAULT_CELL_SIZE
+    }
+}
+
+fn is_valid_rect(rect: Rect) -> bool {
+    rect.min.x.is_finite()
+        && rect.min.y.is_finite()
+        && rect.max.x.is_finite()
+        && rect.max.y.is_finite()
+        && rect.min.x <= rect.max.x
+        && rect.min.y <= rect.max.y
+}
+
+fn remove_from_bucket<K: Copy + PartialEq>(cells: &mut HashMap<Cell, Vec<K>>, cell: Cell, item: K) {
+    let Some(bucket) = cells.get_mut(&cell) else {
+        return;
+    };
+    if let Some(pos) = bucket.iter().position(|i| *i == item) {
+        bucket.swap_remove(pos);
+    }
+    if bucket.is_empty() {
+        cells.remove(&cell);
+    }
+}
+
+/// Cells on the border of the square with the given Chebyshev radius around `center`.
+fn ring_cells(center: Cell, ring: i32) -> Vec<Cell> {
+    if ring == 0 {
+        return vec![center];
+    }
+
+    let (cx, cy) = center;
+    let mut res = Vec::with_capacity((ring * 8) as usize);
+    for x in (cx - ring)..=(cx + ring) {
+        res.push((x, cy - ring));
+        res.push((x, cy + ring));
+    }
+    for y in (cy - ring + 1)..=(cy + ring - 1) {
+        res.push((cx - ring, y));
+        res.push((cx + ring, y));
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::stable_graph::DefaultIx;
+
+    fn n(i: usize) -> NodeIndex<DefaultIx> {
+        NodeIndex::new(i)
+    }
+
+    #[test]
+    fn moving_node_updates_buckets() {
+        let mut index = SpatialIndex::<DefaultIx>::new(10.);
+        index.upsert_node(n(0), Pos2::new(1., 1.), 5.);
+        assert_eq!(
+            index.nodes_in_rect(Rect::from_min_max(Pos2::ZERO, Pos2::new(5., 5.))),
+            vec![n(0)]
+        );
+
+        index.upsert_node(n(0), Pos2::new(101., 101.), 5.);
+        assert!(index
+            .nodes_in_rect(Rect::from_min_max(Pos2::ZERO, Pos2::new(5., 5.)))
+            .is_empty());
+        assert_eq!(
+            index.nodes_in_rect(Rect::from_min_max(
+                Pos2::new(100., 100.),
+                Pos2::new(105., 105.)
+            )),
+            vec![n(0)]
+        );
+
+        index.remove_node(n(0));
+        assert_eq!(index.nodes_len(), 0);
+        assert!(index.node_cells.is_empty());
+    }
+
+    #[test]
+    fn nearest_node_walks_rings_and_falls_back() {
+        let mut index = SpatialIndex::<DefaultIx>::new(10.);
+        index.upsert_node(n(0), Pos2::new(0., 0.), 5.);
+        index.upsert_node(n(1), Pos2::new(24., 0.), 5.);
+        index.upsert_node(n(2), Pos2::new(5000., 5000.), 5.);
+
+        assert_eq!(index.nearest_node(Pos2::new(15., 0.)), Some(n(1)));
+        assert_eq!(index.nearest_node(Pos2::new(4000., 4000.)), Some(n(2)));
+    }
+
+    #[test]
+    fn edge_candidates_include_wide_edges() {
+        let mut index = SpatialIndex::<DefaultIx>::new(10.);
+        let short = EdgeIndex::new(0);
+        let wide = EdgeIndex::new(1);
+        index.upsert_edge(short, Rect::from_min_max(Pos2::ZERO, Pos2::new(15., 15.)));
+        index.upsert_edge(
+            wide,
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1000., 1000.)),
+        );
+
+        let mut found = index.edge_candidates(Pos2::new(12., 12.));
+        found.sort();
+        assert_eq!(found, vec![short, wide]);
+        assert_eq!(index.edge_candidates(Pos2::new(500., 500.)), vec![wide]);
+
+        index.remove_edge(wide);
+        assert!(index.edge_candidates(Pos2::new(500., 500.)).is_empty());
+    }
+}